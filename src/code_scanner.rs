@@ -0,0 +1,204 @@
+use anyhow::Result;
+use image::imageops::FilterType;
+use image::DynamicImage;
+use rxing::{BarcodeFormat, DecodeHints, Exceptions};
+use std::collections::HashSet;
+use std::path::Path;
+use tracing::info;
+
+// QR and the 1D symbologies found on products, tickets and labels
+const FORMATS: [(BarcodeFormat, &str); 10] = [
+    (BarcodeFormat::QR_CODE, "QR code"),
+    (BarcodeFormat::EAN_13, "EAN-13"),
+    (BarcodeFormat::EAN_8, "EAN-8"),
+    (BarcodeFormat::UPC_A, "UPC-A"),
+    (BarcodeFormat::UPC_E, "UPC-E"),
+    (BarcodeFormat::CODE_128, "Code 128"),
+    (BarcodeFormat::CODE_39, "Code 39"),
+    (BarcodeFormat::CODE_93, "Code 93"),
+    (BarcodeFormat::CODABAR, "Codabar"),
+    (BarcodeFormat::ITF, "ITF"),
+];
+
+// Codes smaller than this on screen get a second try enlarged
+const UPSCALE_BELOW: u32 = 400;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedCode {
+    pub format: &'static str,
+    pub text: String,
+}
+
+impl ScannedCode {
+    // Only web links get an "Open" action, and only when asked for
+    pub fn url(&self) -> Option<&str> {
+        let text = self.text.trim();
+        let lower = text.to_ascii_lowercase();
+        (lower.starts_with("https://") || lower.starts_with("http://")).then_some(text)
+    }
+
+    // Readable summary for payloads with a known structure
+    pub fn description(&self) -> Option<String> {
+        let fields = wifi_fields(self.text.strip_prefix("WIFI:")?);
+        let field = |key: &str| fields.iter().find_map(|f| f.strip_prefix(key)).map(str::to_string);
+        let ssid = field("S:")?;
+        let security = field("T:").filter(|t| !t.is_empty()).unwrap_or_else(|| "open".to_string());
+        Some(format!("Wi-Fi network \"{}\" ({})", ssid, security))
+    }
+}
+
+// `S:name;T:WPA;P:pass;;` split on unescaped semicolons, with `\` escapes
+// removed
+fn wifi_fields(payload: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut chars = payload.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.extend(chars.next()),
+            ';' => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        fields.push(current);
+    }
+    fields
+}
+
+fn decode_luma(image: &DynamicImage) -> Result<Vec<ScannedCode>> {
+    let luma = image.to_luma8();
+    let (width, height) = luma.dimensions();
+    let mut hints = DecodeHints {
+        PossibleFormats: Some(FORMATS.iter().map(|(format, _)| *format).collect()),
+        TryHarder: Some(true),
+        // Light-on-dark codes, as on dark themes
+        AlsoInverted: Some(true),
+        ..Default::default()
+    };
+    let results = match rxing::helpers::detect_multiple_in_luma_with_hints(luma.into_raw(), width, height, &mut hints) {
+        Ok(results) => results,
+        Err(Exceptions::NotFoundException(_)) => Vec::new(),
+        Err(e) => return Err(anyhow::anyhow!("failed to decode: {}", e)),
+    };
+    let mut seen = HashSet::new();
+    Ok(results
+        .iter()
+        .filter_map(|result| {
+            let format = FORMATS.iter().find(|(f, _)| f == result.getBarcodeFormat())?.1;
+            let text = result.getText().to_string();
+            seen.insert((format, text.clone())).then_some(ScannedCode { format, text })
+        })
+        .collect())
+}
+
+// Every code in the image, in no particular order
+pub fn decode(image: &DynamicImage) -> Result<Vec<ScannedCode>> {
+    let codes = decode_luma(image)?;
+    if !codes.is_empty() || image.width().max(image.height()) >= UPSCALE_BELOW {
+        return Ok(codes);
+    }
+    // Small selections around a small code decode better with sharp,
+    // larger modules
+    decode_luma(&image.resize(image.width() * 3, image.height() * 3, FilterType::Nearest))
+}
+
+pub async fn scan_file(path: &Path) -> Result<Vec<ScannedCode>> {
+    let path = path.to_path_buf();
+    let codes = tokio::task::spawn_blocking(move || decode(&image::open(&path)?)).await??;
+    info!("Found {} codes", codes.len());
+    Ok(codes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+    use rxing::{MultiFormatWriter, Writer};
+
+    // Black on white with `module` pixels per bar or square; the writers
+    // include the quiet zone
+    fn render(format: BarcodeFormat, contents: &str, width: u32, height: u32, module: u32) -> GrayImage {
+        let matrix = MultiFormatWriter.encode(contents, &format, width as i32, height as i32).unwrap();
+        GrayImage::from_fn(matrix.getWidth() * module, matrix.getHeight() * module, |x, y| {
+            if matrix.get(x / module, y / module) {
+                Luma([0])
+            } else {
+                Luma([255])
+            }
+        })
+    }
+
+    fn sorted(mut codes: Vec<ScannedCode>) -> Vec<ScannedCode> {
+        codes.sort_by(|a, b| a.format.cmp(b.format));
+        codes
+    }
+
+    fn code(format: &'static str, text: &str) -> ScannedCode {
+        ScannedCode { format, text: text.to_string() }
+    }
+
+    #[test]
+    fn decodes_qr_and_barcodes_side_by_side() {
+        let mut canvas = GrayImage::from_pixel(1400, 500, Luma([255]));
+        image::imageops::overlay(&mut canvas, &render(BarcodeFormat::QR_CODE, "https://example.com/a", 300, 300, 1), 20, 20);
+        image::imageops::overlay(&mut canvas, &render(BarcodeFormat::CODE_128, "HXD-0042", 400, 120, 1), 400, 40);
+        image::imageops::overlay(&mut canvas, &render(BarcodeFormat::EAN_13, "5901234123457", 400, 120, 1), 400, 300);
+
+        let codes = decode(&DynamicImage::ImageLuma8(canvas)).unwrap();
+        assert_eq!(
+            sorted(codes),
+            vec![
+                code("Code 128", "HXD-0042"),
+                code("EAN-13", "5901234123457"),
+                code("QR code", "https://example.com/a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_light_on_dark() {
+        let mut qr = render(BarcodeFormat::QR_CODE, "dark theme", 300, 300, 1);
+        image::imageops::invert(&mut qr);
+        assert_eq!(decode(&DynamicImage::ImageLuma8(qr)).unwrap(), vec![code("QR code", "dark theme")]);
+    }
+
+    #[test]
+    fn small_selections_are_enlarged() {
+        // 1.5 pixels per module, smoothed as at a fractional output scale:
+        // too blurry to find as captured
+        let sharp = render(BarcodeFormat::QR_CODE, "tiny", 0, 0, 8);
+        let qr = DynamicImage::ImageLuma8(sharp).resize_exact(44, 44, FilterType::Triangle);
+        assert!(decode_luma(&qr).unwrap().is_empty());
+        assert_eq!(decode(&qr).unwrap(), vec![code("QR code", "tiny")]);
+    }
+
+    #[test]
+    fn blank_image_has_no_codes() {
+        let blank = DynamicImage::ImageLuma8(GrayImage::from_pixel(200, 200, Luma([255])));
+        assert!(decode(&blank).unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_web_links_open() {
+        assert_eq!(code("QR code", " HTTPS://Example.com/x ").url(), Some("HTTPS://Example.com/x"));
+        assert_eq!(code("QR code", "http://localhost").url(), Some("http://localhost"));
+        assert_eq!(code("QR code", "javascript:alert(1)").url(), None);
+        assert_eq!(code("QR code", "file:///etc/passwd").url(), None);
+        assert_eq!(code("QR code", "example.com").url(), None);
+    }
+
+    #[test]
+    fn wifi_payloads_are_described() {
+        let escaped = code("QR code", r#"WIFI:T:WPA;S:Cafe\;Bar\\;P:p\:ss\;word;;"#);
+        assert_eq!(escaped.description().as_deref(), Some(r#"Wi-Fi network "Cafe;Bar\" (WPA)"#));
+        assert_eq!(
+            wifi_fields(r#"T:WPA;S:Cafe\;Bar\\;P:p\:ss\;word;;"#),
+            vec!["T:WPA", r"S:Cafe;Bar\", "P:p:ss;word", ""]
+        );
+        let open = code("QR code", "WIFI:S:Guest;T:;;");
+        assert_eq!(open.description().as_deref(), Some(r#"Wi-Fi network "Guest" (open)"#));
+        assert_eq!(code("QR code", "WIFI:T:WPA;P:x;;").description(), None);
+        assert_eq!(code("QR code", "hello").description(), None);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::display_manager::iio_sensor::IioSensorProxy;
+use crate::display_manager::monitor_control;
+use anyhow::Result;
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use std::process::Stdio;
+use std::sync::Mutex;
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use zbus::Connection;
+
+// Default lux -> brightness (0.0-1.0) curve: dim room, office, bright
+// indoor, overcast daylight, direct sun
+pub const DEFAULT_CURVE: [[f64; 2]; 5] = [[0.0, 0.05], [10.0, 0.25], [100.0, 0.5], [1000.0, 0.8], [10000.0, 1.0]];
+
+// Brightness for a lux reading, interpolated between curve points on a
+// log scale (perceived brightness is roughly logarithmic in lux)
+pub fn curve_brightness(curve: &[[f64; 2]], lux: f64) -> f64 {
+    let log = |x: f64| (x.max(0.0) + 1.0).ln();
+    let Some(first) = curve.first() else {
+        return 1.0;
+    };
+    if lux <= first[0] {
+        return first[1].clamp(0.0, 1.0);
+    }
+    for pair in curve.windows(2) {
+        let ([lux_a, b_a], [lux_b, b_b]) = (pair[0], pair[1]);
+        if lux <= lux_b {
+            let t = (log(lux) - log(lux_a)) / (log(lux_b) - log(lux_a)).max(f64::EPSILON);
+            return (b_a + (b_b - b_a) * t).clamp(0.0, 1.0);
+        }
+    }
+    curve[curve.len() - 1][1].clamp(0.0, 1.0)
+}
+
+// Smooths sensor readings and decides when the backlight should move
+#[derive(Debug, Clone)]
+pub struct BrightnessController {
+    pub curve: Vec<[f64; 2]>,
+    // Exponential moving average weight of a new reading (0-1]
+    pub smoothing: f64,
+    // Minimum change before the backlight is touched again
+    pub hysteresis: f64,
+    // User adjustment on top of the curve, set by moving the slider
+    pub offset: f64,
+    smoothed_lux: Option<f64>,
+    applied: Option<f64>,
+}
+
+impl BrightnessController {
+    pub fn new(curve: Vec<[f64; 2]>) -> Self {
+        Self { curve, smoothing: 0.3, hysteresis: 0.05, offset: 0.0, smoothed_lux: None, applied: None }
+    }
+
+    pub fn target(&self) -> Option<f64> {
+        self.smoothed_lux
+            .map(|lux| (curve_brightness(&self.curve, lux) + self.offset).clamp(0.01, 1.0))
+    }
+
+    // Feed a lux reading; returns the brightness to apply, if it moved
+    // far enough from the last applied value
+    pub fn update(&mut self, lux: f64) -> Option<f64> {
+        let smoothed = match self.smoothed_lux {
+            Some(previous) => previous + self.smoothing * (lux - previous),
+            None => lux,
+        };
+        self.smoothed_lux = Some(smoothed);
+        let target = self.target()?;
+        match self.applied {
+            Some(applied) if (target - applied).abs() < self.hysteresis => None,
+            _ => {
+                self.applied = Some(target);
+                Some(target)
+            }
+        }
+    }
+
+    // The user picked `brightness` by hand: shift the curve so the current
+    // light level maps to it, instead of snapping back on the next reading
+    pub fn manual_adjust(&mut self, brightness: f64) {
+        if let Some(lux) = self.smoothed_lux {
+            self.offset = brightness - curve_brightness(&self.curve, lux);
+        }
+        self.applied = Some(brightness);
+    }
+}
+
+static CONTROLLER: Lazy<Mutex<Option<BrightnessController>>> = Lazy::new(|| Mutex::new(None));
+static BRIGHTNESS_TASK: Lazy<Mutex<Option<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+// Internal panel backlight via brightnessctl
+pub async fn set_backlight(value: f64) -> Result<()> {
+    let pct = (value.clamp(0.0, 1.0) * 100.0).round() as u8;
+    let status = Command::new("brightnessctl")
+        .arg("-q")
+        .arg("set")
+        .arg(format!("{}%", pct))
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("brightnessctl failed"));
+    }
+    Ok(())
+}
+
+async fn apply_brightness(value: f64, include_external: bool) -> Result<()> {
+    info!("Auto-brightness: {:.0}%", value * 100.0);
+    set_backlight(value).await?;
+    if include_external {
+        let monitors = monitor_control::get_monitors().await?;
+        for monitor in monitors.iter().filter(|m| !m.is_internal() && m.is_physical()) {
+            if let Err(e) = monitor_control::set_brightness(monitor, value).await {
+                warn!("DDC brightness failed on {}: {}", monitor.name, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Follow the ambient light sensor until the stream ends. Takes the
+// connection so it can run against a stand-in service on a private bus.
+pub async fn run_auto_brightness(connection: &Connection, include_external: bool) -> Result<()> {
+    let proxy = IioSensorProxy::new(connection).await?;
+    if !proxy.has_ambient_light().await? {
+        return Err(anyhow::anyhow!("No ambient light sensor available"));
+    }
+    if proxy.light_level_unit().await? != "lux" {
+        warn!("Light sensor reports vendor units; the curve may need tuning");
+    }
+    proxy.claim_light().await?;
+    let mut changes = proxy.receive_light_level_changed().await;
+
+    let mut level = Some(proxy.light_level().await?);
+    loop {
+        if let Some(lux) = level {
+            let target = CONTROLLER.lock().unwrap().as_mut().and_then(|c| c.update(lux));
+            if let Some(value) = target {
+                if let Err(e) = apply_brightness(value, include_external).await {
+                    warn!("Failed to apply auto-brightness: {}", e);
+                }
+            }
+        }
+        match changes.next().await {
+            Some(change) => level = change.get().await.ok(),
+            None => break,
+        }
+    }
+
+    let _ = proxy.release_light().await;
+    warn!("Light sensor stream ended.");
+    Ok(())
+}
+
+pub fn start_auto_brightness(curve: Vec<[f64; 2]>, include_external: bool) {
+    let mut task = BRIGHTNESS_TASK.lock().unwrap();
+    if task.as_ref().is_some_and(|t| !t.is_finished()) {
+        return;
+    }
+    *CONTROLLER.lock().unwrap() = Some(BrightnessController::new(curve));
+    *task = Some(tokio::spawn(async move {
+        let result = match Connection::system().await {
+            Ok(connection) => run_auto_brightness(&connection, include_external).await,
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            warn!("Auto-brightness stopped: {}", e);
+        }
+    }));
+}
+
+pub fn stop_auto_brightness() {
+    if let Some(task) = BRIGHTNESS_TASK.lock().unwrap().take() {
+        task.abort();
+    }
+    *CONTROLLER.lock().unwrap() = None;
+}
+
+// Called when the user drags a brightness slider; no-op when auto-brightness is off
+pub fn note_manual_brightness(value: f64) {
+    if let Some(controller) = CONTROLLER.lock().unwrap().as_mut() {
+        controller.manual_adjust(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn curve_interpolates_on_log_scale() {
+        let curve = DEFAULT_CURVE.to_vec();
+        assert!(close(curve_brightness(&curve, 0.0), 0.05));
+        assert!(close(curve_brightness(&curve, 100.0), 0.5));
+        assert!(close(curve_brightness(&curve, 50000.0), 1.0));
+        // Halfway between 10 and 100 lux on a log(x + 1) scale
+        let mid = ((11.0f64.ln() + 101.0f64.ln()) / 2.0).exp() - 1.0;
+        assert!(close(curve_brightness(&curve, mid), 0.375));
+        assert!(close(curve_brightness(&[], 500.0), 1.0));
+        assert!(close(curve_brightness(&[[0.0, 1.5]], 0.0), 1.0));
+    }
+
+    #[test]
+    fn smoothing_and_hysteresis() {
+        let mut controller = BrightnessController::new(DEFAULT_CURVE.to_vec());
+        assert_eq!(controller.update(100.0), Some(0.5));
+        // A brief spike only moves the average part of the way
+        let after_spike = controller.update(1000.0).unwrap();
+        assert!(after_spike > 0.5 && after_spike < 0.8);
+        // A reading that barely moves the target leaves the backlight alone
+        let mut steady = BrightnessController::new(DEFAULT_CURVE.to_vec());
+        steady.update(100.0);
+        assert_eq!(steady.update(101.0), None);
+    }
+
+    #[test]
+    fn manual_adjust_shifts_the_curve() {
+        let mut controller = BrightnessController::new(DEFAULT_CURVE.to_vec());
+        controller.update(100.0);
+        controller.manual_adjust(0.7);
+        assert!(close(controller.offset, 0.2));
+        assert!(close(controller.target().unwrap(), 0.7));
+        // Same light, no change: the user's choice sticks
+        assert_eq!(controller.update(100.0), None);
+        // Darker room: the curve still moves, but from the user's level
+        controller.smoothing = 1.0;
+        assert!(close(controller.update(10.0).unwrap(), 0.45));
+        // The offset never pushes the target off the ends
+        controller.manual_adjust(0.9);
+        assert!(close(controller.update(10000.0).unwrap(), 1.0));
+    }
+}
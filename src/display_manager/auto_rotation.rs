@@ -0,0 +1,278 @@
+use crate::display_manager::iio_sensor::IioSensorProxy;
+use crate::display_manager::input_mapping::{self, InputBinding, InputDevice, InputKind};
+use crate::display_manager::monitor_control;
+use crate::display_manager::monitor_profiles::profile_key;
+use crate::display_manager::monitor_types::Monitor;
+use crate::display_manager::projection;
+use anyhow::Result;
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use zbus::Connection;
+
+static ROTATION_LOCKED: AtomicBool = AtomicBool::new(false);
+static ROTATION_TASK: Lazy<Mutex<Option<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+// (output, orientation) last reported by the sensor, re-applied on unlock
+static LAST_ORIENTATION: Lazy<Mutex<Option<(String, String)>>> = Lazy::new(|| Mutex::new(None));
+
+// Map an iio-sensor-proxy orientation to a Hyprland transform.
+// "undefined" (device lying flat) keeps the current rotation.
+pub fn orientation_transform(orientation: &str) -> Option<i32> {
+    match orientation {
+        "normal" => Some(0),
+        "left-up" => Some(1),
+        "bottom-up" => Some(2),
+        "right-up" => Some(3),
+        _ => None,
+    }
+}
+
+// Point touchscreens and tablets at the rotated output with its transform.
+// Devices with a binding of their own keep their output, and only follow
+// the rotation when they are bound to this output.
+pub fn rotation_input_commands(
+    output: &Monitor,
+    transform: i32,
+    devices: &[(InputKind, InputDevice)],
+    bindings: &[InputBinding],
+) -> Vec<String> {
+    let mut commands = Vec::new();
+    for (_, device) in devices {
+        match bindings.iter().find(|b| b.device == device.name) {
+            None => {
+                commands.push(format!("keyword device[{}]:transform {}", device.name, transform));
+                commands.push(format!("keyword device[{}]:output {}", device.name, output.name));
+            }
+            Some(binding) if binding.monitor == profile_key(output) => {
+                commands.push(format!("keyword device[{}]:transform {}", device.name, transform));
+            }
+            Some(_) => {}
+        }
+    }
+    commands
+}
+
+// Rotate `output` and point touchscreen/tablet input at it with the same transform
+pub async fn apply_orientation(output: &str, orientation: &str) -> Result<()> {
+    let Some(transform) = orientation_transform(orientation) else {
+        return Ok(());
+    };
+    let monitors = monitor_control::get_monitors().await?;
+    let monitor = monitors
+        .iter()
+        .find(|m| m.name == output)
+        .ok_or_else(|| anyhow::anyhow!("Output {} not found", output))?;
+    if monitor.transform == transform {
+        return Ok(());
+    }
+    info!("Rotating {} to {} (transform {})", output, orientation, transform);
+    monitor_control::set_transform(monitor, transform).await?;
+    let devices = input_mapping::list_input_devices().await?;
+    let commands = rotation_input_commands(monitor, transform, &devices, &input_mapping::load_bindings());
+    monitor_control::run_hyprctl_batch(&commands.join("; ")).await
+}
+
+// Pass the current orientation and every change to `on_orientation` until
+// the stream ends. Takes the connection so it can be pointed at a stand-in
+// service on a private bus.
+pub async fn watch_orientation<F, Fut>(connection: &Connection, mut on_orientation: F) -> Result<()>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let proxy = IioSensorProxy::new(connection).await?;
+    if !proxy.has_accelerometer().await? {
+        return Err(anyhow::anyhow!("No accelerometer available"));
+    }
+    proxy.claim_accelerometer().await?;
+    let mut changes = proxy.receive_accelerometer_orientation_changed().await;
+
+    on_orientation(proxy.accelerometer_orientation().await?).await;
+    while let Some(change) = changes.next().await {
+        match change.get().await {
+            Ok(orientation) => on_orientation(orientation).await,
+            Err(e) => warn!("Failed to read orientation: {}", e),
+        }
+    }
+
+    let _ = proxy.release_accelerometer().await;
+    warn!("Accelerometer stream ended.");
+    Ok(())
+}
+
+// Follow the accelerometer until the stream ends
+pub async fn run_auto_rotation(connection: &Connection, output: &str) -> Result<()> {
+    watch_orientation(connection, |orientation| handle_orientation(output, orientation)).await
+}
+
+async fn handle_orientation(output: &str, orientation: String) {
+    *LAST_ORIENTATION.lock().unwrap() = Some((output.to_string(), orientation.clone()));
+    if ROTATION_LOCKED.load(Ordering::SeqCst) {
+        return;
+    }
+    if let Err(e) = apply_orientation(output, &orientation).await {
+        warn!("Auto-rotation failed: {}", e);
+    }
+}
+
+// Start following the system accelerometer. `output` defaults to the
+// built-in panel.
+pub fn start_auto_rotation(output: Option<String>) {
+    let mut task = ROTATION_TASK.lock().unwrap();
+    if task.as_ref().is_some_and(|t| !t.is_finished()) {
+        return;
+    }
+    *task = Some(tokio::spawn(async move {
+        let output = match output {
+            Some(output) => output,
+            None => match monitor_control::get_monitors().await {
+                Ok(monitors) => match projection::pick_primary(&monitors, "") {
+                    Some(m) => m.name.clone(),
+                    None => return,
+                },
+                Err(e) => {
+                    warn!("Auto-rotation: failed to load monitors: {}", e);
+                    return;
+                }
+            },
+        };
+        let result = match Connection::system().await {
+            Ok(connection) => run_auto_rotation(&connection, &output).await,
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            warn!("Auto-rotation stopped: {}", e);
+        }
+    }));
+}
+
+// Dropping the task drops its bus connection, which releases the claim
+pub fn stop_auto_rotation() {
+    if let Some(task) = ROTATION_TASK.lock().unwrap().take() {
+        task.abort();
+    }
+}
+
+pub fn is_rotation_locked() -> bool {
+    ROTATION_LOCKED.load(Ordering::SeqCst)
+}
+
+pub async fn set_rotation_locked(locked: bool) -> Result<()> {
+    ROTATION_LOCKED.store(locked, Ordering::SeqCst);
+    if locked {
+        return Ok(());
+    }
+    // Catch up with whatever happened while locked
+    let last = LAST_ORIENTATION.lock().unwrap().clone();
+    if let Some((output, orientation)) = last {
+        apply_orientation(&output, &orientation).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display_manager::monitor_types::test_support::test_monitor;
+    use tokio::net::UnixStream;
+    use tokio::sync::mpsc;
+    use zbus::connection::Builder;
+
+    const SENSOR_PATH: &str = "/net/hadess/SensorProxy";
+
+    // Just enough of iio-sensor-proxy for the accelerometer
+    struct StandInSensor {
+        orientation: String,
+        claimed: bool,
+    }
+
+    #[zbus::interface(name = "net.hadess.SensorProxy")]
+    impl StandInSensor {
+        fn claim_accelerometer(&mut self) {
+            self.claimed = true;
+        }
+
+        fn release_accelerometer(&mut self) {
+            self.claimed = false;
+        }
+
+        #[zbus(property)]
+        fn has_accelerometer(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn accelerometer_orientation(&self) -> String {
+            self.orientation.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn follows_orientation_changes() {
+        let (server_stream, client_stream) = UnixStream::pair().unwrap();
+        let sensor = StandInSensor { orientation: "normal".to_string(), claimed: false };
+        let server = Builder::unix_stream(server_stream)
+            .server(zbus::Guid::generate())
+            .unwrap()
+            .p2p()
+            .serve_at(SENSOR_PATH, sensor)
+            .unwrap()
+            .build();
+        let client = Builder::unix_stream(client_stream).p2p().build();
+        let (server, client) = futures::try_join!(server, client).unwrap();
+
+        let (sender, mut transforms) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            watch_orientation(&client, |orientation| {
+                let _ = sender.send(orientation_transform(&orientation));
+                async {}
+            })
+            .await
+        });
+        assert_eq!(transforms.recv().await, Some(Some(0)));
+
+        let sensor = server.object_server().interface::<_, StandInSensor>(SENSOR_PATH).await.unwrap();
+        assert!(sensor.get().await.claimed);
+        for orientation in ["left-up", "undefined", "bottom-up", "right-up", "normal"] {
+            sensor.get_mut().await.orientation = orientation.to_string();
+            sensor.get().await.accelerometer_orientation_changed(sensor.signal_context()).await.unwrap();
+            assert_eq!(transforms.recv().await.unwrap(), orientation_transform(orientation));
+        }
+        assert_eq!(orientation_transform("left-up"), Some(1));
+        assert_eq!(orientation_transform("undefined"), None);
+    }
+
+    #[test]
+    fn bound_devices_keep_their_output() {
+        let mut panel = test_monitor("eDP-1", 0);
+        panel.serial = "PANEL".to_string();
+        let mut external = test_monitor("DP-1", 1920);
+        external.serial = "EXTERNAL".to_string();
+        let device = |name: &str| InputDevice { address: String::new(), name: name.to_string(), device_type: None };
+        let devices = [
+            (InputKind::Touch, device("elan-touchscreen")),
+            (InputKind::Tablet, device("wacom-pen")),
+            (InputKind::Tablet, device("huion-tablet")),
+        ];
+        let binding = |name: &str, monitor: &Monitor| InputBinding {
+            device: name.to_string(),
+            kind: InputKind::Tablet,
+            monitor: profile_key(monitor),
+            region: None,
+        };
+        let bindings = [binding("wacom-pen", &panel), binding("huion-tablet", &external)];
+
+        assert_eq!(
+            rotation_input_commands(&panel, 1, &devices, &bindings),
+            vec![
+                "keyword device[elan-touchscreen]:transform 1",
+                "keyword device[elan-touchscreen]:output eDP-1",
+                "keyword device[wacom-pen]:transform 1",
+            ]
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use crate::display_manager::monitor_control;
+use crate::display_manager::monitor_types::Monitor;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::fs;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use zbus::{proxy, Connection};
+
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1",
+    gen_blocking = false
+)]
+trait Login1Manager {
+    // logind does not signal changes to this one, so it must not be cached
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn lid_closed(&self) -> zbus::Result<bool>;
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct WorkspaceInfo {
+    pub id: i32,
+    pub monitor: String,
+}
+
+const LID_POLL: Duration = Duration::from_secs(1);
+
+// Panel as it was before the lid closed, so it comes back where it was
+static PANEL_SNAPSHOT: Lazy<Mutex<Option<Monitor>>> = Lazy::new(|| Mutex::new(None));
+static RECHECK: Lazy<Notify> = Lazy::new(Notify::new);
+// Last lid state the docking service read, None while it isn't running
+static LID_CLOSED: Lazy<Mutex<Option<bool>>> = Lazy::new(|| Mutex::new(None));
+// The service and hotplug handlers must not apply the rule at the same time
+static APPLY_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+static DOCKING_TASK: Lazy<Mutex<Option<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+// Fallback when logind is unavailable. Reads e.g. /proc/acpi/button/lid/LID0/state
+// ("state:      closed").
+pub fn lid_closed_procfs() -> Option<bool> {
+    let entries = fs::read_dir("/proc/acpi/button/lid").ok()?;
+    for entry in entries.flatten() {
+        if let Ok(state) = fs::read_to_string(entry.path().join("state")) {
+            return Some(state.contains("closed"));
+        }
+    }
+    None
+}
+
+// Commands for the docking rule: the internal panel goes off when the lid
+// is closed and an external output is active, and comes back when the lid
+// opens or when it would otherwise leave zero active outputs.
+// `monitors` must include disabled outputs (`hyprctl monitors all`).
+pub fn docking_commands(
+    lid_closed: bool,
+    monitors: &[Monitor],
+    workspaces: &[WorkspaceInfo],
+    snapshot: Option<&Monitor>,
+) -> Vec<String> {
+    let Some(panel) = monitors.iter().find(|m| m.is_internal()) else {
+        return Vec::new();
+    };
+    // An output mirroring the panel (a projector in presentation mode) shows
+    // nothing once the panel is off, so it doesn't count
+    let external = monitors
+        .iter()
+        .find(|m| !m.is_internal() && !m.disabled && m.mirror_source() != Some(panel.name.as_str()));
+    let mut commands = Vec::new();
+    match (lid_closed, external, panel.disabled) {
+        (true, Some(external), false) => {
+            // Move workspaces first so windows land on the external screen
+            // rather than wherever Hyprland picks
+            for workspace in workspaces.iter().filter(|w| w.monitor == panel.name && w.id > 0) {
+                commands.push(format!("dispatch moveworkspacetomonitor {} {}", workspace.id, external.name));
+            }
+            commands.push(format!("keyword monitor {},disable", panel.name));
+        }
+        // Only bring back a panel we turned off ourselves, so "second screen
+        // only" keeps working with the lid open; with no other active output
+        // it comes back regardless
+        (false, _, true) if snapshot.is_some() => {
+            commands.push(monitor_control::enable_rule(snapshot.unwrap_or(panel)));
+        }
+        (_, None, true) => {
+            commands.push(monitor_control::enable_rule(snapshot.unwrap_or(panel)));
+        }
+        _ => {}
+    }
+    commands
+}
+
+async fn get_workspaces() -> Result<Vec<WorkspaceInfo>> {
+    let output = Command::new("hyprctl").arg("workspaces").arg("-j").output().await?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("hyprctl workspaces failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+pub async fn apply_docking(lid_closed: bool) -> Result<()> {
+    let _guard = APPLY_LOCK.lock().await;
+    let monitors = monitor_control::get_all_monitors().await?;
+    let workspaces = get_workspaces().await?;
+    let commands = {
+        let mut snapshot = PANEL_SNAPSHOT.lock().unwrap();
+        let commands = docking_commands(lid_closed, &monitors, &workspaces, snapshot.as_ref());
+        let panel = monitors.iter().find(|m| m.is_internal());
+        match panel {
+            Some(panel) if !panel.disabled && !commands.is_empty() => *snapshot = Some(panel.clone()),
+            Some(panel) if panel.disabled && !commands.is_empty() => *snapshot = None,
+            _ => {}
+        }
+        commands
+    };
+    if commands.is_empty() {
+        return Ok(());
+    }
+    info!("Docking (lid {}): {}", if lid_closed { "closed" } else { "open" }, commands.join("; "));
+    monitor_control::run_hyprctl_batch(&commands.join("; ")).await
+}
+
+// Watch the lid until stopped, re-applying the rule whenever the lid moves
+// or `recheck` is called (monitor hotplug)
+pub async fn run_docking(connection: Option<Connection>) -> Result<()> {
+    let proxy = match connection {
+        Some(ref connection) => Some(Login1ManagerProxy::new(connection).await?),
+        None => None,
+    };
+    let read_lid = || async {
+        match proxy {
+            Some(ref proxy) => proxy.lid_closed().await.ok().or_else(lid_closed_procfs),
+            None => lid_closed_procfs(),
+        }
+    };
+    if read_lid().await.is_none() {
+        return Err(anyhow::anyhow!("No lid switch found"));
+    }
+
+    let mut applied = None;
+    loop {
+        let lid_closed = read_lid().await;
+        *LID_CLOSED.lock().unwrap() = lid_closed;
+        if lid_closed.is_some() && lid_closed != applied {
+            let closed = lid_closed.unwrap_or(false);
+            match apply_docking(closed).await {
+                Ok(()) => applied = lid_closed,
+                Err(e) => warn!("Failed to apply docking rule: {}", e),
+            }
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(LID_POLL) => {}
+            _ = RECHECK.notified() => {
+                // The monitor set changed: re-evaluate even if the lid did not move
+                applied = None;
+            }
+        }
+    }
+}
+
+// Re-evaluate after a monitor was plugged in or removed
+pub fn recheck() {
+    RECHECK.notify_one();
+}
+
+// Lid state as last seen by the docking service, if it is running
+pub fn lid_closed() -> Option<bool> {
+    let running = DOCKING_TASK.lock().unwrap().as_ref().is_some_and(|t| !t.is_finished());
+    if running {
+        *LID_CLOSED.lock().unwrap()
+    } else {
+        None
+    }
+}
+
+// Apply the rule now and wait for it, so hotplug handlers that run after
+// it (projection) see the panel the way docking left it
+pub async fn recheck_now() -> Result<()> {
+    match lid_closed() {
+        Some(closed) => apply_docking(closed).await,
+        None => Ok(()),
+    }
+}
+
+pub fn start_docking() {
+    let mut task = DOCKING_TASK.lock().unwrap();
+    if task.as_ref().is_some_and(|t| !t.is_finished()) {
+        return;
+    }
+    *task = Some(tokio::spawn(async move {
+        let connection = match Connection::system().await {
+            Ok(connection) => Some(connection),
+            Err(e) => {
+                warn!("logind unavailable, reading /proc/acpi/button/lid: {}", e);
+                None
+            }
+        };
+        if let Err(e) = run_docking(connection).await {
+            warn!("Docking service stopped: {}", e);
+        }
+    }));
+}
+
+pub fn stop_docking() {
+    if let Some(task) = DOCKING_TASK.lock().unwrap().take() {
+        task.abort();
+    }
+    *LID_CLOSED.lock().unwrap() = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display_manager::monitor_types::test_support::test_monitor;
+
+    fn workspace(id: i32, monitor: &str) -> WorkspaceInfo {
+        WorkspaceInfo { id, monitor: monitor.to_string() }
+    }
+
+    fn disabled(mut monitor: Monitor) -> Monitor {
+        monitor.disabled = true;
+        monitor
+    }
+
+    #[test]
+    fn closing_the_lid_moves_workspaces_and_disables_the_panel() {
+        let monitors = vec![test_monitor("eDP-1", 0), test_monitor("DP-1", 1920)];
+        // Special workspaces (negative ids) follow on their own
+        let workspaces = vec![workspace(1, "eDP-1"), workspace(2, "DP-1"), workspace(3, "eDP-1"), workspace(-98, "eDP-1")];
+        assert_eq!(
+            docking_commands(true, &monitors, &workspaces, None),
+            vec![
+                "dispatch moveworkspacetomonitor 1 DP-1",
+                "dispatch moveworkspacetomonitor 3 DP-1",
+                "keyword monitor eDP-1,disable",
+            ]
+        );
+    }
+
+    #[test]
+    fn closing_the_lid_without_an_external_does_nothing() {
+        let workspaces = vec![workspace(1, "eDP-1")];
+        assert!(docking_commands(true, &[test_monitor("eDP-1", 0)], &workspaces, None).is_empty());
+        let spare = disabled(test_monitor("DP-1", 1920));
+        assert!(docking_commands(true, &[test_monitor("eDP-1", 0), spare], &workspaces, None).is_empty());
+    }
+
+    #[test]
+    fn an_output_mirroring_the_panel_is_not_an_external() {
+        let mut projector = test_monitor("HDMI-A-1", 0);
+        projector.mirror_of = Some("eDP-1".to_string());
+        let monitors = vec![test_monitor("eDP-1", 0), projector];
+        assert!(docking_commands(true, &monitors, &[workspace(1, "eDP-1")], None).is_empty());
+    }
+
+    #[test]
+    fn unplugging_the_external_brings_the_panel_back() {
+        let mut before = test_monitor("eDP-1", 0);
+        before.scale = 1.5;
+        let monitors = vec![disabled(test_monitor("eDP-1", 0)), disabled(test_monitor("DP-1", 1920))];
+        assert_eq!(
+            docking_commands(true, &monitors, &[], Some(&before)),
+            vec![monitor_control::enable_rule(&before)]
+        );
+        // Even with no snapshot, zero active outputs is never an option
+        assert_eq!(
+            docking_commands(true, &monitors, &[], None),
+            vec![monitor_control::enable_rule(&monitors[0])]
+        );
+    }
+
+    #[test]
+    fn opening_the_lid_keeps_a_panel_the_user_turned_off() {
+        let monitors = vec![disabled(test_monitor("eDP-1", 0)), test_monitor("DP-1", 1920)];
+        assert!(docking_commands(false, &monitors, &[], None).is_empty());
+        // One we turned off comes back
+        let before = test_monitor("eDP-1", 0);
+        assert_eq!(
+            docking_commands(false, &monitors, &[], Some(&before)),
+            vec![monitor_control::enable_rule(&before)]
+        );
+    }
+}
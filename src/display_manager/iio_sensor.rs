@@ -0,0 +1,27 @@
+use zbus::proxy;
+
+// iio-sensor-proxy (system bus). Each sensor must be claimed before its
+// property starts updating; claims are dropped with the bus connection.
+#[proxy(
+    interface = "net.hadess.SensorProxy",
+    default_service = "net.hadess.SensorProxy",
+    default_path = "/net/hadess/SensorProxy",
+    gen_blocking = false
+)]
+pub trait IioSensor {
+    fn claim_accelerometer(&self) -> zbus::Result<()>;
+    fn release_accelerometer(&self) -> zbus::Result<()>;
+    fn claim_light(&self) -> zbus::Result<()>;
+    fn release_light(&self) -> zbus::Result<()>;
+    #[zbus(property)]
+    fn has_accelerometer(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn accelerometer_orientation(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn has_ambient_light(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn light_level(&self) -> zbus::Result<f64>;
+    // "lux" or "vendor" (arbitrary units)
+    #[zbus(property)]
+    fn light_level_unit(&self) -> zbus::Result<String>;
+}
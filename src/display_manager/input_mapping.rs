@@ -0,0 +1,279 @@
+use crate::display_manager::monitor_control;
+use crate::display_manager::monitor_profiles::profile_key;
+use crate::display_manager::monitor_types::Monitor;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+// Hyprland sends no event when an input device is plugged in
+const DEVICE_POLL: Duration = Duration::from_secs(2);
+static DEVICE_WATCH_TASK: Lazy<Mutex<Option<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    Tablet,
+    Touch,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct InputDevice {
+    pub address: String,
+    pub name: String,
+    // Tablets only: "tablet", "tabletPad" or "tabletTool"
+    #[serde(default, rename = "type")]
+    pub device_type: Option<String>,
+}
+
+// The parts of `hyprctl devices -j` we care about
+#[derive(Deserialize, Debug, Default)]
+struct HyprDevices {
+    #[serde(default)]
+    tablets: Vec<InputDevice>,
+    #[serde(default)]
+    touch: Vec<InputDevice>,
+}
+
+// A device pinned to one monitor. The monitor is stored by profile key
+// (serial) so the binding survives the monitor moving to another port.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InputBinding {
+    pub device: String,
+    pub kind: InputKind,
+    pub monitor: String,
+    // Tablets only: x, y, width, height of the mapped area in layout pixels
+    #[serde(default)]
+    pub region: Option<[i32; 4]>,
+}
+
+fn bindings_path() -> PathBuf {
+    dirs::config_dir()
+        .map(|p| p.join("hypr-xdisplay"))
+        .unwrap()
+        .join("input_bindings.json")
+}
+
+pub fn load_bindings() -> Vec<InputBinding> {
+    let path = bindings_path();
+    match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+            warn!("Failed to parse {:?}, ignoring: {}", path, e);
+            Vec::new()
+        }),
+        Err(_) => Vec::new(),
+    }
+}
+
+pub fn save_bindings(bindings: &[InputBinding]) -> std::io::Result<()> {
+    let path = bindings_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(bindings)?;
+    fs::write(path, json)
+}
+
+// Tablets and touchscreens known to Hyprland. Pads and tools are reported
+// as separate entries but are configured through their parent tablet.
+pub async fn list_input_devices() -> anyhow::Result<Vec<(InputKind, InputDevice)>> {
+    let output = Command::new("hyprctl")
+        .arg("devices")
+        .arg("-j")
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let error_message = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "hyprctl command failed: {}",
+            error_message
+        ));
+    }
+
+    let devices: HyprDevices = serde_json::from_slice(&output.stdout)?;
+    let tablets = devices
+        .tablets
+        .into_iter()
+        .filter(|d| matches!(d.device_type.as_deref(), None | Some("tablet")))
+        .map(|d| (InputKind::Tablet, d));
+    let touch = devices.touch.into_iter().map(|d| (InputKind::Touch, d));
+    Ok(tablets.chain(touch).collect())
+}
+
+// Parse "x y w h" (commas also accepted) into a tablet region. Empty text
+// means no region; anything else that isn't four numbers with a positive
+// size is an error.
+pub fn parse_region(text: &str) -> anyhow::Result<Option<[i32; 4]>> {
+    let invalid = || anyhow::anyhow!("Invalid region \"{}\", expected x y width height", text.trim());
+    let values: Vec<i32> = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().map_err(|_| invalid()))
+        .collect::<anyhow::Result<_>>()?;
+    match values.as_slice() {
+        [] => Ok(None),
+        [x, y, w, h] if *w > 0 && *h > 0 => Ok(Some([*x, *y, *w, *h])),
+        _ => Err(invalid()),
+    }
+}
+
+// Per-device keywords for one binding, or None if its monitor is not connected
+pub fn binding_commands(binding: &InputBinding, monitors: &[Monitor]) -> Option<Vec<String>> {
+    let monitor = monitors.iter().find(|m| profile_key(m) == binding.monitor)?;
+    let mut commands = vec![format!("keyword device[{}]:output {}", binding.device, monitor.name)];
+    if binding.kind == InputKind::Tablet {
+        // Without a region, reset it: a cleared field must not leave the old
+        // area mapped. A zero size is Hyprland's default, the whole output.
+        let [x, y, w, h] = binding.region.unwrap_or([0; 4]);
+        commands.push(format!("keyword device[{}]:region_position {} {}", binding.device, x, y));
+        commands.push(format!("keyword device[{}]:region_size {} {}", binding.device, w, h));
+    }
+    Some(commands)
+}
+
+// Store (or replace) a binding and apply it right away
+pub async fn bind_device(binding: InputBinding) -> anyhow::Result<()> {
+    let mut bindings = load_bindings();
+    bindings.retain(|b| b.device != binding.device);
+    bindings.push(binding.clone());
+    save_bindings(&bindings)?;
+
+    let monitors = monitor_control::get_monitors().await?;
+    match binding_commands(&binding, &monitors) {
+        Some(commands) => monitor_control::run_hyprctl_batch(&commands.join("; ")).await,
+        None => Err(anyhow::anyhow!("Monitor for {} is not connected", binding.device)),
+    }
+}
+
+// Forget a binding and let the device span the whole layout again
+pub async fn unbind_device(device: &str) -> anyhow::Result<()> {
+    let mut bindings = load_bindings();
+    let had_region = bindings.iter().any(|b| b.device == device && b.region.is_some());
+    bindings.retain(|b| b.device != device);
+    save_bindings(&bindings)?;
+
+    // An empty value can't be sent in a batch
+    let output = Command::new("hyprctl")
+        .arg("keyword")
+        .arg(format!("device[{}]:output", device))
+        .arg("")
+        .output()
+        .await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.trim() != "ok" {
+        let error_message = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "hyprctl keyword failed: {}{}",
+            stdout.trim(),
+            error_message.trim()
+        ));
+    }
+    // A zero-sized region is Hyprland's default: the whole output
+    if had_region {
+        monitor_control::run_hyprctl_batch(&format!(
+            "keyword device[{d}]:region_position 0 0; keyword device[{d}]:region_size 0 0",
+            d = device
+        ))
+        .await?;
+    }
+    Ok(())
+}
+
+// Re-apply every stored binding whose monitor is connected. Called on
+// monitor hotplug and config reload, which reset runtime keywords.
+pub async fn reapply_bindings() -> anyhow::Result<()> {
+    let bindings = load_bindings();
+    if bindings.is_empty() {
+        return Ok(());
+    }
+    let monitors = monitor_control::get_monitors().await?;
+    let commands: Vec<String> = bindings
+        .iter()
+        .filter_map(|b| binding_commands(b, &monitors))
+        .flatten()
+        .collect();
+    info!("Re-applying {} input binding command(s)", commands.len());
+    monitor_control::run_hyprctl_batch(&commands.join("; ")).await
+}
+
+// Re-apply bindings whenever a tablet or touchscreen shows up
+pub fn start_device_watch() {
+    let mut task = DEVICE_WATCH_TASK.lock().unwrap();
+    if task.as_ref().is_some_and(|t| !t.is_finished()) {
+        return;
+    }
+    *task = Some(tokio::spawn(async move {
+        let mut known: Option<HashSet<String>> = None;
+        loop {
+            match list_input_devices().await {
+                Ok(devices) => {
+                    let names: HashSet<String> = devices.into_iter().map(|(_, d)| d.name).collect();
+                    let added = known.as_ref().is_some_and(|known| !names.is_subset(known));
+                    known = Some(names);
+                    if added {
+                        if let Err(e) = reapply_bindings().await {
+                            warn!("Failed to re-apply input bindings: {}", e);
+                        }
+                    }
+                }
+                Err(e) => warn!("Failed to list input devices: {}", e),
+            }
+            tokio::time::sleep(DEVICE_POLL).await;
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display_manager::monitor_types::test_support::test_monitor;
+
+    fn binding(device: &str, kind: InputKind, monitor: &str, region: Option<[i32; 4]>) -> InputBinding {
+        InputBinding { device: device.to_string(), kind, monitor: monitor.to_string(), region }
+    }
+
+    #[test]
+    fn region_parsing() {
+        assert_eq!(parse_region("").unwrap(), None);
+        assert_eq!(parse_region("  ").unwrap(), None);
+        assert_eq!(parse_region("0 0 1920 1080").unwrap(), Some([0, 0, 1920, 1080]));
+        assert_eq!(parse_region("-10,20, 800 600").unwrap(), Some([-10, 20, 800, 600]));
+        assert!(parse_region("0 0 1920").is_err());
+        assert!(parse_region("0 0 0 1080").is_err());
+        assert!(parse_region("0 0 wide 1080").is_err());
+    }
+
+    #[test]
+    fn binding_commands_per_kind() {
+        let monitors = vec![test_monitor("eDP-1", 0), test_monitor("DP-1", 1920)];
+        let tablet = binding("wacom-tablet", InputKind::Tablet, "DP-1", Some([1920, 0, 960, 540]));
+        assert_eq!(
+            binding_commands(&tablet, &monitors).unwrap(),
+            vec![
+                "keyword device[wacom-tablet]:output DP-1",
+                "keyword device[wacom-tablet]:region_position 1920 0",
+                "keyword device[wacom-tablet]:region_size 960 540",
+            ]
+        );
+        // Re-bound with the region cleared: the old one is reset
+        let cleared = binding("wacom-tablet", InputKind::Tablet, "DP-1", None);
+        assert_eq!(
+            binding_commands(&cleared, &monitors).unwrap(),
+            vec![
+                "keyword device[wacom-tablet]:output DP-1",
+                "keyword device[wacom-tablet]:region_position 0 0",
+                "keyword device[wacom-tablet]:region_size 0 0",
+            ]
+        );
+        let touch = binding("elan-touch", InputKind::Touch, "eDP-1", None);
+        assert_eq!(binding_commands(&touch, &monitors).unwrap(), vec!["keyword device[elan-touch]:output eDP-1"]);
+        let unplugged = binding("elan-touch", InputKind::Touch, "HDMI-A-1", None);
+        assert_eq!(binding_commands(&unplugged, &monitors), None);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::display_manager::monitor_types::Monitor;
+
+const DEFAULT_LIMIT: usize = 50;
+
+// Undo/redo stacks of whole arrangements. Callers record the layout they
+// are leaving, then undo/redo hand back the layout to switch to.
+#[derive(Debug, Clone)]
+pub struct LayoutHistory {
+    undo: Vec<Vec<Monitor>>,
+    redo: Vec<Vec<Monitor>>,
+    limit: usize,
+}
+
+impl Default for LayoutHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_LIMIT)
+    }
+}
+
+impl LayoutHistory {
+    pub fn new(limit: usize) -> Self {
+        Self { undo: Vec::new(), redo: Vec::new(), limit: limit.max(1) }
+    }
+
+    // A new change replaces whatever could have been redone
+    pub fn record(&mut self, previous: Vec<Monitor>) {
+        self.undo.push(previous);
+        if self.undo.len() > self.limit {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, current: Vec<Monitor>) -> Option<Vec<Monitor>> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    pub fn redo(&mut self, current: Vec<Monitor>) -> Option<Vec<Monitor>> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display_manager::monitor_types::test_support::test_monitor;
+
+    // A one-monitor layout told apart by its x position
+    fn layout(x: i32) -> Vec<Monitor> {
+        vec![test_monitor("DP-1", x)]
+    }
+
+    fn x_of(layout: Option<Vec<Monitor>>) -> Option<i32> {
+        layout.map(|monitors| monitors[0].x)
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_stacks() {
+        let mut history = LayoutHistory::default();
+        assert!(!history.can_undo() && !history.can_redo());
+        assert_eq!(x_of(history.undo(layout(0))), None);
+
+        history.record(layout(0));
+        history.record(layout(1));
+        // Now at layout 2
+        assert_eq!(x_of(history.undo(layout(2))), Some(1));
+        assert_eq!(x_of(history.undo(layout(1))), Some(0));
+        assert!(!history.can_undo());
+        assert_eq!(x_of(history.redo(layout(0))), Some(1));
+        assert_eq!(x_of(history.redo(layout(1))), Some(2));
+        assert!(!history.can_redo());
+        assert_eq!(x_of(history.undo(layout(2))), Some(1));
+    }
+
+    #[test]
+    fn recording_clears_redo() {
+        let mut history = LayoutHistory::default();
+        history.record(layout(0));
+        assert_eq!(x_of(history.undo(layout(1))), Some(0));
+        assert!(history.can_redo());
+        history.record(layout(0));
+        assert!(!history.can_redo());
+        assert_eq!(x_of(history.redo(layout(5))), None);
+    }
+
+    #[test]
+    fn oldest_entries_are_evicted_at_the_limit() {
+        let mut history = LayoutHistory::new(2);
+        for x in 0..4 {
+            history.record(layout(x));
+        }
+        assert_eq!(x_of(history.undo(layout(4))), Some(3));
+        assert_eq!(x_of(history.undo(layout(3))), Some(2));
+        assert_eq!(x_of(history.undo(layout(2))), None);
+        // A zero limit still keeps one step
+        let mut single = LayoutHistory::new(0);
+        single.record(layout(0));
+        single.record(layout(1));
+        assert_eq!(x_of(single.undo(layout(2))), Some(1));
+        assert!(!single.can_undo());
+    }
+}
@@ -0,0 +1,214 @@
+use crate::display_manager::monitor_types::Monitor;
+use std::fmt;
+
+// Geometry a monitor ends up with once a layout is applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedMonitor {
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    pub refresh_rate: f64,
+    pub x: i32,
+    pub y: i32,
+    pub scale: f64,
+    pub transform: i32,
+}
+
+impl PlannedMonitor {
+    fn from_current(monitor: &Monitor) -> Self {
+        Self {
+            name: monitor.name.clone(),
+            width: monitor.width,
+            height: monitor.height,
+            refresh_rate: monitor.refresh_rate,
+            x: monitor.x,
+            y: monitor.y,
+            scale: monitor.scale,
+            transform: monitor.transform,
+        }
+    }
+
+    pub fn mode(&self) -> String {
+        format!("{}x{}@{}", self.width, self.height, self.refresh_rate)
+    }
+
+    pub fn rule(&self) -> String {
+        format!(
+            "keyword monitor {},{},{}x{},{},transform,{}",
+            self.name, self.mode(), self.x, self.y, self.scale, self.transform
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutChange {
+    Enable,
+    Mode { from: String, to: String },
+    Position { from: (i32, i32), to: (i32, i32) },
+    Scale { from: f64, to: f64 },
+    Transform { from: i32, to: i32 },
+}
+
+impl fmt::Display for LayoutChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutChange::Enable => write!(f, "enable"),
+            LayoutChange::Mode { from, to } => write!(f, "mode {} → {}", from, to),
+            LayoutChange::Position { from, to } => {
+                write!(f, "position {}x{} → {}x{}", from.0, from.1, to.0, to.1)
+            }
+            LayoutChange::Scale { from, to } => write!(f, "scale {} → {}", from, to),
+            LayoutChange::Transform { from, to } => write!(f, "transform {} → {}", from, to),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorDiff {
+    pub name: String,
+    pub changes: Vec<LayoutChange>,
+    pub planned: PlannedMonitor,
+}
+
+// Where each monitor of the desired arrangement lands. Extended monitors
+// are placed left to right in list order; Copy monitors take the geometry
+// of the first Extended one.
+pub fn plan_layout(desired: &[Monitor]) -> Vec<PlannedMonitor> {
+    // First pass: find the geometry of the first Extended monitor (for
+    // mirroring). Copy monitors do not take up room, so it always sits at
+    // the left edge.
+    let extended_geometry = desired
+        .iter()
+        .find(|monitor| monitor.mode.as_deref().unwrap_or("Extended") == "Extended")
+        .map(|monitor| (monitor.width, monitor.height, monitor.refresh_rate, 0, monitor.y));
+
+    // Second pass: place every monitor
+    let mut current_x = 0;
+    desired
+        .iter()
+        .map(|monitor| {
+            let mode = monitor.mode.as_deref().unwrap_or("Extended");
+            let (width, height, refresh_rate, x, y) = match extended_geometry {
+                Some(geometry) if mode == "Copy" => geometry,
+                _ => (monitor.width, monitor.height, monitor.refresh_rate, current_x, monitor.y),
+            };
+            if mode == "Extended" {
+                current_x += width;
+            }
+            PlannedMonitor {
+                name: monitor.name.clone(),
+                width,
+                height,
+                refresh_rate,
+                x,
+                y,
+                scale: monitor.scaling.unwrap_or(monitor.scale),
+                transform: monitor.transform,
+            }
+        })
+        .collect()
+}
+
+// What would change for each monitor going from `current` to `desired`.
+// Monitors that already match are left out.
+pub fn layout_diff(current: &[Monitor], desired: &[Monitor]) -> Vec<MonitorDiff> {
+    plan_layout(desired)
+        .into_iter()
+        .filter_map(|planned| {
+            let changes = match current.iter().find(|m| m.name == planned.name && !m.disabled) {
+                None => vec![LayoutChange::Enable],
+                Some(monitor) => {
+                    let before = PlannedMonitor::from_current(monitor);
+                    let mut changes = Vec::new();
+                    if before.width != planned.width
+                        || before.height != planned.height
+                        || (before.refresh_rate - planned.refresh_rate).abs() > 0.01
+                    {
+                        changes.push(LayoutChange::Mode { from: before.mode(), to: planned.mode() });
+                    }
+                    if (before.x, before.y) != (planned.x, planned.y) {
+                        changes.push(LayoutChange::Position {
+                            from: (before.x, before.y),
+                            to: (planned.x, planned.y),
+                        });
+                    }
+                    if (before.scale - planned.scale).abs() > 0.001 {
+                        changes.push(LayoutChange::Scale { from: before.scale, to: planned.scale });
+                    }
+                    if before.transform != planned.transform {
+                        changes.push(LayoutChange::Transform { from: before.transform, to: planned.transform });
+                    }
+                    changes
+                }
+            };
+            (!changes.is_empty()).then(|| MonitorDiff { name: planned.name.clone(), changes, planned })
+        })
+        .collect()
+}
+
+// The hyprctl commands `apply_monitor_layout` sends, one per changed monitor
+pub fn layout_commands(current: &[Monitor], desired: &[Monitor]) -> Vec<String> {
+    layout_diff(current, desired)
+        .iter()
+        .map(|diff| diff.planned.rule())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display_manager::monitor_types::test_support::test_monitor;
+
+    fn batch(current: &[Monitor], desired: &[Monitor]) -> String {
+        layout_commands(current, desired).join("; ")
+    }
+
+    #[test]
+    fn unchanged_layout_sends_nothing() {
+        let current = vec![test_monitor("eDP-1", 0), test_monitor("DP-1", 1920)];
+        assert_eq!(batch(&current, &current), "");
+        assert!(layout_diff(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn moved_monitors_get_one_rule_each() {
+        let current = vec![test_monitor("eDP-1", 0), test_monitor("DP-1", 1920)];
+        // Swapped in the arrangement: DP-1 now comes first
+        let desired = vec![current[1].clone(), current[0].clone()];
+        assert_eq!(
+            batch(&current, &desired),
+            "keyword monitor DP-1,1920x1080@60,0x0,1,transform,0; \
+             keyword monitor eDP-1,1920x1080@60,1920x0,1,transform,0"
+        );
+        let diff = layout_diff(&current, &desired);
+        assert_eq!(diff[0].changes, vec![LayoutChange::Position { from: (1920, 0), to: (0, 0) }]);
+    }
+
+    #[test]
+    fn disabled_output_is_enabled() {
+        let mut spare = test_monitor("DP-2", 1920);
+        spare.disabled = true;
+        let current = vec![test_monitor("eDP-1", 0), spare];
+        let mut desired = current.clone();
+        desired[1].disabled = false;
+        desired[1].scaling = Some(1.5);
+        assert_eq!(batch(&current, &desired), "keyword monitor DP-2,1920x1080@60,1920x0,1.5,transform,0");
+        assert_eq!(layout_diff(&current, &desired)[0].changes, vec![LayoutChange::Enable]);
+    }
+
+    #[test]
+    fn copy_takes_the_extended_geometry() {
+        let current = vec![test_monitor("eDP-1", 0), test_monitor("HDMI-A-1", 1920)];
+        let mut projector = current[1].clone();
+        projector.mode = Some("Copy".to_string());
+        projector.width = 1280;
+        projector.height = 720;
+        let mut panel = current[0].clone();
+        panel.mode = Some("Extended".to_string());
+
+        let expected = "keyword monitor HDMI-A-1,1920x1080@60,0x0,1,transform,0";
+        assert_eq!(batch(&current, &[panel.clone(), projector.clone()]), expected);
+        // Listed before the screen it copies, it still lands on it
+        assert_eq!(batch(&current, &[projector, panel]), expected);
+    }
+}
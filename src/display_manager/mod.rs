@@ -1,2 +1,15 @@
 pub mod monitor_types;
-pub mod monitor_control; 
\ No newline at end of file
+pub mod monitor_control;
+pub mod layout_plan;
+pub mod layout_history;
+pub mod monitor_profiles;
+pub mod projection;
+pub mod iio_sensor;
+pub mod auto_rotation;
+pub mod auto_brightness;
+pub mod input_mapping;
+pub mod night_light;
+pub mod wallpaper;
+pub mod power_policy;
+pub mod spacing;
+pub mod docking;
\ No newline at end of file
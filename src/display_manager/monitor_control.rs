@@ -1,6 +1,9 @@
+use crate::display_manager::layout_plan;
 use crate::display_manager::monitor_types::Monitor;
 use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::process::Command;
+use tracing::info;
 
 pub async fn get_monitors() -> anyhow::Result<Vec<Monitor>> {
     let output = Command::new("hyprctl")
@@ -21,51 +24,36 @@ pub async fn get_monitors() -> anyhow::Result<Vec<Monitor>> {
     Ok(monitors)
 }
 
-pub async fn apply_monitor_layout(monitors: &[Monitor]) -> anyhow::Result<()> {
-    let mut command_batch = String::new();
-    let mut current_x = 0;
-    let mut extended_geometry = None;
-
-    // First pass: find the geometry of the first Extended monitor (for mirroring)
-    for monitor in monitors {
-        if monitor.mode.as_deref() == Some("Extended") {
-            extended_geometry = Some((monitor.width, monitor.height, monitor.refresh_rate, current_x, monitor.y));
-            break;
-        }
-        current_x += monitor.width;
-    }
+// Like get_monitors, but also lists outputs that are currently disabled
+pub async fn get_all_monitors() -> anyhow::Result<Vec<Monitor>> {
+    let output = Command::new("hyprctl")
+        .arg("monitors")
+        .arg("all")
+        .arg("-j")
+        .output()
+        .await?;
 
-    // Second pass: generate commands
-    let mut current_x = 0;
-    for monitor in monitors {
-        let mode = monitor.mode.as_deref().unwrap_or("Extended");
-        let scaling = monitor.scaling.unwrap_or(monitor.scale);
-        let (width, height, refresh_rate, x, y) = if mode == "Copy" {
-            if let Some((w, h, r, x, y)) = extended_geometry {
-                (w, h, r, x, y)
-            } else {
-                (monitor.width, monitor.height, monitor.refresh_rate, current_x, monitor.y)
-            }
-        } else {
-            (monitor.width, monitor.height, monitor.refresh_rate, current_x, monitor.y)
-        };
-        let command = format!(
-            "keyword monitor {},{}x{}@{},{}x{}; keyword monitor {} scale {};",
-            monitor.name, width, height, refresh_rate, x, y, monitor.name, scaling
-        );
-        command_batch.push_str(&command);
-        if mode == "Extended" {
-            current_x += width;
-        }
+    if !output.status.success() {
+        let error_message = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "hyprctl command failed: {}",
+            error_message
+        ));
     }
 
+    let monitors: Vec<Monitor> = serde_json::from_slice(&output.stdout)?;
+    Ok(monitors)
+}
+
+// Send a `;`-separated list of hyprctl commands in one batch
+pub async fn run_hyprctl_batch(command_batch: &str) -> anyhow::Result<()> {
     if command_batch.is_empty() {
         return Ok(());
     }
 
     let output = Command::new("hyprctl")
         .arg("--batch")
-        .arg(&command_batch)
+        .arg(command_batch)
         .output()
         .await?;
 
@@ -80,19 +68,59 @@ pub async fn apply_monitor_layout(monitors: &[Monitor]) -> anyhow::Result<()> {
     Ok(())
 }
 
+// Hyprland rule that turns an output (back) on with its last known mode,
+// position, scale and transform
+pub fn enable_rule(monitor: &Monitor) -> String {
+    if monitor.width > 0 && monitor.height > 0 {
+        format!(
+            "keyword monitor {},{}x{}@{},{}x{},{},transform,{}",
+            monitor.name, monitor.width, monitor.height, monitor.refresh_rate,
+            monitor.x, monitor.y, monitor.scale, monitor.transform
+        )
+    } else {
+        format!("keyword monitor {},preferred,auto,1", monitor.name)
+    }
+}
+
+// Print layout commands instead of running them (`--dry-run`)
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+pub fn set_dry_run(enabled: bool) {
+    DRY_RUN.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+pub async fn apply_monitor_layout(monitors: &[Monitor]) -> anyhow::Result<()> {
+    let current = get_all_monitors().await?;
+    let commands = layout_plan::layout_commands(&current, monitors);
+    if is_dry_run() {
+        info!("Dry run, not applying {} layout command(s)", commands.len());
+        for command in &commands {
+            println!("hyprctl {}", command);
+        }
+        return Ok(());
+    }
+    run_hyprctl_batch(&commands.join("; ")).await
+}
+
 // Set brightness for a physical monitor using ddcutil
 pub async fn set_brightness(monitor: &Monitor, value: f64) -> anyhow::Result<()> {
     // Only for physical monitors
-    if monitor.device_type.as_deref() == Some("Physical") {
+    if monitor.is_physical() {
         let pct = (value * 100.0).round() as u8;
-        let _ = Command::new("ddcutil")
+        let output = Command::new("ddcutil")
             .arg("setvcp").arg("10")
             .arg(format!("{}", pct))
             .arg("--sn").arg(&monitor.serial)
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
             .output()
             .await?;
+        if !output.status.success() {
+            let error_message = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow::anyhow!("ddcutil failed on {}: {}", monitor.name, error_message.trim()));
+        }
     }
     Ok(())
 }
@@ -110,4 +138,11 @@ pub async fn set_rotation(monitor: &Monitor, orientation: &str) -> anyhow::Resul
             .await?;
     }
     Ok(())
-} 
\ No newline at end of file
+}
+
+// Set an arbitrary Hyprland transform (0-7), keeping the current mode and position
+pub async fn set_transform(monitor: &Monitor, transform: i32) -> anyhow::Result<()> {
+    let mut rotated = monitor.clone();
+    rotated.transform = transform;
+    run_hyprctl_batch(&enable_rule(&rotated)).await
+}
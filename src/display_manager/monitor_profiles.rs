@@ -0,0 +1,79 @@
+use crate::display_manager::monitor_types::Monitor;
+use crate::display_manager::power_policy::ModeOverride;
+use crate::display_manager::projection::ProjectionMode;
+use crate::display_manager::spacing::{GapOverride, ReservedArea};
+use crate::display_manager::wallpaper::Wallpaper;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tracing::warn;
+
+// Per-monitor preferences, keyed by monitor serial so they follow the
+// physical screen across ports and re-plugs.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MonitorProfile {
+    #[serde(default)]
+    pub projection: Option<ProjectionMode>,
+    #[serde(default)]
+    pub wallpaper: Option<Wallpaper>,
+    #[serde(default)]
+    pub battery_mode: Option<ModeOverride>,
+    #[serde(default)]
+    pub ac_mode: Option<ModeOverride>,
+    #[serde(default)]
+    pub reserved: Option<ReservedArea>,
+    #[serde(default)]
+    pub gaps: Option<GapOverride>,
+}
+
+fn profiles_path() -> PathBuf {
+    dirs::config_dir()
+        .map(|p| p.join("hypr-xdisplay"))
+        .unwrap()
+        .join("monitor_profiles.json")
+}
+
+// Stable key for a monitor. Some panels report an empty serial, so fall
+// back to the description (make + model) and finally the connector name.
+pub fn profile_key(monitor: &Monitor) -> String {
+    if !monitor.serial.trim().is_empty() {
+        monitor.serial.clone()
+    } else if !monitor.description.trim().is_empty() {
+        monitor.description.clone()
+    } else {
+        monitor.name.clone()
+    }
+}
+
+pub fn load_profiles() -> HashMap<String, MonitorProfile> {
+    let path = profiles_path();
+    match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+            warn!("Failed to parse {:?}, ignoring: {}", path, e);
+            HashMap::new()
+        }),
+        Err(_) => HashMap::new(),
+    }
+}
+
+pub fn save_profiles(profiles: &HashMap<String, MonitorProfile>) -> std::io::Result<()> {
+    let path = profiles_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(profiles)?;
+    fs::write(path, json)
+}
+
+pub fn get_profile(monitor: &Monitor) -> MonitorProfile {
+    load_profiles()
+        .remove(&profile_key(monitor))
+        .unwrap_or_default()
+}
+
+pub fn update_profile<F: FnOnce(&mut MonitorProfile)>(monitor: &Monitor, update: F) -> std::io::Result<()> {
+    let mut profiles = load_profiles();
+    update(profiles.entry(profile_key(monitor)).or_default());
+    save_profiles(&profiles)
+}
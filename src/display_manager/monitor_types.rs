@@ -25,6 +25,12 @@ pub struct Monitor {
     pub dpms_status: bool,
     pub vrr: bool,
     #[serde(default)]
+    pub mirror_of: Option<String>, // output this one mirrors, "none" if it doesn't
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub available_modes: Vec<String>, // e.g. "1920x1080@60.00Hz", preferred first
+    #[serde(default)]
     pub mode: Option<String>, // "Extended" or "Copy"
     #[serde(default)]
     pub orientation: Option<String>, // "Landscape" or "Portrait"
@@ -36,8 +42,82 @@ pub struct Monitor {
     pub device_type: Option<String>, // "Physical", "Wireless", "Android", "AirPlay", "VNC"
 }
 
+impl Monitor {
+    // Built-in laptop/tablet panel (as opposed to an external connector)
+    pub fn is_internal(&self) -> bool {
+        ["eDP", "LVDS", "DSI"].iter().any(|prefix| self.name.starts_with(prefix))
+    }
+
+    // A real screen DDC can talk to. hyprctl reports no device type, so only
+    // the known network display kinds are excluded.
+    pub fn is_physical(&self) -> bool {
+        !matches!(self.device_type.as_deref(), Some("Wireless" | "Android" | "AirPlay" | "VNC"))
+    }
+
+    // Output being mirrored onto this one, if any
+    pub fn mirror_source(&self) -> Option<&str> {
+        self.mirror_of.as_deref().filter(|name| !name.is_empty() && *name != "none")
+    }
+
+    // Native mode without the "Hz" suffix, e.g. "2560x1600@165.00".
+    // Hyprland lists it first in availableModes.
+    pub fn preferred_mode(&self) -> Option<String> {
+        self.available_modes.first().map(|mode| mode.trim_end_matches("Hz").to_string())
+    }
+
+    // Size in layout coordinates: pixels divided by scale, swapped when
+    // the output is rotated by 90 or 270 degrees
+    pub fn logical_size(&self) -> (i32, i32) {
+        let width = (self.width as f64 / self.scale).round() as i32;
+        let height = (self.height as f64 / self.scale).round() as i32;
+        if self.transform % 2 == 1 {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Workspace {
     pub id: i32,
     pub name: String,
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+pub mod test_support {
+    use super::*;
+
+    // A 1920x1080 output at (x, 0) for tests
+    pub fn test_monitor(name: &str, x: i32) -> Monitor {
+        Monitor {
+            id: 0,
+            name: name.to_string(),
+            description: String::new(),
+            make: String::new(),
+            model: String::new(),
+            serial: String::new(),
+            width: 1920,
+            height: 1080,
+            refresh_rate: 60.0,
+            x,
+            y: 0,
+            active_workspace: Workspace { id: 1, name: "1".to_string() },
+            special_workspace: Workspace { id: 0, name: String::new() },
+            reserved: [0; 4],
+            scale: 1.0,
+            transform: 0,
+            focused: false,
+            dpms_status: true,
+            vrr: false,
+            mirror_of: None,
+            disabled: false,
+            available_modes: Vec::new(),
+            mode: None,
+            orientation: None,
+            scaling: None,
+            brightness: None,
+            device_type: None,
+        }
+    }
+}
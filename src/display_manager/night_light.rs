@@ -0,0 +1,450 @@
+use crate::recorder;
+use crate::settings::Settings;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset, Local, NaiveTime, Timelike, Utc};
+use once_cell::sync::Lazy;
+use std::future::Future;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::{Child, Command};
+use tokio::sync::{watch, Mutex};
+use tracing::{info, warn};
+
+// Neutral white point; hyprsunset treats this as "no filter"
+pub const IDENTITY_TEMPERATURE: u32 = 6500;
+// Redshift-style transition band: full night below -6° solar elevation
+// (end of civil twilight), full day above +3°
+const NIGHT_ELEVATION: f64 = -6.0;
+const DAY_ELEVATION: f64 = 3.0;
+const TICK: Duration = Duration::from_secs(60);
+// Fixed schedules fade in over this long before night starts, and out
+// over this long after it ends
+const FIXED_FADE_MINUTES: f64 = 30.0;
+// Jumps (mode changes, recording pauses) are spread over FADE, in steps
+// of about STEP_KELVIN
+const FADE: Duration = Duration::from_secs(2);
+const FADE_STEPS: u32 = 20;
+const STEP_KELVIN: f64 = 50.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NightLightMode {
+    Off,
+    Manual { temperature: u32 },
+    Schedule { latitude: f64, longitude: f64, day_temperature: u32, night_temperature: u32 },
+    // Night between two local times; `start` may be after `end` (22:00-06:00)
+    Fixed { start: NaiveTime, end: NaiveTime, day_temperature: u32, night_temperature: u32 },
+}
+
+// "HH:MM" as typed in the settings
+pub fn parse_time(text: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(text.trim(), "%H:%M").ok()
+}
+
+impl NightLightMode {
+    // Schedule mode needs coordinates; without them it falls back to Off
+    pub fn from_settings(settings: &Settings) -> Self {
+        let temperature = settings.night_light_temperature.unwrap_or(4000);
+        match settings.night_light_mode.as_deref() {
+            Some("Manual") => NightLightMode::Manual { temperature },
+            Some("Schedule") => match (settings.night_light_latitude, settings.night_light_longitude) {
+                (Some(latitude), Some(longitude)) => NightLightMode::Schedule {
+                    latitude,
+                    longitude,
+                    day_temperature: IDENTITY_TEMPERATURE,
+                    night_temperature: temperature,
+                },
+                _ => {
+                    warn!("Night light schedule needs latitude and longitude; leaving it off");
+                    NightLightMode::Off
+                }
+            },
+            Some("Fixed") => {
+                let time = |text: &Option<String>| text.as_deref().and_then(parse_time);
+                match (time(&settings.night_light_start), time(&settings.night_light_end)) {
+                    (Some(start), Some(end)) => NightLightMode::Fixed {
+                        start,
+                        end,
+                        day_temperature: IDENTITY_TEMPERATURE,
+                        night_temperature: temperature,
+                    },
+                    _ => {
+                        warn!("Night light fixed schedule needs start and end times (HH:MM); leaving it off");
+                        NightLightMode::Off
+                    }
+                }
+            }
+            _ => NightLightMode::Off,
+        }
+    }
+}
+
+// Time source for the service loop, both for the schedule and for the
+// waits between ticks and fade steps, so it can be driven by a fake clock.
+// The offset is the local one, for fixed schedules.
+#[async_trait]
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<FixedOffset>;
+    async fn sleep(&self, duration: Duration);
+}
+
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        Local::now().fixed_offset()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+// Solar elevation in degrees for a position and instant (NOAA low-precision
+// formulas, accurate to well under a degree, which is plenty here)
+pub fn solar_elevation(latitude: f64, longitude: f64, at: DateTime<Utc>) -> f64 {
+    let julian_day = at.timestamp() as f64 / 86400.0 + 2440587.5;
+    let n = julian_day - 2451545.0;
+    let mean_longitude = (280.460 + 0.9856474 * n).rem_euclid(360.0);
+    let mean_anomaly = (357.528 + 0.9856003 * n).rem_euclid(360.0).to_radians();
+    let ecliptic_longitude =
+        (mean_longitude + 1.915 * mean_anomaly.sin() + 0.020 * (2.0 * mean_anomaly).sin()).to_radians();
+    let obliquity = (23.439 - 0.0000004 * n).to_radians();
+
+    let right_ascension = (obliquity.cos() * ecliptic_longitude.sin()).atan2(ecliptic_longitude.cos());
+    let declination = (obliquity.sin() * ecliptic_longitude.sin()).asin();
+    let sidereal_hours = (18.697374558 + 24.06570982441908 * n).rem_euclid(24.0);
+    let hour_angle = (sidereal_hours * 15.0 + longitude).to_radians() - right_ascension;
+
+    let lat = latitude.to_radians();
+    (lat.sin() * declination.sin() + lat.cos() * declination.cos() * hour_angle.cos())
+        .asin()
+        .to_degrees()
+}
+
+// How far into the night `time` is, from 0 (day) to 1 (night), for a
+// night from `start` to `end` that may cross midnight
+pub fn fixed_night_fraction(start: NaiveTime, end: NaiveTime, time: NaiveTime) -> f64 {
+    const DAY_MINUTES: f64 = 24.0 * 60.0;
+    let minutes = |t: NaiveTime| t.num_seconds_from_midnight() as f64 / 60.0;
+    // Minutes going forward from `a` to `b`, wrapping at midnight
+    let forward = |a: NaiveTime, b: NaiveTime| (minutes(b) - minutes(a)).rem_euclid(DAY_MINUTES);
+    if forward(start, time) < forward(start, end) {
+        return 1.0;
+    }
+    let after_end = forward(end, time);
+    let before_start = forward(time, start);
+    if after_end < FIXED_FADE_MINUTES {
+        1.0 - after_end / FIXED_FADE_MINUTES
+    } else if before_start < FIXED_FADE_MINUTES {
+        1.0 - before_start / FIXED_FADE_MINUTES
+    } else {
+        0.0
+    }
+}
+
+fn blend(day_temperature: u32, night_temperature: u32, night_fraction: f64) -> u32 {
+    let temperature = day_temperature as f64 + (night_temperature as f64 - day_temperature as f64) * night_fraction;
+    temperature.round() as u32
+}
+
+// Colour temperature the mode asks for at `now`. Scheduled mode blends
+// linearly through twilight, which gives the smooth dusk/dawn transition;
+// fixed times blend over half an hour either side of the night.
+pub fn target_temperature(mode: &NightLightMode, now: DateTime<FixedOffset>) -> u32 {
+    match *mode {
+        NightLightMode::Off => IDENTITY_TEMPERATURE,
+        NightLightMode::Manual { temperature } => temperature,
+        NightLightMode::Schedule { latitude, longitude, day_temperature, night_temperature } => {
+            let elevation = solar_elevation(latitude, longitude, now.with_timezone(&Utc));
+            let day_fraction = ((elevation - NIGHT_ELEVATION) / (DAY_ELEVATION - NIGHT_ELEVATION)).clamp(0.0, 1.0);
+            blend(day_temperature, night_temperature, 1.0 - day_fraction)
+        }
+        NightLightMode::Fixed { start, end, day_temperature, night_temperature } => {
+            blend(day_temperature, night_temperature, fixed_night_fraction(start, end, now.time()))
+        }
+    }
+}
+
+// Temperatures to pass through on the way from `from` to `to`, ending at
+// `to`, so a change fades in rather than jumping
+pub fn fade_steps(from: u32, to: u32) -> Vec<u32> {
+    let distance = to as f64 - from as f64;
+    let steps = ((distance.abs() / STEP_KELVIN).ceil() as u32).clamp(1, FADE_STEPS);
+    (1..=steps)
+        .map(|step| (from as f64 + distance * step as f64 / steps as f64).round() as u32)
+        .collect()
+}
+
+static MODE: Lazy<watch::Sender<NightLightMode>> = Lazy::new(|| watch::channel(NightLightMode::Off).0);
+// Fallback for hyprsunset builds without IPC: a child started with -t
+static HYPRSUNSET_PROCESS: Lazy<Arc<Mutex<Option<Child>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
+
+static SERVICE_STARTED: std::sync::Once = std::sync::Once::new();
+
+// Spawn the night light loop on the system clock (only once per process)
+pub fn start_night_light() {
+    SERVICE_STARTED.call_once(|| {
+        tokio::spawn(run_night_light(Arc::new(SystemClock)));
+    });
+}
+
+pub fn set_mode(mode: NightLightMode) {
+    MODE.send_replace(mode);
+}
+
+pub fn current_mode() -> NightLightMode {
+    *MODE.borrow()
+}
+
+// Push a temperature to hyprsunset, preferring its hyprctl IPC and falling
+// back to restarting it with the new temperature
+pub async fn apply_temperature(temperature: u32) -> Result<()> {
+    let output = if temperature >= IDENTITY_TEMPERATURE {
+        Command::new("hyprctl").arg("hyprsunset").arg("identity").output().await
+    } else {
+        Command::new("hyprctl")
+            .arg("hyprsunset")
+            .arg("temperature")
+            .arg(temperature.to_string())
+            .output()
+            .await
+    };
+    if let Ok(output) = output {
+        // hyprctl exits 0 even when hyprsunset is not listening, so check the reply too
+        let reply = String::from_utf8_lossy(&output.stdout).to_lowercase();
+        let rejected = ["couldn't", "unknown", "error"].iter().any(|word| reply.contains(word));
+        if output.status.success() && !rejected {
+            return Ok(());
+        }
+    }
+
+    let mut process = HYPRSUNSET_PROCESS.lock().await;
+    if let Some(mut child) = process.take() {
+        let _ = child.kill().await;
+    }
+    if temperature < IDENTITY_TEMPERATURE {
+        let child = Command::new("hyprsunset")
+            .arg("-t")
+            .arg(temperature.to_string())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        *process = Some(child);
+    }
+    Ok(())
+}
+
+// Keep the screen at the mode's target temperature. Re-evaluates on every
+// tick, on mode changes, and whenever a recording starts or stops; while
+// recording the filter is lifted so captures keep true colours.
+pub async fn run_night_light(clock: Arc<dyn Clock>) {
+    let mode_rx = MODE.subscribe();
+    let recording_rx = recorder::subscribe_recording_state();
+    drive(clock.as_ref(), mode_rx, recording_rx, apply_temperature).await;
+}
+
+// The service loop with its inputs and output passed in. Runs until either
+// sender is dropped.
+async fn drive<F, Fut>(
+    clock: &dyn Clock,
+    mut mode_rx: watch::Receiver<NightLightMode>,
+    mut recording_rx: watch::Receiver<bool>,
+    mut apply: F,
+) where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let mut applied: Option<u32> = None;
+    loop {
+        let mode = *mode_rx.borrow_and_update();
+        let recording = *recording_rx.borrow_and_update();
+        let temperature = if recording {
+            IDENTITY_TEMPERATURE
+        } else {
+            target_temperature(&mode, clock.now())
+        };
+        if applied != Some(temperature) {
+            info!("Night light: {}K{}", temperature, if recording { " (paused for recording)" } else { "" });
+            // Nothing to fade from until the first temperature is set
+            let steps = applied.map_or_else(|| vec![temperature], |from| fade_steps(from, temperature));
+            let pause = FADE / steps.len() as u32;
+            for (i, step) in steps.into_iter().enumerate() {
+                if i > 0 {
+                    clock.sleep(pause).await;
+                }
+                match apply(step).await {
+                    Ok(()) => applied = Some(step),
+                    Err(e) => {
+                        warn!("Failed to set colour temperature: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+        tokio::select! {
+            _ = clock.sleep(TICK) => {}
+            changed = mode_rx.changed() => if changed.is_err() { break },
+            changed = recording_rx.changed() => if changed.is_err() { break },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex as StdMutex;
+    use tokio::sync::mpsc;
+
+    // Time only moves when the loop sleeps
+    struct FakeClock {
+        now: StdMutex<DateTime<FixedOffset>>,
+    }
+
+    impl FakeClock {
+        fn at(rfc3339: &str) -> Self {
+            Self { now: StdMutex::new(DateTime::parse_from_rfc3339(rfc3339).unwrap()) }
+        }
+    }
+
+    #[async_trait]
+    impl Clock for FakeClock {
+        fn now(&self) -> DateTime<FixedOffset> {
+            *self.now.lock().unwrap()
+        }
+
+        async fn sleep(&self, duration: Duration) {
+            *self.now.lock().unwrap() += chrono::Duration::from_std(duration).unwrap();
+            tokio::task::yield_now().await;
+        }
+    }
+
+    fn at(rfc3339: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap()
+    }
+
+    fn time(text: &str) -> NaiveTime {
+        parse_time(text).unwrap()
+    }
+
+    // Temperatures every five minutes from `from` for `hours`
+    fn samples(mode: &NightLightMode, from: &str, hours: i64) -> Vec<u32> {
+        let start = at(from);
+        (0..hours * 12)
+            .map(|i| target_temperature(mode, start + chrono::Duration::minutes(i * 5)))
+            .collect()
+    }
+
+    #[test]
+    fn schedule_blends_through_twilight() {
+        // On the equator at the equinox the sun sets around 18:07 UTC and
+        // rises around 06:07
+        let mode = NightLightMode::Schedule {
+            latitude: 0.0,
+            longitude: 0.0,
+            day_temperature: IDENTITY_TEMPERATURE,
+            night_temperature: 4000,
+        };
+        assert_eq!(target_temperature(&mode, at("2024-03-20T12:00:00+00:00")), IDENTITY_TEMPERATURE);
+        assert_eq!(target_temperature(&mode, at("2024-03-21T00:00:00+00:00")), 4000);
+        // The local offset doesn't move the sun
+        assert_eq!(target_temperature(&mode, at("2024-03-21T03:00:00+03:00")), 4000);
+
+        let dusk = samples(&mode, "2024-03-20T17:00:00+00:00", 3);
+        assert_eq!(dusk.first(), Some(&IDENTITY_TEMPERATURE));
+        assert_eq!(dusk.last(), Some(&4000));
+        assert!(dusk.windows(2).all(|w| w[1] <= w[0]));
+        assert!(dusk.iter().filter(|t| **t > 4000 && **t < IDENTITY_TEMPERATURE).count() >= 3);
+
+        let dawn = samples(&mode, "2024-03-21T05:00:00+00:00", 3);
+        assert_eq!(dawn.first(), Some(&4000));
+        assert_eq!(dawn.last(), Some(&IDENTITY_TEMPERATURE));
+        assert!(dawn.windows(2).all(|w| w[1] >= w[0]));
+        assert!(dawn.iter().filter(|t| **t > 4000 && **t < IDENTITY_TEMPERATURE).count() >= 3);
+    }
+
+    #[test]
+    fn fixed_schedule_crosses_midnight() {
+        let mode = NightLightMode::Fixed {
+            start: time("22:00"),
+            end: time("06:00"),
+            day_temperature: IDENTITY_TEMPERATURE,
+            night_temperature: 4000,
+        };
+        let at_local = |hh_mm: &str| target_temperature(&mode, at(&format!("2024-06-01T{}:00+02:00", hh_mm)));
+        assert_eq!(at_local("12:00"), IDENTITY_TEMPERATURE);
+        assert_eq!(at_local("21:00"), IDENTITY_TEMPERATURE);
+        assert_eq!(at_local("21:45"), 5250);
+        assert_eq!(at_local("22:00"), 4000);
+        assert_eq!(at_local("23:59"), 4000);
+        assert_eq!(at_local("00:00"), 4000);
+        assert_eq!(at_local("05:59"), 4000);
+        assert_eq!(at_local("06:15"), 5250);
+        assert_eq!(at_local("06:30"), IDENTITY_TEMPERATURE);
+
+        // Same day too
+        assert_eq!(fixed_night_fraction(time("01:00"), time("05:00"), time("03:00")), 1.0);
+        assert_eq!(fixed_night_fraction(time("01:00"), time("05:00"), time("12:00")), 0.0);
+    }
+
+    #[test]
+    fn jumps_fade_in_steps() {
+        let steps = fade_steps(IDENTITY_TEMPERATURE, 4000);
+        assert_eq!(steps.len(), FADE_STEPS as usize);
+        assert_eq!(steps.last(), Some(&4000));
+        assert!(steps.windows(2).all(|w| w[1] < w[0]));
+        // Small schedule ticks are a single step
+        assert_eq!(fade_steps(5000, 4980), vec![4980]);
+    }
+
+    // Applied temperatures up to and including `until`
+    async fn applied_until(applied: &mut mpsc::UnboundedReceiver<u32>, until: u32) -> Vec<u32> {
+        let mut seen = Vec::new();
+        while let Some(temperature) = applied.recv().await {
+            seen.push(temperature);
+            if temperature == until {
+                break;
+            }
+        }
+        seen
+    }
+
+    #[tokio::test]
+    async fn pauses_while_recording() {
+        let clock = FakeClock::at("2024-03-20T12:00:00+00:00");
+        let (mode_tx, mode_rx) = watch::channel(NightLightMode::Manual { temperature: 4000 });
+        let (recording_tx, recording_rx) = watch::channel(false);
+        let (applied_tx, mut applied) = mpsc::unbounded_channel();
+        let service = tokio::spawn(async move {
+            drive(&clock, mode_rx, recording_rx, |temperature| {
+                let _ = applied_tx.send(temperature);
+                async { Ok(()) }
+            })
+            .await
+        });
+
+        // Straight to the first temperature, there's nothing to fade from
+        assert_eq!(applied_until(&mut applied, 4000).await, vec![4000]);
+
+        recording_tx.send_replace(true);
+        let lifted = applied_until(&mut applied, IDENTITY_TEMPERATURE).await;
+        assert!(lifted.len() > 1);
+        assert!(lifted.windows(2).all(|w| w[1] > w[0]));
+
+        recording_tx.send_replace(false);
+        let restored = applied_until(&mut applied, 4000).await;
+        assert!(restored.len() > 1);
+        assert!(restored.windows(2).all(|w| w[1] < w[0]));
+
+        // A manual change fades too
+        mode_tx.send_replace(NightLightMode::Manual { temperature: 3000 });
+        let warmer = applied_until(&mut applied, 3000).await;
+        assert!(warmer.len() > 1);
+
+        drop(mode_tx);
+        drop(recording_tx);
+        service.await.unwrap();
+    }
+}
@@ -0,0 +1,259 @@
+use crate::display_manager::monitor_control;
+use crate::display_manager::monitor_profiles::{self, MonitorProfile};
+use crate::display_manager::monitor_types::Monitor;
+use anyhow::Result;
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use zbus::{proxy, Connection};
+
+#[proxy(
+    interface = "org.freedesktop.UPower",
+    default_service = "org.freedesktop.UPower",
+    default_path = "/org/freedesktop/UPower",
+    gen_blocking = false
+)]
+trait UPower {
+    #[zbus(property)]
+    fn on_battery(&self) -> zbus::Result<bool>;
+}
+
+// Mode and VRR to use for a monitor in one power state. `mode` is a
+// Hyprland mode string such as "1920x1080@60"; None keeps the current one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ModeOverride {
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub vrr: Option<bool>,
+}
+
+const SYSFS_POLL: Duration = Duration::from_secs(5);
+
+static POLICY_TASK: Lazy<Mutex<Option<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+// Refresh rate of the native mode. The current rate is no guide once the
+// battery policy has already dropped the panel to 60 Hz.
+fn native_refresh_rate(monitor: &Monitor) -> f64 {
+    monitor
+        .preferred_mode()
+        .and_then(|mode| mode.split_once('@').and_then(|(_, rate)| rate.parse().ok()))
+        .unwrap_or(monitor.refresh_rate)
+}
+
+// What a monitor should run on battery: the user's override, or for a
+// high-refresh internal panel, the same resolution at 60 Hz with VRR on
+pub fn battery_override(monitor: &Monitor, profile: &MonitorProfile) -> Option<ModeOverride> {
+    if let Some(ref ov) = profile.battery_mode {
+        return Some(ov.clone());
+    }
+    if monitor.is_internal() && native_refresh_rate(monitor) > 61.0 {
+        return Some(ModeOverride {
+            mode: Some(format!("{}x{}@60", monitor.width, monitor.height)),
+            vrr: Some(true),
+        });
+    }
+    None
+}
+
+pub fn override_rule(monitor: &Monitor, ov: &ModeOverride) -> String {
+    let mode = ov
+        .mode
+        .clone()
+        .unwrap_or_else(|| format!("{}x{}@{}", monitor.width, monitor.height, monitor.refresh_rate));
+    let vrr = ov.vrr.unwrap_or(monitor.vrr);
+    format!(
+        "keyword monitor {},{},{}x{},{},transform,{},vrr,{}",
+        monitor.name, mode, monitor.x, monitor.y, monitor.scale, monitor.transform, vrr as i32
+    )
+}
+
+// What a monitor should run on AC: the user's override, or, if the battery
+// policy touches it, its native mode with VRR back off. Never taken from the
+// current layout, which may already be the battery one.
+pub fn ac_override(monitor: &Monitor, profile: &MonitorProfile) -> Option<ModeOverride> {
+    if let Some(ref ov) = profile.ac_mode {
+        return Some(ov.clone());
+    }
+    battery_override(monitor, profile).map(|battery| ModeOverride {
+        mode: Some(monitor.preferred_mode().unwrap_or_else(|| "preferred".to_string())),
+        vrr: battery.vrr.map(|_| false),
+    })
+}
+
+// Commands for switching every monitor to the given power state
+pub fn power_state_commands(
+    on_battery: bool,
+    monitors: &[Monitor],
+    profiles: &HashMap<String, MonitorProfile>,
+) -> Vec<String> {
+    monitors
+        .iter()
+        .filter_map(|monitor| {
+            let default_profile = MonitorProfile::default();
+            let profile = profiles
+                .get(&monitor_profiles::profile_key(monitor))
+                .unwrap_or(&default_profile);
+            let ov = if on_battery {
+                battery_override(monitor, profile)
+            } else {
+                ac_override(monitor, profile)
+            };
+            ov.map(|ov| override_rule(monitor, &ov))
+        })
+        .collect()
+}
+
+pub async fn apply_power_state(on_battery: bool) -> Result<()> {
+    info!("Applying {} display modes", if on_battery { "battery" } else { "AC" });
+    let monitors = monitor_control::get_monitors().await?;
+    let profiles = monitor_profiles::load_profiles();
+    let commands = power_state_commands(on_battery, &monitors, &profiles);
+    monitor_control::run_hyprctl_batch(&commands.join("; ")).await
+}
+
+// Fallback when UPower is unavailable: on battery unless some mains
+// adapter reports online
+pub fn on_battery_sysfs() -> Option<bool> {
+    let entries = fs::read_dir("/sys/class/power_supply").ok()?;
+    let mut found_mains = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let kind = fs::read_to_string(path.join("type")).unwrap_or_default();
+        if kind.trim() == "Mains" {
+            found_mains = true;
+            if fs::read_to_string(path.join("online")).unwrap_or_default().trim() == "1" {
+                return Some(false);
+            }
+        }
+    }
+    found_mains.then_some(true)
+}
+
+// Follow power state changes until stopped. A change only takes effect once
+// it has held for `delay`, so a flaky connector does not flap the modes.
+pub async fn run_power_policy(connection: Option<Connection>, delay: Duration) -> Result<()> {
+    let proxy = match connection {
+        Some(ref connection) => Some(UPowerProxy::new(connection).await?),
+        None => None,
+    };
+    let mut changes = match proxy {
+        Some(ref proxy) => Some(proxy.receive_on_battery_changed().await),
+        None => None,
+    };
+    // UPower can be running yet unable to answer (no battery device, daemon
+    // restarting), so fall back to sysfs whenever it gives nothing
+    let read_state = || async {
+        match proxy {
+            Some(ref proxy) => match proxy.on_battery().await.ok() {
+                Some(on_battery) => Some(on_battery),
+                None => on_battery_sysfs(),
+            },
+            None => on_battery_sysfs(),
+        }
+    };
+
+    let mut applied = None;
+    loop {
+        let Some(on_battery) = read_state().await else {
+            return Err(anyhow::anyhow!("Could not determine power state"));
+        };
+        if applied != Some(on_battery) {
+            if applied.is_some() {
+                // Debounce: wait, then re-check before acting
+                tokio::time::sleep(delay).await;
+                if read_state().await != Some(on_battery) {
+                    continue;
+                }
+            }
+            match apply_power_state(on_battery).await {
+                Ok(()) => applied = Some(on_battery),
+                Err(e) => warn!("Failed to apply power modes: {}", e),
+            }
+        }
+        match changes {
+            Some(ref mut changes) => {
+                if changes.next().await.is_none() {
+                    return Ok(());
+                }
+            }
+            None => tokio::time::sleep(SYSFS_POLL).await,
+        }
+    }
+}
+
+// Start the policy on the system bus, falling back to polling sysfs
+pub fn start_power_policy(delay: Duration) {
+    let mut task = POLICY_TASK.lock().unwrap();
+    if task.as_ref().is_some_and(|t| !t.is_finished()) {
+        return;
+    }
+    *task = Some(tokio::spawn(async move {
+        let connection = match Connection::system().await {
+            Ok(connection) => Some(connection),
+            Err(e) => {
+                warn!("UPower unavailable, polling /sys/class/power_supply: {}", e);
+                None
+            }
+        };
+        if let Err(e) = run_power_policy(connection, delay).await {
+            warn!("Power policy stopped: {}", e);
+        }
+    }));
+}
+
+pub fn stop_power_policy() {
+    if let Some(task) = POLICY_TASK.lock().unwrap().take() {
+        task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display_manager::monitor_types::test_support::test_monitor;
+
+    fn fast_panel(refresh_rate: f64) -> Monitor {
+        let mut panel = test_monitor("eDP-1", 0);
+        panel.width = 2560;
+        panel.height = 1600;
+        panel.refresh_rate = refresh_rate;
+        panel.available_modes = vec!["2560x1600@165.00Hz".to_string(), "2560x1600@60.00Hz".to_string()];
+        panel
+    }
+
+    #[test]
+    fn battery_drops_fast_panel_to_60hz() {
+        let commands = power_state_commands(true, &[fast_panel(165.0), test_monitor("DP-1", 2560)], &HashMap::new());
+        assert_eq!(commands, vec!["keyword monitor eDP-1,2560x1600@60,0x0,1,transform,0,vrr,1"]);
+    }
+
+    #[test]
+    fn ac_restores_native_mode_even_when_started_on_battery() {
+        // Already at 60 Hz with VRR on, as left by the battery policy
+        let mut panel = fast_panel(60.0);
+        panel.vrr = true;
+        let commands = power_state_commands(false, &[panel], &HashMap::new());
+        assert_eq!(commands, vec!["keyword monitor eDP-1,2560x1600@165.00,0x0,1,transform,0,vrr,0"]);
+    }
+
+    #[test]
+    fn ac_profile_override_wins() {
+        let panel = fast_panel(60.0);
+        let profile = MonitorProfile {
+            ac_mode: Some(ModeOverride { mode: Some("2560x1600@120".to_string()), vrr: None }),
+            ..Default::default()
+        };
+        let profiles = HashMap::from([(monitor_profiles::profile_key(&panel), profile)]);
+        assert_eq!(
+            power_state_commands(false, &[panel], &profiles),
+            vec!["keyword monitor eDP-1,2560x1600@120,0x0,1,transform,0,vrr,0"]
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use crate::display_manager::docking;
+use crate::display_manager::monitor_control;
+use crate::display_manager::monitor_types::Monitor;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tracing::info;
+
+// Outputs connected (enabled or not) at the last hotplug event. Hyprland
+// also sends `monitoradded` when an output it already had is turned back
+// on, e.g. the panel after the lid opens; those aren't new screens.
+static KNOWN_OUTPUTS: Lazy<Mutex<Option<HashSet<String>>>> = Lazy::new(|| Mutex::new(None));
+
+// The four Win+P style choices offered when a new output shows up
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectionMode {
+    PcScreenOnly,
+    Duplicate,
+    Extend,
+    SecondScreenOnly,
+}
+
+impl ProjectionMode {
+    pub const ALL: [ProjectionMode; 4] = [
+        ProjectionMode::PcScreenOnly,
+        ProjectionMode::Duplicate,
+        ProjectionMode::Extend,
+        ProjectionMode::SecondScreenOnly,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProjectionMode::PcScreenOnly => "PC screen only",
+            ProjectionMode::Duplicate => "Duplicate",
+            ProjectionMode::Extend => "Extend",
+            ProjectionMode::SecondScreenOnly => "Second screen only",
+        }
+    }
+
+    pub fn icon_name(&self) -> &'static str {
+        match self {
+            ProjectionMode::PcScreenOnly => "computer-symbolic",
+            ProjectionMode::Duplicate => "view-mirror-symbolic",
+            ProjectionMode::Extend => "view-dual-symbolic",
+            ProjectionMode::SecondScreenOnly => "video-display-symbolic",
+        }
+    }
+}
+
+// Build the hyprctl commands that put `primary` (the built-in panel) and
+// `second` (the newly plugged output) into the requested arrangement.
+pub fn projection_commands(mode: ProjectionMode, primary: &Monitor, second: &Monitor) -> Vec<String> {
+    match mode {
+        ProjectionMode::PcScreenOnly => vec![
+            monitor_control::enable_rule(primary),
+            format!("keyword monitor {},disable", second.name),
+        ],
+        ProjectionMode::Duplicate => vec![
+            monitor_control::enable_rule(primary),
+            format!("keyword monitor {},preferred,auto,1,mirror,{}", second.name, primary.name),
+        ],
+        ProjectionMode::Extend => vec![
+            monitor_control::enable_rule(primary),
+            format!("keyword monitor {},preferred,auto-right,1", second.name),
+        ],
+        // Enable the new output before disabling the panel so Hyprland
+        // never ends up without an active monitor.
+        ProjectionMode::SecondScreenOnly => vec![
+            format!("keyword monitor {},preferred,auto,1", second.name),
+            format!("keyword monitor {},disable", primary.name),
+        ],
+    }
+}
+
+// Pick the output the projection is relative to: the internal panel if
+// there is one, otherwise the first other monitor.
+pub fn pick_primary<'a>(monitors: &'a [Monitor], second_name: &str) -> Option<&'a Monitor> {
+    let others = || monitors.iter().filter(|m| m.name != second_name);
+    others()
+        .find(|m| m.is_internal())
+        .or_else(|| others().next())
+}
+
+// Outputs still showing something after `mode` is applied. A panel under
+// a closed lid doesn't count.
+pub fn usable_outputs_after(
+    mode: ProjectionMode,
+    monitors: &[Monitor],
+    primary: &Monitor,
+    second: &Monitor,
+    lid_closed: bool,
+) -> usize {
+    monitors
+        .iter()
+        .filter(|m| {
+            let enabled = if m.name == primary.name {
+                mode != ProjectionMode::SecondScreenOnly
+            } else if m.name == second.name {
+                mode != ProjectionMode::PcScreenOnly
+            } else {
+                !m.disabled
+            };
+            enabled && !(lid_closed && m.is_internal())
+        })
+        .count()
+}
+
+// Record the connected outputs and return the ones known before, or None
+// on the first call
+pub fn remember_outputs(monitors: &[Monitor]) -> Option<HashSet<String>> {
+    let names = monitors.iter().map(|m| m.name.clone()).collect();
+    KNOWN_OUTPUTS.lock().unwrap().replace(names)
+}
+
+pub async fn refresh_known_outputs() -> anyhow::Result<()> {
+    remember_outputs(&monitor_control::get_all_monitors().await?);
+    Ok(())
+}
+
+// Follows the docking rule of never leaving zero usable outputs, so a
+// remembered "PC screen only" can't black out a docked laptop
+pub async fn apply_projection(mode: ProjectionMode, primary: &Monitor, second: &Monitor) -> anyhow::Result<()> {
+    let monitors = monitor_control::get_all_monitors().await?;
+    let lid_closed = docking::lid_closed().unwrap_or(false);
+    if usable_outputs_after(mode, &monitors, primary, second, lid_closed) == 0 {
+        return Err(anyhow::anyhow!("{} would leave no usable screen", mode.label()));
+    }
+    info!("Applying projection {:?}: primary {}, second {}", mode, primary.name, second.name);
+    let batch = projection_commands(mode, primary, second).join("; ");
+    monitor_control::run_hyprctl_batch(&batch).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display_manager::monitor_types::test_support::test_monitor;
+
+    #[test]
+    fn commands_per_mode() {
+        let panel = test_monitor("eDP-1", 0);
+        let external = test_monitor("HDMI-A-1", 1920);
+        let enable_panel = "keyword monitor eDP-1,1920x1080@60,0x0,1,transform,0";
+        assert_eq!(
+            projection_commands(ProjectionMode::PcScreenOnly, &panel, &external),
+            vec![enable_panel, "keyword monitor HDMI-A-1,disable"]
+        );
+        assert_eq!(
+            projection_commands(ProjectionMode::Duplicate, &panel, &external),
+            vec![enable_panel, "keyword monitor HDMI-A-1,preferred,auto,1,mirror,eDP-1"]
+        );
+        assert_eq!(
+            projection_commands(ProjectionMode::Extend, &panel, &external),
+            vec![enable_panel, "keyword monitor HDMI-A-1,preferred,auto-right,1"]
+        );
+        // The new output comes up before the panel goes down
+        assert_eq!(
+            projection_commands(ProjectionMode::SecondScreenOnly, &panel, &external),
+            vec!["keyword monitor HDMI-A-1,preferred,auto,1", "keyword monitor eDP-1,disable"]
+        );
+    }
+
+    #[test]
+    fn primary_prefers_internal_panel() {
+        let monitors = [test_monitor("DP-1", 0), test_monitor("eDP-1", 1920), test_monitor("HDMI-A-1", 3840)];
+        assert_eq!(pick_primary(&monitors, "HDMI-A-1").unwrap().name, "eDP-1");
+        // The new output is never its own primary, even when it's the panel
+        assert_eq!(pick_primary(&monitors, "eDP-1").unwrap().name, "DP-1");
+    }
+
+    #[test]
+    fn primary_falls_back_to_first_other_output() {
+        let monitors = [test_monitor("DP-1", 0), test_monitor("HDMI-A-1", 1920)];
+        assert_eq!(pick_primary(&monitors, "HDMI-A-1").unwrap().name, "DP-1");
+        assert_eq!(pick_primary(&monitors, "").unwrap().name, "DP-1");
+        assert!(pick_primary(&monitors[1..], "HDMI-A-1").is_none());
+    }
+
+    #[test]
+    fn closed_lid_panel_is_not_usable() {
+        let panel = test_monitor("eDP-1", 0);
+        let external = test_monitor("HDMI-A-1", 1920);
+        let monitors = [panel.clone(), external.clone()];
+        let usable = |mode, lid_closed| usable_outputs_after(mode, &monitors, &panel, &external, lid_closed);
+        assert_eq!(usable(ProjectionMode::PcScreenOnly, false), 1);
+        assert_eq!(usable(ProjectionMode::PcScreenOnly, true), 0);
+        assert_eq!(usable(ProjectionMode::Extend, true), 1);
+        assert_eq!(usable(ProjectionMode::SecondScreenOnly, true), 1);
+    }
+}
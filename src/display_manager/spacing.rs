@@ -0,0 +1,167 @@
+use crate::display_manager::monitor_control;
+use crate::display_manager::monitor_profiles::{self, MonitorProfile};
+use crate::display_manager::monitor_types::Monitor;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use tracing::info;
+
+// Extra space kept free at the monitor edges (Hyprland `addreserved`), on
+// top of whatever bars already reserve
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReservedArea {
+    pub top: i32,
+    pub bottom: i32,
+    pub left: i32,
+    pub right: i32,
+}
+
+impl ReservedArea {
+    // hyprctl reports the total reserved area as [left, top, right, bottom]
+    pub fn from_hyprctl(reserved: [i32; 4]) -> Self {
+        Self { left: reserved[0], top: reserved[1], right: reserved[2], bottom: reserved[3] }
+    }
+}
+
+// Gap and border sizes for workspaces on one monitor; None keeps the
+// global value
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GapOverride {
+    #[serde(default)]
+    pub gaps_in: Option<i32>,
+    #[serde(default)]
+    pub gaps_out: Option<i32>,
+    #[serde(default)]
+    pub border_size: Option<i32>,
+}
+
+impl GapOverride {
+    pub fn is_empty(&self) -> bool {
+        self.gaps_in.is_none() && self.gaps_out.is_none() && self.border_size.is_none()
+    }
+}
+
+// Commands that bring a monitor in line with its profile. Runtime keywords
+// cannot be removed one by one, so a cleared setting is written back with
+// zero reserved space or the global gap/border values.
+pub fn spacing_commands(monitor_name: &str, profile: &MonitorProfile, globals: &GapOverride) -> Vec<String> {
+    let reserved = profile.reserved.unwrap_or_default();
+    let mut commands = vec![format!(
+        "keyword monitor {},addreserved,{},{},{},{}",
+        monitor_name, reserved.top, reserved.bottom, reserved.left, reserved.right
+    )];
+    let gaps = profile.gaps.unwrap_or_default();
+    let rules: Vec<String> = [
+        ("gapsin", gaps.gaps_in.or(globals.gaps_in)),
+        ("gapsout", gaps.gaps_out.or(globals.gaps_out)),
+        ("bordersize", gaps.border_size.or(globals.border_size)),
+    ]
+    .iter()
+    .filter_map(|(rule, value)| value.map(|v| format!("{}:{}", rule, v)))
+    .collect();
+    if !rules.is_empty() {
+        commands.push(format!("keyword workspace m[{}],{}", monitor_name, rules.join(",")));
+    }
+    commands
+}
+
+// Integer value of a Hyprland option. Gaps are CSS-style ("5 5 5 5"), in
+// which case the first side is used.
+async fn global_option(name: &str) -> Option<i32> {
+    let output = Command::new("hyprctl").arg("getoption").arg(name).arg("-j").output().await.ok()?;
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    if let Some(int) = value.get("int").and_then(|v| v.as_i64()) {
+        return Some(int as i32);
+    }
+    value
+        .get("custom")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.split_whitespace().next())
+        .and_then(|s| s.parse().ok())
+}
+
+async fn global_gaps() -> GapOverride {
+    GapOverride {
+        gaps_in: global_option("general:gaps_in").await,
+        gaps_out: global_option("general:gaps_out").await,
+        border_size: global_option("general:border_size").await,
+    }
+}
+
+pub async fn apply_spacing(monitor: &Monitor) -> Result<()> {
+    let profile = monitor_profiles::get_profile(monitor);
+    let commands = spacing_commands(&monitor.name, &profile, &global_gaps().await);
+    monitor_control::run_hyprctl_batch(&commands.join("; ")).await
+}
+
+pub async fn set_monitor_spacing(monitor: &Monitor, reserved: Option<ReservedArea>, gaps: Option<GapOverride>) -> Result<()> {
+    monitor_profiles::update_profile(monitor, |p| {
+        p.reserved = reserved;
+        p.gaps = gaps.filter(|g| !g.is_empty());
+    })?;
+    apply_spacing(monitor).await
+}
+
+// Restore saved spacing on every connected monitor (hotplug, config reload)
+pub async fn reapply_spacing() -> Result<()> {
+    let monitors = monitor_control::get_monitors().await?;
+    let profiles = monitor_profiles::load_profiles();
+    let globals = global_gaps().await;
+    let mut commands = Vec::new();
+    for monitor in &monitors {
+        if let Some(profile) = profiles.get(&monitor_profiles::profile_key(monitor)) {
+            if profile.reserved.is_some() || profile.gaps.is_some() {
+                info!("Restoring reserved space and gaps on {}", monitor.name);
+                commands.extend(spacing_commands(&monitor.name, profile, &globals));
+            }
+        }
+    }
+    monitor_control::run_hyprctl_batch(&commands.join("; ")).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hyprctl_order_is_left_top_right_bottom() {
+        assert_eq!(
+            ReservedArea::from_hyprctl([1, 2, 3, 4]),
+            ReservedArea { left: 1, top: 2, right: 3, bottom: 4 }
+        );
+    }
+
+    #[test]
+    fn addreserved_takes_top_bottom_left_right() {
+        let profile = MonitorProfile {
+            reserved: Some(ReservedArea { top: 30, bottom: 0, left: 60, right: 10 }),
+            gaps: Some(GapOverride { gaps_in: Some(2), gaps_out: None, border_size: Some(0) }),
+            ..Default::default()
+        };
+        let globals = GapOverride { gaps_in: Some(5), gaps_out: Some(20), border_size: Some(2) };
+        assert_eq!(
+            spacing_commands("DP-1", &profile, &globals),
+            vec![
+                "keyword monitor DP-1,addreserved,30,0,60,10",
+                "keyword workspace m[DP-1],gapsin:2,gapsout:20,bordersize:0",
+            ]
+        );
+    }
+
+    #[test]
+    fn cleared_profile_writes_back_defaults() {
+        let globals = GapOverride { gaps_in: Some(5), gaps_out: Some(20), border_size: Some(2) };
+        assert_eq!(
+            spacing_commands("eDP-1", &MonitorProfile::default(), &globals),
+            vec![
+                "keyword monitor eDP-1,addreserved,0,0,0,0",
+                "keyword workspace m[eDP-1],gapsin:5,gapsout:20,bordersize:2",
+            ]
+        );
+        // Globals that could not be read leave the workspace rule out
+        assert_eq!(
+            spacing_commands("eDP-1", &MonitorProfile::default(), &GapOverride::default()),
+            vec!["keyword monitor eDP-1,addreserved,0,0,0,0"]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crate::display_manager::monitor_control;
+use crate::display_manager::monitor_profiles::{self, MonitorProfile};
+use crate::display_manager::monitor_types::Monitor;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+use tracing::{info, warn};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FitMode {
+    #[default]
+    Cover,
+    Contain,
+    Tile,
+}
+
+impl FitMode {
+    pub const ALL: [FitMode; 3] = [FitMode::Cover, FitMode::Contain, FitMode::Tile];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FitMode::Cover => "Cover",
+            FitMode::Contain => "Contain",
+            FitMode::Tile => "Tile",
+        }
+    }
+
+    // hyprpaper selects the mode with a prefix on the path
+    fn path_prefix(&self) -> &'static str {
+        match self {
+            FitMode::Cover => "",
+            FitMode::Contain => "contain:",
+            FitMode::Tile => "tile:",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Wallpaper {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub fit: FitMode,
+}
+
+// Client for hyprpaper's request socket. The socket path is explicit so a
+// stand-in listener can take its place.
+pub struct HyprpaperClient {
+    socket: PathBuf,
+}
+
+impl HyprpaperClient {
+    pub fn new(socket: impl Into<PathBuf>) -> Self {
+        Self { socket: socket.into() }
+    }
+
+    // $XDG_RUNTIME_DIR/hypr/<signature>/.hyprpaper.sock
+    pub fn from_env() -> Result<Self> {
+        let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
+            .map_err(|_| anyhow::anyhow!("HYPRLAND_INSTANCE_SIGNATURE is not set; is Hyprland running?"))?;
+        let runtime_dir = std::env::var("XDG_RUNTIME_DIR")
+            .map_err(|_| anyhow::anyhow!("XDG_RUNTIME_DIR is not set"))?;
+        Ok(Self::new(Path::new(&runtime_dir).join("hypr").join(signature).join(".hyprpaper.sock")))
+    }
+
+    // One request per connection; hyprpaper answers "ok" or an error message
+    pub async fn request(&self, command: &str) -> Result<()> {
+        let mut stream = UnixStream::connect(&self.socket).await?;
+        stream.write_all(command.as_bytes()).await?;
+        stream.shutdown().await?;
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).await?;
+        if reply.trim() == "ok" {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("hyprpaper rejected `{}`: {}", command, reply.trim()))
+        }
+    }
+
+    pub async fn set_wallpaper(&self, monitor: &str, wallpaper: &Wallpaper) -> Result<()> {
+        let path = wallpaper.path.to_string_lossy();
+        self.request(&format!("preload {}", path)).await?;
+        self.request(&format!("wallpaper {},{}{}", monitor, wallpaper.fit.path_prefix(), path)).await?;
+        // Free images no monitor shows any more
+        self.request("unload unused").await
+    }
+}
+
+// Set and remember the wallpaper for one monitor
+pub async fn set_monitor_wallpaper(monitor: &Monitor, wallpaper: Wallpaper) -> Result<()> {
+    HyprpaperClient::from_env()?.set_wallpaper(&monitor.name, &wallpaper).await?;
+    monitor_profiles::update_profile(monitor, |p| p.wallpaper = Some(wallpaper))?;
+    Ok(())
+}
+
+// Put every connected monitor's saved wallpaper back on it. Called after
+// hotplug, when outputs may have swapped connector names.
+pub async fn reapply_wallpapers() -> Result<()> {
+    let monitors = monitor_control::get_monitors().await?;
+    let profiles = monitor_profiles::load_profiles();
+    restore_wallpapers(&HyprpaperClient::from_env()?, &monitors, &profiles).await
+}
+
+// One monitor failing (a deleted image, say) doesn't stop the others; the
+// failures are reported together at the end
+pub async fn restore_wallpapers(
+    client: &HyprpaperClient,
+    monitors: &[Monitor],
+    profiles: &HashMap<String, MonitorProfile>,
+) -> Result<()> {
+    let mut failures = Vec::new();
+    for monitor in monitors {
+        let key = monitor_profiles::profile_key(monitor);
+        if let Some(wallpaper) = profiles.get(&key).and_then(|p| p.wallpaper.as_ref()) {
+            info!("Restoring wallpaper {:?} on {}", wallpaper.path, monitor.name);
+            if let Err(e) = client.set_wallpaper(&monitor.name, wallpaper).await {
+                warn!("Failed to restore wallpaper on {}: {}", monitor.name, e);
+                failures.push(format!("{}: {}", monitor.name, e));
+            }
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("{}", failures.join("; ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display_manager::monitor_types::test_support::test_monitor;
+    use tokio::net::UnixListener;
+    use tokio::sync::mpsc;
+
+    // Answers every request like hyprpaper, rejecting wallpapers whose
+    // path contains "missing", and passes each request on
+    fn stand_in_hyprpaper(socket: &Path) -> mpsc::UnboundedReceiver<String> {
+        let listener = UnixListener::bind(socket).unwrap();
+        let (sender, requests) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = String::new();
+                stream.read_to_string(&mut request).await.unwrap();
+                let reply = if request.contains("missing") { "wallpaper failed (not preloaded)" } else { "ok" };
+                let _ = sender.send(request);
+                stream.write_all(reply.as_bytes()).await.unwrap();
+            }
+        });
+        requests
+    }
+
+    fn profile(path: &str, fit: FitMode) -> MonitorProfile {
+        MonitorProfile { wallpaper: Some(Wallpaper { path: PathBuf::from(path), fit }), ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn restores_every_monitor_and_reports_failures() {
+        let dir = std::env::temp_dir().join(format!("hyprpaper-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join(".hyprpaper.sock");
+        let _ = std::fs::remove_file(&socket);
+        let mut requests = stand_in_hyprpaper(&socket);
+
+        let monitors = [test_monitor("DP-1", 0), test_monitor("HDMI-A-1", 1920), test_monitor("eDP-1", 3840)];
+        let profiles = HashMap::from([
+            ("DP-1".to_string(), profile("/walls/missing.png", FitMode::Cover)),
+            ("HDMI-A-1".to_string(), profile("/walls/lake.jpg", FitMode::Contain)),
+            ("eDP-1".to_string(), profile("/walls/tiles.png", FitMode::Tile)),
+        ]);
+        let result = restore_wallpapers(&HyprpaperClient::new(&socket), &monitors, &profiles).await;
+
+        let error = result.unwrap_err().to_string();
+        assert!(error.starts_with("DP-1: hyprpaper rejected"), "{}", error);
+        assert!(!error.contains("HDMI-A-1") && !error.contains("eDP-1"), "{}", error);
+        let mut seen = Vec::new();
+        while let Ok(request) = requests.try_recv() {
+            seen.push(request);
+        }
+        assert_eq!(
+            seen,
+            vec![
+                "preload /walls/missing.png",
+                "preload /walls/lake.jpg",
+                "wallpaper HDMI-A-1,contain:/walls/lake.jpg",
+                "unload unused",
+                "preload /walls/tiles.png",
+                "wallpaper eDP-1,tile:/walls/tiles.png",
+                "unload unused",
+            ]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,45 +1,64 @@
 use anyhow::Result;
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
-use tracing::{info, error, warn};
-use glib::Sender;
+use tokio::net::UnixStream;
+use tracing::{debug, info, error, warn};
+use std::path::PathBuf;
+use async_channel::Sender;
 
 #[derive(Debug)]
 pub enum HyprlandEvent {
-    MonitorAdded,
-    MonitorRemoved,
+    MonitorAdded(String),
+    MonitorRemoved(String),
+    ConfigReloaded,
     Unknown(String),
 }
 
-pub async fn listen(sender: Sender<HyprlandEvent>) -> Result<()> {
-    info!("Starting hyprctl event listener");
-    let mut cmd = Command::new("hyprctl")
-        .arg("events")
-        .stdout(std::process::Stdio::piped())
-        .spawn()?;
+// Hyprland publishes its event stream on `.socket2.sock` inside the
+// instance directory ($XDG_RUNTIME_DIR/hypr/<signature>, or /tmp/hypr on
+// older releases).
+fn event_socket_path() -> Result<PathBuf> {
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
+        .map_err(|_| anyhow::anyhow!("HYPRLAND_INSTANCE_SIGNATURE is not set; is Hyprland running?"))?;
+    let runtime_dir = std::env::var("XDG_RUNTIME_DIR").map(PathBuf::from).ok();
+    let candidates = runtime_dir
+        .into_iter()
+        .map(|dir| dir.join("hypr"))
+        .chain(std::iter::once(PathBuf::from("/tmp/hypr")));
+    for base in candidates {
+        let path = base.join(&signature).join(".socket2.sock");
+        if path.exists() {
+            return Ok(path);
+        }
+    }
+    Err(anyhow::anyhow!("Could not find Hyprland event socket"))
+}
 
-    let stdout = cmd.stdout.take().expect("Failed to get stdout");
-    let mut reader = BufReader::new(stdout).lines();
+pub async fn listen(sender: Sender<HyprlandEvent>) -> Result<()> {
+    info!("Starting Hyprland event listener");
+    let stream = UnixStream::connect(event_socket_path()?).await?;
+    let mut reader = BufReader::new(stream).lines();
 
     while let Some(line) = reader.next_line().await? {
-        info!("Received event: {}", line);
+        debug!("Received event: {}", line);
         let event = parse_event(&line);
-        if let Err(_) = sender.send(event) {
+        if sender.send(event).await.is_err() {
             error!("Failed to send event to UI thread. Receiver dropped.");
             break;
         }
         // TODO: Optionally handle event here if needed
     }
     
-    warn!("Hyprctl event stream ended.");
+    warn!("Hyprland event stream ended.");
     Ok(())
 }
 
 fn parse_event(line: &str) -> HyprlandEvent {
-    if line.starts_with("monitoradded>>") {
-        HyprlandEvent::MonitorAdded
-    } else if line.starts_with("monitorremoved>>") {
-        HyprlandEvent::MonitorRemoved
+    if let Some(name) = line.strip_prefix("monitoradded>>") {
+        HyprlandEvent::MonitorAdded(name.trim().to_string())
+    } else if let Some(name) = line.strip_prefix("monitorremoved>>") {
+        HyprlandEvent::MonitorRemoved(name.trim().to_string())
+    } else if line.starts_with("configreloaded>>") {
+        HyprlandEvent::ConfigReloaded
     } else {
         HyprlandEvent::Unknown(line.to_string())
     }
@@ -0,0 +1,335 @@
+use crate::screenshot::{Capture, CaptureTarget};
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+// Mode recorded for image files found in the screenshot directory that
+// were not captured by us
+pub const EXTERNAL_MODE: &str = "external";
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "webp", "avif"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GalleryEntry {
+    pub path: PathBuf,
+    pub mode: String, // capture mode key, as in `screenshot_sinks`
+    pub monitor: Option<String>,
+    pub window_class: Option<String>,
+    pub window_title: Option<String>,
+    pub timestamp: DateTime<Local>,
+}
+
+impl GalleryEntry {
+    pub fn file_name(&self) -> String {
+        self.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+    }
+
+    // Case-insensitive match on file name, monitor and window
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return true;
+        }
+        [
+            Some(self.file_name()),
+            self.monitor.clone(),
+            self.window_class.clone(),
+            self.window_title.clone(),
+        ]
+        .iter()
+        .flatten()
+        .any(|field| field.to_lowercase().contains(&query))
+    }
+}
+
+// One entry per file of a capture
+pub fn entries_for_capture(capture: &Capture, mode: &str, target: &CaptureTarget) -> Vec<GalleryEntry> {
+    let timestamp = Local::now();
+    capture
+        .files
+        .iter()
+        .enumerate()
+        .map(|(i, path)| {
+            let window = match target {
+                CaptureTarget::Window(client) => Some(client),
+                _ => None,
+            };
+            GalleryEntry {
+                path: path.clone(),
+                mode: mode.to_string(),
+                monitor: capture.outputs.get(i).cloned(),
+                window_class: window.map(|w| w.class.clone()),
+                window_title: window.map(|w| w.title.clone()),
+                timestamp,
+            }
+        })
+        .collect()
+}
+
+fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("hypr-xdisplay")
+}
+
+pub fn index_path() -> PathBuf {
+    data_dir().join("gallery.json")
+}
+
+// Newest last, as recorded
+pub fn load() -> Vec<GalleryEntry> {
+    load_from(&index_path())
+}
+
+// The functions below take the index path so tests can use a temporary one
+fn load_from(path: &Path) -> Vec<GalleryEntry> {
+    match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable gallery index {:?}: {}", path, e);
+            Vec::new()
+        }),
+        Err(_) => Vec::new(),
+    }
+}
+
+pub fn save(entries: &[GalleryEntry]) -> Result<()> {
+    save_to(&index_path(), entries)
+}
+
+fn save_to(path: &Path, entries: &[GalleryEntry]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Write then rename, so a crash never leaves a truncated index
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(entries)?)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
+
+pub fn record(new_entries: Vec<GalleryEntry>) -> Result<()> {
+    record_in(&index_path(), new_entries)
+}
+
+fn record_in(index: &Path, new_entries: Vec<GalleryEntry>) -> Result<()> {
+    let mut entries = load_from(index);
+    entries.retain(|e| !new_entries.iter().any(|n| n.path == e.path));
+    entries.extend(new_entries);
+    save_to(index, &entries)
+}
+
+// Index a derived file, such as an annotated copy, like its original
+pub fn record_copy(original: &Path, copy: &Path) -> Result<()> {
+    record_copy_in(&index_path(), original, copy)
+}
+
+fn record_copy_in(index: &Path, original: &Path, copy: &Path) -> Result<()> {
+    let entry = load_from(index)
+        .into_iter()
+        .find(|e| e.path == original)
+        .map(|e| GalleryEntry { path: copy.to_path_buf(), timestamp: Local::now(), ..e })
+        .unwrap_or_else(|| GalleryEntry {
+            path: copy.to_path_buf(),
+            mode: EXTERNAL_MODE.to_string(),
+            monitor: None,
+            window_class: None,
+            window_title: None,
+            timestamp: Local::now(),
+        });
+    record_in(index, vec![entry])
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+// Drop entries whose file is gone and add images in `dir` captured by
+// other tools. Returns whether anything changed.
+pub fn sync_with_dir(entries: &mut Vec<GalleryEntry>, dir: &Path) -> bool {
+    let before = entries.len();
+    entries.retain(|e| e.path.exists());
+    let mut changed = entries.len() != before;
+
+    let known: HashSet<PathBuf> = entries.iter().map(|e| e.path.clone()).collect();
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return changed;
+    };
+    let mut found: Vec<GalleryEntry> = read_dir
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_image(path) && !known.contains(path))
+        .map(|path| {
+            let timestamp = fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .map(DateTime::<Local>::from)
+                .unwrap_or_else(|_| Local::now());
+            GalleryEntry {
+                path,
+                mode: EXTERNAL_MODE.to_string(),
+                monitor: None,
+                window_class: None,
+                window_title: None,
+                timestamp,
+            }
+        })
+        .collect();
+    if !found.is_empty() {
+        changed = true;
+        entries.append(&mut found);
+        entries.sort_by_key(|e| e.timestamp);
+    }
+    changed
+}
+
+// Remove entries from the index without touching the files
+pub fn forget(paths: &[PathBuf]) -> Result<()> {
+    let mut entries = load();
+    entries.retain(|e| !paths.contains(&e.path));
+    save(&entries)
+}
+
+// Move files into `dir`, keeping their index entries. Returns the files
+// that could not be moved.
+pub fn move_to(paths: &[PathBuf], dir: &Path) -> Result<Vec<PathBuf>> {
+    move_in(&index_path(), paths, dir)
+}
+
+fn move_in(index: &Path, paths: &[PathBuf], dir: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    let mut entries = load_from(index);
+    let mut failed = Vec::new();
+    for path in paths {
+        let Some(name) = path.file_name() else { continue };
+        let target = dir.join(name);
+        if target.exists() {
+            failed.push(path.clone());
+            continue;
+        }
+        // rename fails across filesystems; fall back to copy and remove
+        let moved = fs::rename(path, &target)
+            .or_else(|_| fs::copy(path, &target).and_then(|_| fs::remove_file(path)));
+        match moved {
+            Ok(()) => {
+                for entry in entries.iter_mut().filter(|e| &e.path == path) {
+                    entry.path = target.clone();
+                }
+            }
+            Err(e) => {
+                warn!("Failed to move {:?} to {:?}: {}", path, dir, e);
+                failed.push(path.clone());
+            }
+        }
+    }
+    save_to(index, &entries)?;
+    Ok(failed)
+}
+
+// Cached thumbnail location, keyed by path and modification time so an
+// edited file gets a fresh one
+pub fn thumbnail_path(path: &Path) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    if let Ok(modified) = fs::metadata(path).and_then(|m| m.modified()) {
+        modified.hash(&mut hasher);
+    }
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("hypr-xdisplay")
+        .join("thumbnails")
+        .join(format!("{:016x}.png", hasher.finish()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hypr-xdisplay-gallery-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn entry(path: &Path, mode: &str) -> GalleryEntry {
+        GalleryEntry {
+            path: path.to_path_buf(),
+            mode: mode.to_string(),
+            monitor: Some("DP-1".to_string()),
+            window_class: None,
+            window_title: None,
+            timestamp: Local.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap(),
+        }
+    }
+
+    #[test]
+    fn sync_drops_missing_files_and_adds_foreign_images() {
+        let dir = temp_dir("sync");
+        let kept = dir.join("kept.png");
+        let foreign = dir.join("other-tool.JPG");
+        fs::write(&kept, b"png").unwrap();
+        fs::write(&foreign, b"jpg").unwrap();
+        fs::write(dir.join("notes.txt"), b"text").unwrap();
+        let mut entries = vec![entry(&kept, "region"), entry(&dir.join("gone.png"), "region")];
+
+        assert!(sync_with_dir(&mut entries, &dir));
+        let paths: Vec<&Path> = entries.iter().map(|e| e.path.as_path()).collect();
+        // Sorted by time: the foreign file's mtime is now, after the fixed timestamp
+        assert_eq!(paths, vec![kept.as_path(), foreign.as_path()]);
+        assert_eq!(entries[1].mode, EXTERNAL_MODE);
+        assert!(!sync_with_dir(&mut entries, &dir));
+        assert!(!sync_with_dir(&mut entries, &dir.join("missing")));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn move_keeps_entries_and_skips_name_clashes() {
+        let dir = temp_dir("move");
+        let index = dir.join("gallery.json");
+        let target = dir.join("archive");
+        fs::create_dir_all(&target).unwrap();
+        let a = dir.join("a.png");
+        let b = dir.join("b.png");
+        fs::write(&a, b"a").unwrap();
+        fs::write(&b, b"b").unwrap();
+        fs::write(target.join("b.png"), b"already there").unwrap();
+        save_to(&index, &[entry(&a, "region"), entry(&b, "window")]).unwrap();
+
+        let failed = move_in(&index, &[a.clone(), b.clone(), dir.join("missing.png")], &target).unwrap();
+        assert_eq!(failed, vec![b.clone(), dir.join("missing.png")]);
+        assert!(!a.exists());
+        assert_eq!(fs::read(target.join("a.png")).unwrap(), b"a");
+        assert_eq!(fs::read(target.join("b.png")).unwrap(), b"already there");
+        let paths: Vec<PathBuf> = load_from(&index).into_iter().map(|e| e.path).collect();
+        assert_eq!(paths, vec![target.join("a.png"), b]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn copies_inherit_their_original() {
+        let dir = temp_dir("copy");
+        let index = dir.join("gallery.json");
+        let original = dir.join("shot.png");
+        save_to(&index, &[entry(&original, "window")]).unwrap();
+
+        record_copy_in(&index, &original, &dir.join("shot-annotated.png")).unwrap();
+        record_copy_in(&index, &dir.join("unknown.png"), &dir.join("unknown-annotated.png")).unwrap();
+        let entries = load_from(&index);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1].path, dir.join("shot-annotated.png"));
+        assert_eq!((entries[1].mode.as_str(), entries[1].monitor.as_deref()), ("window", Some("DP-1")));
+        assert_eq!((entries[2].mode.as_str(), entries[2].monitor.as_deref()), (EXTERNAL_MODE, None));
+        // Recording the same copy again replaces its entry
+        record_copy_in(&index, &original, &dir.join("shot-annotated.png")).unwrap();
+        assert_eq!(load_from(&index).len(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,7 +1,15 @@
 pub mod settings;
 pub mod screenshot;
+pub mod output_sinks;
+pub mod uploader;
+pub mod gallery;
+pub mod ocr;
+pub mod redaction;
+pub mod code_scanner;
 pub mod recorder;
+pub mod presentation;
 pub mod display_manager;
+pub mod event_listener;
 pub mod network_display;
 pub mod ui;
 pub mod wireless;
@@ -3,8 +3,10 @@ use libadwaita as adw;
 use adw::prelude::*;
 use std::sync::{Arc, Mutex};
 use tracing::{error, info, warn};
+use hypr_xdisplay::display_manager::monitor_control;
 use hypr_xdisplay::settings::Settings;
 use hypr_xdisplay::ui::main_window::{build_ui, AppState};
+use hypr_xdisplay::ui::{projection_switcher, screenshot_controls};
 
 #[tokio::main]
 async fn main() {
@@ -29,9 +31,53 @@ async fn main() {
     let app_state: AppState = Arc::new(Mutex::new(Vec::new()));
     let settings = Arc::new(Settings::new());
 
+    // `--project` opens the projection switcher, e.g. from a Hyprland
+    // keybinding: bind = SUPER, P, exec, hypr-xdisplay --project
+    let show_switcher = std::env::args().any(|arg| arg == "--project");
+
+    // `--dry-run` prints the hyprctl commands a layout change would send
+    // instead of running them
+    if std::env::args().any(|arg| arg == "--dry-run") {
+        monitor_control::set_dry_run(true);
+        info!("Dry run: monitor layout changes will not be applied");
+    }
+
+    application.connect_startup(|app| {
+        let switcher_action = gtk::gio::ActionEntry::builder("projection-switcher")
+            .activate(|_: &adw::Application, _, _| projection_switcher::show_projection_switcher())
+            .build();
+        let cancel_screenshot_action = gtk::gio::ActionEntry::builder("cancel-screenshot")
+            .activate(|_: &adw::Application, _, _| screenshot_controls::cancel_delayed_capture())
+            .build();
+        app.add_action_entries([switcher_action, cancel_screenshot_action]);
+    });
+
+    if show_switcher {
+        // Forward to the running instance instead of opening a second window
+        match application.register(gtk::gio::Cancellable::NONE) {
+            Ok(_) if application.is_remote() => {
+                application.activate_action("projection-switcher", None);
+                if let Some(connection) = application.dbus_connection() {
+                    if let Err(e) = connection.flush_sync(gtk::gio::Cancellable::NONE) {
+                        warn!("Failed to reach running instance: {}", e);
+                    }
+                }
+                return;
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to register application: {}", e),
+        }
+    }
+
     application.connect_activate(move |app| {
         build_ui(app, app_state.clone(), settings.clone());
+        if show_switcher {
+            projection_switcher::show_projection_switcher();
+        }
     });
 
-    application.run();
+    let args: Vec<String> = std::env::args()
+        .filter(|arg| arg != "--project" && arg != "--dry-run")
+        .collect();
+    application.run_with_args(&args);
 }
\ No newline at end of file
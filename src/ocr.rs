@@ -0,0 +1,212 @@
+use crate::output_sinks;
+use crate::settings::Settings;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use tracing::info;
+
+pub const DEFAULT_LANGUAGES: &str = "eng";
+
+// One recognised word, in pixels of the image it came from. Block,
+// paragraph and line numbers are tesseract's, for grouping words.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrWord {
+    pub text: String,
+    pub left: u32,
+    pub top: u32,
+    pub width: u32,
+    pub height: u32,
+    pub confidence: f32, // 0-100
+    pub block: u32,
+    pub paragraph: u32,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrResult {
+    pub image: PathBuf,
+    pub languages: String,
+    pub text: String,
+    pub words: Vec<OcrWord>,
+}
+
+// Words from `tesseract ... tsv` output. Rows of other levels (page, block,
+// paragraph, line) and empty words are skipped.
+pub fn parse_tsv(tsv: &str) -> Vec<OcrWord> {
+    tsv.lines()
+        .skip(1) // header
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.splitn(12, '\t').collect();
+            if fields.len() < 12 || fields[0] != "5" {
+                return None;
+            }
+            let text = fields[11].trim();
+            if text.is_empty() {
+                return None;
+            }
+            let number = |i: usize| fields[i].parse::<u32>().ok();
+            Some(OcrWord {
+                text: text.to_string(),
+                block: number(2)?,
+                paragraph: number(3)?,
+                line: number(4)?,
+                left: number(6)?,
+                top: number(7)?,
+                width: number(8)?,
+                height: number(9)?,
+                confidence: fields[10].parse().unwrap_or(0.0),
+            })
+        })
+        .collect()
+}
+
+// Plain text in reading order: spaces between words, a newline between
+// lines and a blank line between paragraphs
+pub fn text_from_words(words: &[OcrWord]) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&OcrWord> = None;
+    for word in words {
+        if let Some(prev) = previous {
+            if (prev.block, prev.paragraph) != (word.block, word.paragraph) {
+                text.push_str("\n\n");
+            } else if prev.line != word.line {
+                text.push('\n');
+            } else {
+                text.push(' ');
+            }
+        }
+        text.push_str(&word.text);
+        previous = Some(word);
+    }
+    text
+}
+
+// Run tesseract locally; nothing leaves the machine. `languages` is in
+// tesseract's `-l` syntax, e.g. "eng" or "eng+deu".
+pub async fn recognize(path: &Path, languages: &str) -> Result<OcrResult> {
+    let output = Command::new("tesseract")
+        .arg(path)
+        .arg("stdout")
+        .arg("-l")
+        .arg(languages)
+        .arg("tsv")
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to run tesseract: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!("tesseract failed: {}", stderr.trim()));
+    }
+    let words = parse_tsv(&String::from_utf8_lossy(&output.stdout));
+    Ok(OcrResult {
+        image: path.to_path_buf(),
+        languages: languages.to_string(),
+        text: text_from_words(&words),
+        words,
+    })
+}
+
+// Installed traineddata, without the orientation/script model
+pub async fn available_languages() -> Result<Vec<String>> {
+    let output = Command::new("tesseract").arg("--list-langs").output().await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .skip(1) // "List of available languages in ..."
+        .map(str::trim)
+        .filter(|lang| !lang.is_empty() && *lang != "osd")
+        .map(str::to_string)
+        .collect())
+}
+
+// "shot.png" -> "shot.ocr.json"
+pub fn sidecar_path(image: &Path) -> PathBuf {
+    image.with_extension("ocr.json")
+}
+
+pub fn write_sidecar(result: &OcrResult) -> Result<PathBuf> {
+    let path = sidecar_path(&result.image);
+    fs::write(&path, serde_json::to_string_pretty(result)?)?;
+    Ok(path)
+}
+
+// First half of the "capture text" pipeline, run on the raw capture:
+// post-processing can shrink, blur or re-encode the text away
+pub async fn recognize_capture(settings: &Settings, path: &Path) -> Result<OcrResult> {
+    let languages = settings.ocr_languages.as_deref().unwrap_or(DEFAULT_LANGUAGES);
+    let result = recognize(path, languages).await?;
+    info!("Recognised {} words in {:?}", result.words.len(), path);
+    Ok(result)
+}
+
+// Second half, once the capture has been delivered: write the word boxes
+// beside `image` (the kept file, if they still line up with it) and copy
+// the text
+pub async fn keep_and_copy(mut result: OcrResult, image: Option<&Path>) -> Result<OcrResult> {
+    if let Some(image) = image {
+        result.image = image.to_path_buf();
+        let sidecar = write_sidecar(&result)?;
+        info!("Word boxes for {:?} in {:?}", image, sidecar);
+    }
+    if !result.text.is_empty() {
+        output_sinks::copy_text(&result.text).await?;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Trimmed `tesseract ... tsv` output: two lines in the first paragraph,
+    // one in the second
+    const TSV: &str = "\
+level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext
+1\t1\t0\t0\t0\t0\t0\t0\t800\t600\t-1\t
+2\t1\t1\t0\t0\t0\t10\t10\t300\t80\t-1\t
+3\t1\t1\t1\t0\t0\t10\t10\t300\t50\t-1\t
+4\t1\t1\t1\t1\t0\t10\t10\t200\t20\t-1\t
+5\t1\t1\t1\t1\t1\t10\t10\t60\t20\t96.5\tHello
+5\t1\t1\t1\t1\t2\t80\t10\t70\t20\t91.2\tworld
+5\t1\t1\t1\t1\t3\t160\t10\t10\t20\t95.0\t \t
+4\t1\t1\t1\t2\t0\t10\t40\t200\t20\t-1\t
+5\t1\t1\t1\t2\t1\t10\t40\t90\t20\t88.0\tsecond
+3\t1\t1\t2\t0\t0\t10\t70\t300\t20\t-1\t
+4\t1\t1\t2\t1\t0\t10\t70\t300\t20\t-1\t
+5\t1\t1\t2\t1\t1\t10\t70\t100\t20\t77.7\tparagraph
+5\t1\t1\t2\t1\t2\t120\t70\t20\t20\tbad\tok";
+
+    #[test]
+    fn parses_word_rows_only() {
+        let words = parse_tsv(TSV);
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, vec!["Hello", "world", "second", "paragraph", "ok"]);
+        assert_eq!(
+            words[1],
+            OcrWord {
+                text: "world".to_string(),
+                left: 80,
+                top: 10,
+                width: 70,
+                height: 20,
+                confidence: 91.2,
+                block: 1,
+                paragraph: 1,
+                line: 1,
+            }
+        );
+        // An unreadable confidence is kept as zero rather than dropping the word
+        assert_eq!(words[4].confidence, 0.0);
+        // The header alone, or nothing at all, yields no words
+        assert!(parse_tsv(TSV.lines().next().unwrap()).is_empty());
+        assert!(parse_tsv("").is_empty());
+    }
+
+    #[test]
+    fn lines_and_paragraphs_are_separated() {
+        assert_eq!(text_from_words(&parse_tsv(TSV)), "Hello world\nsecond\n\nparagraph ok");
+        assert_eq!(text_from_words(&[]), "");
+    }
+}
@@ -0,0 +1,274 @@
+use crate::screenshot::{self, Capture, CaptureTarget};
+use crate::settings::Settings;
+use crate::uploader;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+// Where a finished capture goes. Any combination can be enabled per capture
+// mode; they run in order, so with both clipboard sinks the later one wins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputSink {
+    Save,
+    CopyImage,
+    CopyUri,
+    Edit,
+    // Upload with the configured provider and copy the link
+    Upload,
+    // Shell command; `{file}` is replaced by the primary file, `{files}` by
+    // all of them, otherwise the files are appended
+    Command(String),
+}
+
+impl OutputSink {
+    pub fn label(&self) -> &'static str {
+        match self {
+            OutputSink::Save => "Save to file",
+            OutputSink::CopyImage => "Copy image",
+            OutputSink::CopyUri => "Copy file URI",
+            OutputSink::Edit => "Open in editor",
+            OutputSink::Upload => "Upload",
+            OutputSink::Command(_) => "Run command",
+        }
+    }
+}
+
+// Captures that are not saved still need a file for the other sinks
+fn unsaved_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("hypr-xdisplay")
+        .join("screenshots")
+}
+
+// Capture into the screenshot directory, or the cache when saving is off
+pub async fn capture_for(settings: &Settings, target: &CaptureTarget, sinks: &[OutputSink]) -> Result<Capture> {
+    if sinks.contains(&OutputSink::Save) {
+        return screenshot::capture(settings, target).await;
+    }
+    capture_staged(settings, target).await
+}
+
+// Capture into the cache. Captures that still need redacting start here, so
+// an unredacted file never lands in a directory that gets synced or backed
+// up; `move_into_place` saves them once they are clean.
+pub async fn capture_staged(settings: &Settings, target: &CaptureTarget) -> Result<Capture> {
+    let mut unsaved = settings.clone();
+    unsaved.screenshot_dir = unsaved_dir();
+    screenshot::capture(&unsaved, target).await
+}
+
+// Move a staged capture into the screenshot directory if the sinks save it.
+// Copies when the cache is on another filesystem.
+pub fn move_into_place(settings: &Settings, capture: &Capture, sinks: &[OutputSink]) -> Result<Capture> {
+    if !sinks.contains(&OutputSink::Save) {
+        return Ok(capture.clone());
+    }
+    std::fs::create_dir_all(&settings.screenshot_dir)?;
+    let mut files = Vec::with_capacity(capture.files.len());
+    for path in &capture.files {
+        let name = path.file_name().ok_or_else(|| anyhow::anyhow!("{:?} has no file name", path))?;
+        let target = settings.screenshot_dir.join(name);
+        if std::fs::rename(path, &target).is_err() {
+            std::fs::copy(path, &target)?;
+            std::fs::remove_file(path)?;
+        }
+        files.push(target);
+    }
+    Ok(Capture { files, outputs: capture.outputs.clone() })
+}
+
+// Run every sink, reporting all that failed rather than stopping at the first
+pub async fn deliver(settings: &Settings, capture: &Capture, sinks: &[OutputSink]) -> Result<()> {
+    let mut errors = Vec::new();
+    for sink in sinks {
+        let result = match (sink, capture.primary()) {
+            (OutputSink::Save, _) | (_, None) => Ok(()),
+            (OutputSink::CopyImage, Some(path)) => copy_image(path).await,
+            (OutputSink::CopyUri, Some(_)) => copy_uris(&capture.files).await,
+            // Without an external editor the UI opens the built-in one
+            (OutputSink::Edit, Some(path)) => match &settings.screenshot_editor {
+                Some(editor) => open_in_editor(editor, path),
+                None => Ok(()),
+            },
+            (OutputSink::Upload, Some(path)) => uploader::upload_and_copy(settings, path).await.map(|_| ()),
+            (OutputSink::Command(template), Some(_)) => run_command(template, &capture.files).await,
+        };
+        if let Err(e) = result {
+            warn!("Screenshot sink {:?} failed: {}", sink, e);
+            errors.push(format!("{}: {}", sink.label(), e));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(errors.join("; ")))
+    }
+}
+
+// Unsaved captures only sit in the cache for the sinks. Once those have run
+// the files are deleted, unless a sink handed out their path (a file URI,
+// an editor). True if they were deleted.
+pub fn discard_unsaved(capture: &Capture, sinks: &[OutputSink]) -> bool {
+    let keep = sinks
+        .iter()
+        .any(|sink| matches!(sink, OutputSink::Save | OutputSink::CopyUri | OutputSink::Edit));
+    if keep {
+        return false;
+    }
+    for path in &capture.files {
+        if let Err(e) = std::fs::remove_file(path) {
+            warn!("Failed to delete unsaved capture {:?}: {}", path, e);
+        }
+    }
+    true
+}
+
+pub fn image_mime_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref() {
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        Some("avif") => "image/avif",
+        _ => "image/png",
+    }
+}
+
+// Put the image data itself on the clipboard, so it pastes as a picture
+pub async fn copy_image(path: &Path) -> Result<()> {
+    let file = std::fs::File::open(path)?;
+    let status = Command::new("wl-copy")
+        .arg("--type")
+        .arg(image_mime_type(path))
+        .stdin(Stdio::from(file))
+        .status()
+        .await?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("wl-copy failed"));
+    }
+    info!("Copied {:?} to the clipboard", path);
+    Ok(())
+}
+
+// file:// URI with everything outside the unreserved set percent-encoded
+pub fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+pub async fn copy_uris(files: &[PathBuf]) -> Result<()> {
+    let list: String = files.iter().map(|f| format!("{}\r\n", file_uri(f))).collect();
+    let mut child = Command::new("wl-copy")
+        .arg("--type")
+        .arg("text/uri-list")
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(list.as_bytes()).await?;
+    }
+    if !child.wait().await?.success() {
+        return Err(anyhow::anyhow!("wl-copy failed"));
+    }
+    Ok(())
+}
+
+// Text goes through stdin, so it can be long or start with a dash
+pub async fn copy_text(text: &str) -> Result<()> {
+    let mut child = Command::new("wl-copy")
+        .arg("--type")
+        .arg("text/plain;charset=utf-8")
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).await?;
+    }
+    if !child.wait().await?.success() {
+        return Err(anyhow::anyhow!("wl-copy failed"));
+    }
+    Ok(())
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+pub fn command_line(template: &str, files: &[PathBuf]) -> String {
+    let quoted: Vec<String> = files.iter().map(|f| shell_quote(&f.to_string_lossy())).collect();
+    let all = quoted.join(" ");
+    if template.contains("{file}") || template.contains("{files}") {
+        let primary = quoted.first().cloned().unwrap_or_default();
+        template.replace("{files}", &all).replace("{file}", &primary)
+    } else {
+        format!("{} {}", template, all)
+    }
+}
+
+// Editors stay open, so they are started without waiting for them
+pub fn open_in_editor(editor: &str, path: &Path) -> Result<()> {
+    Command::new("sh")
+        .arg("-c")
+        .arg(command_line(editor, &[path.to_path_buf()]))
+        .spawn()?;
+    Ok(())
+}
+
+pub async fn run_command(template: &str, files: &[PathBuf]) -> Result<()> {
+    let line = command_line(template, files);
+    info!("Running screenshot command: {}", line);
+    let status = Command::new("sh").arg("-c").arg(&line).status().await?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("`{}` exited with {}", template, status));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsaved_captures_are_deleted_after_delivery() {
+        let dir = std::env::temp_dir().join(format!("hypr-xdisplay-sinks-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("shot.png");
+        std::fs::write(&path, b"png").unwrap();
+        let capture = Capture::from(path.clone());
+
+        assert!(!discard_unsaved(&capture, &[OutputSink::CopyImage, OutputSink::CopyUri]));
+        assert!(!discard_unsaved(&capture, &[OutputSink::Save]));
+        assert!(path.exists());
+        assert!(discard_unsaved(&capture, &[OutputSink::CopyImage, OutputSink::Upload]));
+        assert!(!path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn staged_captures_move_into_the_screenshot_dir() {
+        let dir = std::env::temp_dir().join(format!("hypr-xdisplay-staged-{}", std::process::id()));
+        let staged = dir.join("cache").join("shot.png");
+        std::fs::create_dir_all(staged.parent().unwrap()).unwrap();
+        std::fs::write(&staged, b"png").unwrap();
+        let capture = Capture { files: vec![staged.clone()], outputs: vec!["DP-1".to_string()] };
+        let settings = Settings { screenshot_dir: dir.join("Screenshots"), ..Default::default() };
+
+        let kept = move_into_place(&settings, &capture, &[OutputSink::CopyImage]).unwrap();
+        assert_eq!(kept, capture);
+
+        let saved = move_into_place(&settings, &capture, &[OutputSink::Save]).unwrap();
+        assert_eq!(saved.files, vec![dir.join("Screenshots").join("shot.png")]);
+        assert_eq!(saved.outputs, capture.outputs);
+        assert!(!staged.exists());
+        assert_eq!(std::fs::read(&saved.files[0]).unwrap(), b"png");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use crate::display_manager::monitor_control;
+use crate::display_manager::monitor_types::Monitor;
+use crate::display_manager::projection;
+use crate::recorder;
+use crate::settings::Settings;
+
+#[derive(Debug, Clone)]
+pub struct PresentationOptions {
+    pub projector: String,
+    pub start_recording: bool,
+}
+
+// Which notification daemon we silenced, and whether it was already in
+// do-not-disturb before we touched it
+#[derive(Debug)]
+enum NotificationState {
+    Mako { had_dnd: bool },
+    SwayNc { had_dnd: bool },
+}
+
+// Everything needed to undo presentation mode. Each field is only filled
+// in once its step has succeeded, so a partial enable can be rolled back.
+#[derive(Default)]
+struct PresentationState {
+    monitors: Vec<Monitor>,
+    notifications: Option<NotificationState>,
+    idle_inhibitor: Option<Child>,
+    started_recording: bool,
+}
+
+static PRESENTATION: Lazy<Arc<Mutex<Option<PresentationState>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
+
+pub async fn is_active() -> bool {
+    PRESENTATION.lock().await.is_some()
+}
+
+pub async fn enable_presentation(settings: &Settings, options: &PresentationOptions) -> Result<()> {
+    let mut lock = PRESENTATION.lock().await;
+    if lock.is_some() {
+        info!("Presentation mode is already active.");
+        return Ok(());
+    }
+
+    let mut state = PresentationState {
+        monitors: monitor_control::get_all_monitors().await?,
+        ..Default::default()
+    };
+    info!("Enabling presentation mode on {}", options.projector);
+    if let Err(e) = apply_steps(&mut state, settings, options).await {
+        warn!("Presentation mode failed, rolling back: {}", e);
+        restore(state).await;
+        return Err(e);
+    }
+    *lock = Some(state);
+    Ok(())
+}
+
+pub async fn disable_presentation() -> Result<()> {
+    let mut lock = PRESENTATION.lock().await;
+    if let Some(state) = lock.take() {
+        info!("Disabling presentation mode");
+        restore(state).await;
+    } else {
+        info!("Presentation mode is not active.");
+    }
+    Ok(())
+}
+
+async fn apply_steps(state: &mut PresentationState, settings: &Settings, options: &PresentationOptions) -> Result<()> {
+    // 1. Projector at its native mode, mirroring the main screen
+    let projector = state
+        .monitors
+        .iter()
+        .find(|m| m.name == options.projector)
+        .ok_or_else(|| anyhow::anyhow!("Projector {} is not connected", options.projector))?;
+    let primary = projection::pick_primary(&state.monitors, &options.projector)
+        .ok_or_else(|| anyhow::anyhow!("No screen to mirror onto {}", options.projector))?;
+    let batch = format!(
+        "{}; keyword monitor {},{},auto,1,mirror,{}",
+        monitor_control::enable_rule(primary),
+        projector.name,
+        native_mode(projector),
+        primary.name
+    );
+    monitor_control::run_hyprctl_batch(&batch).await?;
+
+    // 2. Do not disturb
+    state.notifications = enable_dnd().await?;
+
+    // 3. Keep the screen from idling, locking or suspending
+    let inhibitor = Command::new("systemd-inhibit")
+        .arg("--what=idle:sleep")
+        .arg("--who=hypr-xdisplay")
+        .arg("--why=Presentation mode")
+        .arg("--mode=block")
+        .arg("sleep")
+        .arg("infinity")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+    state.idle_inhibitor = Some(inhibitor);
+
+    // 4. Optional recording (leave an already running one alone)
+    if options.start_recording && !recorder::is_recording().await {
+        recorder::start_recording(settings).await?;
+        state.started_recording = true;
+    }
+    Ok(())
+}
+
+// Undo in reverse order. Errors are logged rather than returned so one
+// failing step does not prevent the rest from being restored.
+async fn restore(mut state: PresentationState) {
+    if state.started_recording {
+        if let Err(e) = recorder::stop_recording().await {
+            warn!("Failed to stop presentation recording: {}", e);
+        }
+    }
+    if let Some(mut inhibitor) = state.idle_inhibitor.take() {
+        if let Err(e) = inhibitor.kill().await {
+            warn!("Failed to release idle inhibitor: {}", e);
+        }
+    }
+    if let Some(notifications) = state.notifications.take() {
+        if let Err(e) = restore_dnd(notifications).await {
+            warn!("Failed to restore notification state: {}", e);
+        }
+    }
+    if !state.monitors.is_empty() {
+        let commands = restore_commands(&state.monitors);
+        if let Err(e) = monitor_control::run_hyprctl_batch(&commands.join("; ")).await {
+            warn!("Failed to restore monitor layout: {}", e);
+        }
+    }
+}
+
+// Rules putting every output back as snapshotted: mode, position, scale,
+// transform, mirroring and VRR. Mirror sources come up before the outputs
+// mirroring them, and disables go last so Hyprland is never left without
+// an output.
+fn restore_commands(monitors: &[Monitor]) -> Vec<String> {
+    let rule = |monitor: &Monitor| {
+        let mut rule = monitor_control::enable_rule(monitor);
+        if let Some(source) = monitor.mirror_source() {
+            rule.push_str(&format!(",mirror,{}", source));
+        }
+        rule.push_str(&format!(",vrr,{}", monitor.vrr as u8));
+        rule
+    };
+    let enabled = monitors.iter().filter(|m| !m.disabled);
+    let mut commands: Vec<String> = enabled.clone().filter(|m| m.mirror_source().is_none()).map(rule).collect();
+    commands.extend(enabled.filter(|m| m.mirror_source().is_some()).map(rule));
+    commands.extend(
+        monitors
+            .iter()
+            .filter(|m| m.disabled)
+            .map(|m| format!("keyword monitor {},disable", m.name)),
+    );
+    commands
+}
+
+fn native_mode(monitor: &Monitor) -> String {
+    monitor.preferred_mode().unwrap_or_else(|| "preferred".to_string())
+}
+
+async fn run_tool(program: &str, args: &[&str]) -> Result<Option<String>> {
+    match Command::new(program).args(args).stderr(Stdio::null()).output().await {
+        Ok(output) if output.status.success() => Ok(Some(String::from_utf8_lossy(&output.stdout).to_string())),
+        Ok(output) => Err(anyhow::anyhow!("{} {} failed: {}", program, args.join(" "), output.status)),
+        // Not installed
+        Err(_) => Ok(None),
+    }
+}
+
+async fn enable_dnd() -> Result<Option<NotificationState>> {
+    // swaync-client -D prints "true" or "false"
+    if let Ok(Some(stdout)) = run_tool("swaync-client", &["-D"]).await {
+        let had_dnd = stdout.trim() == "true";
+        run_tool("swaync-client", &["-dn"]).await?;
+        return Ok(Some(NotificationState::SwayNc { had_dnd }));
+    }
+    // makoctl mode prints the active modes, one per line
+    if let Ok(Some(stdout)) = run_tool("makoctl", &["mode"]).await {
+        let had_dnd = stdout.lines().any(|l| l.trim() == "do-not-disturb");
+        if !had_dnd {
+            run_tool("makoctl", &["mode", "-a", "do-not-disturb"]).await?;
+        }
+        return Ok(Some(NotificationState::Mako { had_dnd }));
+    }
+    warn!("No supported notification daemon (mako, swaync) found; notifications stay on");
+    Ok(None)
+}
+
+async fn restore_dnd(state: NotificationState) -> Result<()> {
+    match state {
+        NotificationState::SwayNc { had_dnd: false } => {
+            run_tool("swaync-client", &["-df"]).await?;
+        }
+        NotificationState::Mako { had_dnd: false } => {
+            run_tool("makoctl", &["mode", "-r", "do-not-disturb"]).await?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display_manager::monitor_types::test_support::test_monitor;
+
+    #[test]
+    fn restores_mirror_and_vrr() {
+        let mut projector = test_monitor("HDMI-A-1", 0);
+        projector.mirror_of = Some("eDP-1".to_string());
+        let mut panel = test_monitor("eDP-1", 0);
+        panel.vrr = true;
+        let mut spare = test_monitor("DP-2", 1920);
+        spare.disabled = true;
+        let unmirrored = test_monitor("DP-1", 1920);
+        let mut reported_none = unmirrored.clone();
+        reported_none.mirror_of = Some("none".to_string());
+
+        assert_eq!(
+            restore_commands(&[projector, panel, spare]),
+            vec![
+                "keyword monitor eDP-1,1920x1080@60,0x0,1,transform,0,vrr,1",
+                "keyword monitor HDMI-A-1,1920x1080@60,0x0,1,transform,0,mirror,eDP-1,vrr,0",
+                "keyword monitor DP-2,disable",
+            ]
+        );
+        assert_eq!(restore_commands(&[reported_none]), restore_commands(&[unmirrored]));
+    }
+}
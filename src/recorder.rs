@@ -9,8 +9,23 @@ use nix::unistd::Pid;
 use crate::settings::Settings;
 use std::time::Instant;
 use std::fs;
+use tokio::sync::watch;
+use std::path::PathBuf;
 
 pub static RECORDER_PROCESS: Lazy<Arc<Mutex<Option<(Child, Instant)>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
+// Broadcasts whether a recording is running, for services that react to it
+static RECORDING_STATE: Lazy<watch::Sender<bool>> = Lazy::new(|| watch::channel(false).0);
+
+// File of the most recent recording, for uploading once it has stopped
+static LAST_RECORDING: Lazy<std::sync::Mutex<Option<PathBuf>>> = Lazy::new(|| std::sync::Mutex::new(None));
+
+pub fn last_recording() -> Option<PathBuf> {
+    LAST_RECORDING.lock().unwrap().clone()
+}
+
+pub fn subscribe_recording_state() -> watch::Receiver<bool> {
+    RECORDING_STATE.subscribe()
+}
 
 pub async fn start_recording(settings: &Settings) -> Result<()> {
     let mut process_lock = RECORDER_PROCESS.lock().await;
@@ -65,13 +80,20 @@ pub async fn start_recording(settings: &Settings) -> Result<()> {
     }
     let child = cmd.spawn()?;
     *process_lock = Some((child, Instant::now()));
+    *LAST_RECORDING.lock().unwrap() = Some(path);
+    RECORDING_STATE.send_replace(true);
     Ok(())
 }
 
+pub async fn is_recording() -> bool {
+    RECORDER_PROCESS.lock().await.is_some()
+}
+
 pub async fn stop_recording() -> Result<()> {
     let mut process_lock = RECORDER_PROCESS.lock().await;
     if let Some((mut child, _)) = process_lock.take() {
         info!("Stopping recording...");
+        RECORDING_STATE.send_replace(false);
         child.kill().await?;
         info!("Recording stopped.");
     } else {
@@ -0,0 +1,408 @@
+pub mod postprocess;
+
+use anyhow::Result;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use tracing::info;
+use crate::display_manager::monitor_control;
+use crate::display_manager::monitor_types::Monitor;
+use crate::settings::Settings;
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+// Rectangle in Hyprland's global layout coordinates, as `grim -g` expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Region {
+    pub fn to_geometry(&self) -> String {
+        format!("{},{} {}x{}", self.x, self.y, self.width, self.height)
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x as f64
+            && y >= self.y as f64
+            && x < (self.x + self.width) as f64
+            && y < (self.y + self.height) as f64
+    }
+
+    pub fn area(&self) -> i64 {
+        self.width as i64 * self.height as i64
+    }
+
+    pub fn intersect(&self, other: &Region) -> Option<Region> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        (right > x && bottom > y).then_some(Region { x, y, width: right - x, height: bottom - y })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ClientWorkspace {
+    pub id: i32,
+    pub name: String,
+}
+
+// A window as reported by `hyprctl clients -j`. `at` and `size` are in
+// layout coordinates, so they can be passed to grim unchanged.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Client {
+    pub address: String,
+    pub mapped: bool,
+    pub hidden: bool,
+    pub at: [i32; 2],
+    pub size: [i32; 2],
+    pub workspace: ClientWorkspace,
+    pub floating: bool,
+    pub monitor: i32,
+    pub class: String,
+    pub title: String,
+}
+
+impl Client {
+    pub fn region(&self) -> Region {
+        Region { x: self.at[0], y: self.at[1], width: self.size[0], height: self.size[1] }
+    }
+}
+
+fn get_screenshot_path(settings: &Settings) -> PathBuf {
+    let now = chrono::Local::now();
+    let timestamp = now.format(&settings.screenshot_filename_format).to_string();
+    let dir = &settings.screenshot_dir;
+    if let Err(e) = fs::create_dir_all(dir) {
+        tracing::warn!("Failed to create screenshot directory: {}", e);
+    }
+    dir.join(timestamp)
+}
+
+// Per-output variant of a screenshot path: "shot.png" -> "shot_DP-1.png"
+fn with_output_name(path: &Path, output: &str) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("screenshot");
+    let name = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}_{}.{}", stem, output, ext),
+        None => format!("{}_{}", stem, output),
+    };
+    path.with_file_name(name)
+}
+
+// The files written by one capture. Capturing every monitor separately
+// produces several, which history and preview treat as a single entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    pub files: Vec<PathBuf>,
+    // Output each file shows, where the capture was of whole outputs
+    pub outputs: Vec<String>,
+}
+
+impl From<PathBuf> for Capture {
+    fn from(path: PathBuf) -> Self {
+        Capture { files: vec![path], outputs: Vec::new() }
+    }
+}
+
+impl Capture {
+    pub fn primary(&self) -> Option<&PathBuf> {
+        self.files.first()
+    }
+
+    pub fn label(&self) -> String {
+        let name = self
+            .primary()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match self.files.len() {
+            0 | 1 => name,
+            n => format!("{} (+{} more)", name, n - 1),
+        }
+    }
+}
+
+pub async fn capture_fullscreen(settings: &Settings) -> Result<PathBuf> {
+    let path = get_screenshot_path(settings);
+    info!("Capturing full screen to {:?}", path);
+    let status = Command::new("grim")
+        .arg(path.to_str().unwrap())
+        .status()
+        .await?;
+
+    if !status.success() {
+        return Err(anyhow::anyhow!("grim command failed for fullscreen capture"));
+    }
+    Ok(path)
+}
+
+pub async fn capture_region(settings: &Settings, region: Region) -> Result<PathBuf> {
+    let path = get_screenshot_path(settings);
+    info!("Capturing region {} to {:?}", region.to_geometry(), path);
+
+    let status = Command::new("grim")
+        .arg("-g")
+        .arg(region.to_geometry())
+        .arg(path.to_str().unwrap())
+        .status()
+        .await?;
+
+    if !status.success() {
+        return Err(anyhow::anyhow!("grim command failed for region capture"));
+    }
+    Ok(path)
+}
+
+// Capture a single output at its native resolution
+pub async fn capture_output(name: &str, path: &Path) -> Result<()> {
+    let status = Command::new("grim")
+        .arg("-o")
+        .arg(name)
+        .arg(path.to_str().unwrap())
+        .status()
+        .await?;
+
+    if !status.success() {
+        return Err(anyhow::anyhow!("grim command failed for output {}", name));
+    }
+    Ok(())
+}
+
+pub async fn get_clients() -> Result<Vec<Client>> {
+    let output = Command::new("hyprctl")
+        .arg("clients")
+        .arg("-j")
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("hyprctl clients command failed"));
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+// Windows that are actually on screen: mapped, not hidden, and on a
+// workspace some monitor is currently showing
+pub async fn visible_clients() -> Result<Vec<Client>> {
+    let monitors = monitor_control::get_monitors().await?;
+    let shown: Vec<i32> = monitors
+        .iter()
+        .flat_map(|m| [m.active_workspace.id, m.special_workspace.id])
+        .filter(|id| *id != 0)
+        .collect();
+    Ok(get_clients()
+        .await?
+        .into_iter()
+        .filter(|c| c.mapped && !c.hidden && shown.contains(&c.workspace.id))
+        .collect())
+}
+
+// Bring a window to the front, switching its monitor to the window's
+// workspace if needed
+pub async fn focus_window(client: &Client) -> Result<()> {
+    let output = Command::new("hyprctl")
+        .arg("dispatch")
+        .arg("focuswindow")
+        .arg(format!("address:{}", client.address))
+        .output()
+        .await?;
+    let reply = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || reply.trim() != "ok" {
+        return Err(anyhow::anyhow!("Could not focus window {:?}: {}", client.title, reply.trim()));
+    }
+    Ok(())
+}
+
+// Capture one window. It is focused first, since grim only sees what is
+// on screen and the window may be covered or on a hidden workspace. The
+// region is clipped to the window's monitor, so neighbouring outputs never
+// bleed in, and grabbed at that monitor's scale so a window on a HiDPI
+// screen keeps its full resolution.
+pub async fn capture_window(settings: &Settings, client: &Client) -> Result<PathBuf> {
+    let path = get_screenshot_path(settings);
+    info!("Capturing window {:?} ({}) to {:?}", client.title, client.class, path);
+
+    focus_window(client).await?;
+    let monitors = monitor_control::get_monitors().await?;
+    let monitor = monitors.iter().find(|m| m.id == client.monitor);
+    // Give the compositor a couple of frames to draw the window on top
+    let refresh_rate = monitor.map(|m| m.refresh_rate).filter(|r| *r > 0.0).unwrap_or(60.0);
+    tokio::time::sleep(Duration::from_secs_f64(2.0 / refresh_rate)).await;
+    let mut region = client.region();
+    let mut command = Command::new("grim");
+    if let Some(monitor) = monitor {
+        let (width, height) = monitor.logical_size();
+        let bounds = Region { x: monitor.x, y: monitor.y, width, height };
+        region = region.intersect(&bounds).unwrap_or(region);
+        command.arg("-s").arg(monitor.scale.to_string());
+    }
+
+    let status = command
+        .arg("-g")
+        .arg(region.to_geometry())
+        .arg(path.to_str().unwrap())
+        .status()
+        .await?;
+
+    if !status.success() {
+        return Err(anyhow::anyhow!("grim command failed for window capture"));
+    }
+    Ok(path)
+}
+
+// Monitor under the mouse pointer, falling back to the focused one
+pub async fn cursor_monitor() -> Result<Monitor> {
+    let monitors = monitor_control::get_monitors().await?;
+    let output = Command::new("hyprctl").arg("cursorpos").arg("-j").output().await?;
+    let cursor: Option<serde_json::Value> = serde_json::from_slice(&output.stdout).ok();
+    let position = cursor.and_then(|c| Some((c.get("x")?.as_f64()?, c.get("y")?.as_f64()?)));
+    let under_cursor = position.and_then(|(x, y)| {
+        monitors.iter().find(|m| {
+            let (width, height) = m.logical_size();
+            Region { x: m.x, y: m.y, width, height }.contains(x, y)
+        })
+    });
+    under_cursor
+        .or_else(|| monitors.iter().find(|m| m.focused))
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("No monitor found"))
+}
+
+pub async fn capture_monitor(settings: &Settings, monitor: &Monitor) -> Result<PathBuf> {
+    let path = with_output_name(&get_screenshot_path(settings), &monitor.name);
+    info!("Capturing monitor {} to {:?}", monitor.name, path);
+    capture_output(&monitor.name, &path).await?;
+    Ok(path)
+}
+
+// Every monitor to its own file, sharing one timestamp
+pub async fn capture_each_monitor(settings: &Settings) -> Result<Capture> {
+    let base = get_screenshot_path(settings);
+    let monitors = monitor_control::get_monitors().await?;
+    let mut files = Vec::new();
+    let mut outputs = Vec::new();
+    for monitor in &monitors {
+        let path = with_output_name(&base, &monitor.name);
+        info!("Capturing monitor {} to {:?}", monitor.name, path);
+        capture_output(&monitor.name, &path).await?;
+        files.push(path);
+        outputs.push(monitor.name.clone());
+    }
+    Ok(Capture { files, outputs })
+}
+
+// What a capture will grab, resolved before any delay so pickers and
+// overlays are already gone when the countdown runs
+#[derive(Debug, Clone)]
+pub enum CaptureTarget {
+    Fullscreen,
+    Region(Region),
+    Window(Client),
+    Monitor(Box<Monitor>),
+    EachMonitor,
+}
+
+pub async fn capture(settings: &Settings, target: &CaptureTarget) -> Result<Capture> {
+    match target {
+        CaptureTarget::Fullscreen => capture_fullscreen(settings).await.map(Capture::from),
+        CaptureTarget::Region(region) => capture_region(settings, *region).await.map(Capture::from),
+        CaptureTarget::Window(client) => capture_window(settings, client).await.map(Capture::from),
+        CaptureTarget::Monitor(monitor) => Ok(Capture {
+            files: vec![capture_monitor(settings, monitor).await?],
+            outputs: vec![monitor.name.clone()],
+        }),
+        CaptureTarget::EachMonitor => capture_each_monitor(settings).await,
+    }
+}
+
+// Cancels a pending delayed capture. Clones share the same countdown.
+#[derive(Debug, Clone)]
+pub struct DelayHandle {
+    cancelled: Arc<watch::Sender<bool>>,
+}
+
+impl Default for DelayHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DelayHandle {
+    pub fn new() -> Self {
+        DelayHandle { cancelled: Arc::new(watch::channel(false).0) }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow()
+    }
+
+    pub fn same_as(&self, other: &DelayHandle) -> bool {
+        Arc::ptr_eq(&self.cancelled, &other.cancelled)
+    }
+}
+
+// Wait `seconds`, calling `on_tick` with the seconds left at the start of
+// each one. Returns false as soon as the handle is cancelled.
+pub async fn countdown(seconds: u64, handle: &DelayHandle, mut on_tick: impl FnMut(u64)) -> bool {
+    let mut cancelled = handle.cancelled.subscribe();
+    for remaining in (1..=seconds).rev() {
+        if *cancelled.borrow_and_update() {
+            return false;
+        }
+        on_tick(remaining);
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+            _ = cancelled.changed() => return false,
+        }
+    }
+    !handle.is_cancelled()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::Instant;
+
+    #[tokio::test(start_paused = true)]
+    async fn countdown_ticks_down_to_one() {
+        let start = Instant::now();
+        let mut ticks = Vec::new();
+        assert!(countdown(3, &DelayHandle::new(), |remaining| ticks.push(remaining)).await);
+        assert_eq!(ticks, vec![3, 2, 1]);
+        assert_eq!(start.elapsed(), Duration::from_secs(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancelling_mid_countdown_stops_at_once() {
+        let handle = DelayHandle::new();
+        let canceller = handle.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(1500)).await;
+            canceller.cancel();
+        });
+        let start = Instant::now();
+        let mut ticks = Vec::new();
+        assert!(!countdown(3, &handle, |remaining| ticks.push(remaining)).await);
+        assert_eq!(ticks, vec![3, 2]);
+        assert_eq!(start.elapsed(), Duration::from_millis(1500));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancelled_before_the_first_tick() {
+        let handle = DelayHandle::new();
+        handle.cancel();
+        let mut ticks = Vec::new();
+        assert!(!countdown(3, &handle, |remaining| ticks.push(remaining)).await);
+        assert!(ticks.is_empty());
+        assert!(handle.is_cancelled());
+    }
+}
@@ -0,0 +1,442 @@
+use crate::screenshot::Capture;
+use anyhow::Result;
+use image::codecs::avif::AvifEncoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType as PngFilter, PngEncoder};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Png,
+    Jpeg,
+    Webp,
+    Avif,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 4] = [OutputFormat::Png, OutputFormat::Jpeg, OutputFormat::Webp, OutputFormat::Avif];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Jpeg => "jpg",
+            OutputFormat::Webp => "webp",
+            OutputFormat::Avif => "avif",
+        }
+    }
+
+    // Format matching a file's extension, PNG when unknown
+    pub fn from_path(path: &Path) -> OutputFormat {
+        let extension = path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("jpg") | Some("jpeg") => OutputFormat::Jpeg,
+            Some("webp") => OutputFormat::Webp,
+            Some("avif") => OutputFormat::Avif,
+            _ => OutputFormat::Png,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "PNG",
+            OutputFormat::Jpeg => "JPEG",
+            OutputFormat::Webp => "WebP",
+            OutputFormat::Avif => "AVIF",
+        }
+    }
+}
+
+// Pixels trimmed from each edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Insets {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+// Padding, rounded corners and a drop shadow on a solid background, for
+// screenshots that go into docs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FrameOptions {
+    pub padding: u32,
+    pub corner_radius: u32,
+    pub shadow_blur: f32, // 0 disables the shadow
+    pub shadow_offset: i32,
+    pub background: String, // "#rgb", "#rrggbb" or "#rrggbbaa"
+}
+
+impl Default for FrameOptions {
+    fn default() -> Self {
+        Self {
+            padding: 48,
+            corner_radius: 12,
+            shadow_blur: 16.0,
+            shadow_offset: 8,
+            background: "#4a6fa5".to_string(),
+        }
+    }
+}
+
+// Per capture mode, under `[screenshot_processing.<mode>]`. Steps run in
+// field order: crop, downscale, frame, encode, optimise.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessOptions {
+    pub crop: Option<Insets>,
+    pub max_width: Option<u32>,
+    pub max_height: Option<u32>,
+    pub frame: Option<FrameOptions>,
+    pub format: OutputFormat,
+    pub quality: u8, // 1-100 for JPEG, WebP and AVIF; 100 makes WebP lossless
+    pub optimize_png: bool,
+}
+
+impl Default for ProcessOptions {
+    fn default() -> Self {
+        Self {
+            crop: None,
+            max_width: None,
+            max_height: None,
+            frame: None,
+            format: OutputFormat::Png,
+            quality: 90,
+            optimize_png: false,
+        }
+    }
+}
+
+impl ProcessOptions {
+    // Whether the grim PNG can be kept as it is
+    pub fn is_noop(&self) -> bool {
+        self.crop.is_none()
+            && self.max_width.is_none()
+            && self.max_height.is_none()
+            && self.frame.is_none()
+            && self.format == OutputFormat::Png
+            && !self.optimize_png
+    }
+
+    // Whether the pixels move: cropped, scaled or framed
+    pub fn transforms(&self) -> bool {
+        self.crop.is_some() || self.max_width.is_some() || self.max_height.is_some() || self.frame.is_some()
+    }
+}
+
+// Insets that would leave nothing are ignored
+pub fn crop(image: &DynamicImage, insets: &Insets) -> DynamicImage {
+    let (width, height) = (image.width(), image.height());
+    let horizontal = insets.left.saturating_add(insets.right);
+    let vertical = insets.top.saturating_add(insets.bottom);
+    if horizontal >= width || vertical >= height {
+        return image.clone();
+    }
+    image.crop_imm(insets.left, insets.top, width - horizontal, height - vertical)
+}
+
+// Shrink to fit within the limits, keeping the aspect ratio. Never enlarges.
+pub fn downscale(image: DynamicImage, max_width: Option<u32>, max_height: Option<u32>) -> DynamicImage {
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let scale = [
+        max_width.filter(|w| *w > 0).map(|w| w as f64 / width),
+        max_height.filter(|h| *h > 0).map(|h| h as f64 / height),
+    ]
+    .into_iter()
+    .flatten()
+    .fold(1.0, f64::min);
+    if scale >= 1.0 {
+        return image;
+    }
+    let new_width = ((width * scale).round() as u32).max(1);
+    let new_height = ((height * scale).round() as u32).max(1);
+    image.resize_exact(new_width, new_height, FilterType::Lanczos3)
+}
+
+pub fn parse_color(hex: &str) -> Option<Rgba<u8>> {
+    let hex = hex.trim().trim_start_matches('#');
+    let channel = |i: usize, len: usize| -> Option<u8> {
+        let digits = hex.get(i * len..(i + 1) * len)?;
+        let value = u8::from_str_radix(digits, 16).ok()?;
+        Some(if len == 1 { value * 17 } else { value })
+    };
+    match hex.len() {
+        3 => Some(Rgba([channel(0, 1)?, channel(1, 1)?, channel(2, 1)?, 255])),
+        6 => Some(Rgba([channel(0, 2)?, channel(1, 2)?, channel(2, 2)?, 255])),
+        8 => Some(Rgba([channel(0, 2)?, channel(1, 2)?, channel(2, 2)?, channel(3, 2)?])),
+        _ => None,
+    }
+}
+
+// Make the corners transparent, antialiased over one pixel
+pub fn round_corners(image: &mut RgbaImage, radius: u32) {
+    let (width, height) = image.dimensions();
+    let radius = radius.min(width / 2).min(height / 2);
+    if radius == 0 {
+        return;
+    }
+    let r = radius as f32;
+    for y in 0..radius {
+        for x in 0..radius {
+            // Distance from the corner circle's centre, at pixel centres
+            let dx = r - (x as f32 + 0.5);
+            let dy = r - (y as f32 + 0.5);
+            let coverage = (r - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0);
+            if coverage >= 1.0 {
+                continue;
+            }
+            for (px, py) in [(x, y), (width - 1 - x, y), (x, height - 1 - y), (width - 1 - x, height - 1 - y)] {
+                let pixel = image.get_pixel_mut(px, py);
+                pixel[3] = (pixel[3] as f32 * coverage).round() as u8;
+            }
+        }
+    }
+}
+
+// Larger paddings from a hand-edited config are clamped to this, which also
+// keeps the canvas size from overflowing
+pub const MAX_FRAME_PADDING: u32 = 1024;
+
+pub fn frame(image: &DynamicImage, options: &FrameOptions) -> RgbaImage {
+    let background = parse_color(&options.background).unwrap_or(Rgba([74, 111, 165, 255]));
+    let padding = options.padding.min(MAX_FRAME_PADDING);
+    let mut content = image.to_rgba8();
+    let (width, height) = content.dimensions();
+    let mut canvas = RgbaImage::from_pixel(
+        width.saturating_add(2 * padding),
+        height.saturating_add(2 * padding),
+        background,
+    );
+
+    if options.shadow_blur > 0.0 {
+        let mut shape = RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 110]));
+        round_corners(&mut shape, options.corner_radius);
+        let mut shadow = RgbaImage::new(canvas.width(), canvas.height());
+        imageops::overlay(&mut shadow, &shape, padding as i64, padding as i64 + options.shadow_offset as i64);
+        let shadow = imageops::fast_blur(&shadow, options.shadow_blur);
+        imageops::overlay(&mut canvas, &shadow, 0, 0);
+    }
+
+    round_corners(&mut content, options.corner_radius);
+    imageops::overlay(&mut canvas, &content, padding as i64, padding as i64);
+    canvas
+}
+
+pub fn encode(image: &DynamicImage, format: OutputFormat, quality: u8) -> Result<Vec<u8>> {
+    let quality = quality.clamp(1, 100);
+    let mut data = Vec::new();
+    match format {
+        OutputFormat::Png => {
+            image.write_with_encoder(PngEncoder::new_with_quality(&mut data, CompressionType::Best, PngFilter::Adaptive))?
+        }
+        // JPEG has no alpha channel
+        OutputFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut data, quality))?,
+        OutputFormat::Webp => {
+            let rgba = image.to_rgba8();
+            let encoder = webp::Encoder::from_rgba(&rgba, rgba.width(), rgba.height());
+            let encoded = if quality == 100 { encoder.encode_lossless() } else { encoder.encode(quality as f32) };
+            data.extend_from_slice(&encoded);
+        }
+        OutputFormat::Avif => image.write_with_encoder(AvifEncoder::new_with_speed_quality(&mut data, 6, quality))?,
+    }
+    Ok(data)
+}
+
+// Lossless recompression; the pixels are untouched
+pub fn optimize_png(data: &[u8]) -> Result<Vec<u8>> {
+    Ok(oxipng::optimize_from_memory(data, &oxipng::Options::from_preset(2))?)
+}
+
+// Process one file in place. A format change writes a file with the new
+// extension and removes the original.
+pub fn process_file(path: &Path, options: &ProcessOptions) -> Result<PathBuf> {
+    if options.is_noop() {
+        return Ok(path.to_path_buf());
+    }
+    let target = path.with_extension(options.format.extension());
+    let mut data = if options.transforms() || options.format != OutputFormat::Png {
+        let mut image = image::open(path)?;
+        if let Some(insets) = &options.crop {
+            image = crop(&image, insets);
+        }
+        image = downscale(image, options.max_width, options.max_height);
+        if let Some(frame_options) = &options.frame {
+            image = DynamicImage::ImageRgba8(frame(&image, frame_options));
+        }
+        encode(&image, options.format, options.quality)?
+    } else {
+        fs::read(path)?
+    };
+    if options.optimize_png && options.format == OutputFormat::Png {
+        data = optimize_png(&data)?;
+    }
+    fs::write(&target, &data)?;
+    if target != path {
+        fs::remove_file(path)?;
+    }
+    info!("Post-processed {:?} to {:?} ({} bytes)", path, target, data.len());
+    Ok(target)
+}
+
+pub fn process_capture(capture: &Capture, options: &ProcessOptions) -> Result<Capture> {
+    let files = capture
+        .files
+        .iter()
+        .map(|path| process_file(path, options))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Capture { files, outputs: capture.outputs.clone() })
+}
+
+// Runs on the blocking pool; encoding large captures takes a while
+pub async fn process(capture: Capture, options: ProcessOptions) -> Result<Capture> {
+    if options.is_noop() {
+        return Ok(capture);
+    }
+    tokio::task::spawn_blocking(move || process_capture(&capture, &options)).await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Left half red, right half blue
+    fn test_image(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(width, height, |x, _| {
+            if x < width / 2 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 255, 255])
+            }
+        }))
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hypr-xdisplay-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn crop_trims_edges() {
+        let image = test_image(100, 50);
+        let insets = Insets { top: 5, right: 10, bottom: 15, left: 20 };
+        let cropped = crop(&image, &insets);
+        assert_eq!((cropped.width(), cropped.height()), (70, 30));
+        assert_eq!(cropped.to_rgba8().get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        // Nothing would be left: the image is kept whole
+        let everything = Insets { left: 60, right: 40, ..Default::default() };
+        assert_eq!(crop(&image, &everything).width(), 100);
+        let huge = Insets { top: u32::MAX, bottom: u32::MAX, ..Default::default() };
+        assert_eq!(crop(&image, &huge).height(), 50);
+    }
+
+    #[test]
+    fn downscale_keeps_aspect_and_never_enlarges() {
+        let scaled = downscale(test_image(400, 200), Some(100), Some(100));
+        assert_eq!((scaled.width(), scaled.height()), (100, 50));
+        let scaled = downscale(test_image(400, 200), None, Some(20));
+        assert_eq!((scaled.width(), scaled.height()), (40, 20));
+        let kept = downscale(test_image(40, 20), Some(400), None);
+        assert_eq!((kept.width(), kept.height()), (40, 20));
+        let ignored = downscale(test_image(40, 20), Some(0), None);
+        assert_eq!((ignored.width(), ignored.height()), (40, 20));
+    }
+
+    #[test]
+    fn parse_color_forms() {
+        assert_eq!(parse_color("#fff"), Some(Rgba([255, 255, 255, 255])));
+        assert_eq!(parse_color("4a6fa5"), Some(Rgba([74, 111, 165, 255])));
+        assert_eq!(parse_color(" #11223380 "), Some(Rgba([0x11, 0x22, 0x33, 0x80])));
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(parse_color("#ggg"), None);
+        assert_eq!(parse_color("#éé"), None);
+    }
+
+    #[test]
+    fn round_corners_clears_only_the_corners() {
+        let mut image = test_image(40, 40).to_rgba8();
+        round_corners(&mut image, 10);
+        for (x, y) in [(0, 0), (39, 0), (0, 39), (39, 39)] {
+            assert_eq!(image.get_pixel(x, y)[3], 0);
+        }
+        assert_eq!(image.get_pixel(20, 0)[3], 255);
+        assert_eq!(image.get_pixel(0, 20)[3], 255);
+        assert_eq!(image.get_pixel(5, 5)[3], 255);
+        // A radius past half the size is clamped rather than wrapping around
+        let mut small = test_image(8, 8).to_rgba8();
+        round_corners(&mut small, 100);
+        assert_eq!(small.get_pixel(0, 0)[3], 0);
+        assert_eq!(small.get_pixel(4, 4)[3], 255);
+    }
+
+    #[test]
+    fn frame_pads_and_clamps() {
+        let options = FrameOptions { padding: 10, shadow_blur: 0.0, background: "#00ff00".to_string(), ..Default::default() };
+        let framed = frame(&test_image(40, 20), &options);
+        assert_eq!(framed.dimensions(), (60, 40));
+        assert_eq!(framed.get_pixel(0, 0), &Rgba([0, 255, 0, 255]));
+        assert_eq!(framed.get_pixel(20, 20), &Rgba([255, 0, 0, 255]));
+        assert_eq!(framed.get_pixel(40, 20), &Rgba([0, 0, 255, 255]));
+
+        let shadowed = frame(&test_image(40, 20), &FrameOptions::default());
+        assert_eq!(shadowed.dimensions(), (136, 116));
+
+        let huge = FrameOptions { padding: u32::MAX, shadow_blur: 0.0, ..Default::default() };
+        assert_eq!(frame(&test_image(2, 2), &huge).dimensions(), (2 + 2 * MAX_FRAME_PADDING, 2 + 2 * MAX_FRAME_PADDING));
+    }
+
+    #[test]
+    fn encode_round_trips() {
+        let image = test_image(32, 16);
+        for format in OutputFormat::ALL {
+            let data = encode(&image, format, 80).unwrap();
+            if format == OutputFormat::Avif {
+                // No AVIF decoder is built in; check the container instead
+                assert_eq!(&data[4..12], b"ftypavif");
+                continue;
+            }
+            let decoded = image::load_from_memory(&data).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (32, 16), "{:?}", format);
+            let pixel = decoded.to_rgb8().get_pixel(4, 8).0;
+            assert!(pixel[0] > 200 && pixel[2] < 60, "{:?} decoded to {:?}", format, pixel);
+        }
+        let lossless = encode(&image, OutputFormat::Webp, 100).unwrap();
+        assert_eq!(image::load_from_memory(&lossless).unwrap().to_rgba8(), image.to_rgba8());
+        let png = encode(&image, OutputFormat::Png, 1).unwrap();
+        let optimized = optimize_png(&png).unwrap();
+        assert_eq!(image::load_from_memory(&optimized).unwrap().to_rgba8(), image.to_rgba8());
+    }
+
+    #[test]
+    fn process_file_replaces_the_capture() {
+        let dir = temp_dir("postprocess");
+        let path = dir.join("shot.png");
+        test_image(200, 100).save(&path).unwrap();
+
+        let untouched = process_file(&path, &ProcessOptions::default()).unwrap();
+        assert_eq!(untouched, path);
+
+        let options = ProcessOptions {
+            crop: Some(Insets { left: 100, ..Default::default() }),
+            max_width: Some(50),
+            format: OutputFormat::Jpeg,
+            ..Default::default()
+        };
+        let processed = process_file(&path, &options).unwrap();
+        assert_eq!(processed, dir.join("shot.jpg"));
+        assert!(!path.exists());
+        let image = image::open(&processed).unwrap();
+        assert_eq!((image.width(), image.height()), (50, 50));
+        assert!(image.to_rgb8().get_pixel(25, 25)[2] > 200);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,7 +1,12 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use tracing::info;
+use std::collections::HashMap;
 use std::fs;
+use crate::output_sinks::OutputSink;
+use crate::uploader::UploadProvider;
+use crate::screenshot::postprocess::ProcessOptions;
+use crate::redaction::RedactionOptions;
 
 #[derive(Debug, Deserialize, Clone, Serialize)]
 pub struct Settings {
@@ -16,6 +21,30 @@ pub struct Settings {
     pub recorder_resolution: Option<String>,
     pub recorder_hardware_accel: Option<bool>,
     pub recorder_audio_device: Option<String>,
+    pub auto_rotation: Option<bool>,
+    pub rotation_output: Option<String>,
+    pub night_light_mode: Option<String>, // "Off", "Manual", "Schedule" or "Fixed"
+    pub night_light_temperature: Option<u32>,
+    pub night_light_latitude: Option<f64>,
+    pub night_light_longitude: Option<f64>,
+    pub night_light_start: Option<String>, // "HH:MM", Fixed mode
+    pub night_light_end: Option<String>,
+    pub power_policy: Option<bool>,
+    pub power_policy_delay_secs: Option<u64>,
+    pub auto_brightness: Option<bool>,
+    pub auto_brightness_external: Option<bool>,
+    pub auto_brightness_curve: Option<Vec<[f64; 2]>>, // [lux, brightness 0.0-1.0] points
+    pub stage_layout_changes: Option<bool>,
+    pub docking: Option<bool>,
+    pub screenshot_custom_aspect: Option<String>, // e.g. "21:9", offered in the region selector
+    pub screenshot_delay_secs: Option<u64>,
+    pub screenshot_sinks: Option<HashMap<String, Vec<OutputSink>>>, // per capture mode, default save only
+    pub screenshot_editor: Option<String>,
+    pub upload_provider: Option<String>, // key into upload_providers
+    pub upload_providers: Option<HashMap<String, UploadProvider>>,
+    pub screenshot_processing: Option<HashMap<String, ProcessOptions>>, // per capture mode
+    pub ocr_languages: Option<String>, // tesseract -l syntax, e.g. "eng+deu"
+    pub screenshot_redaction: Option<RedactionOptions>,
 }
 
 impl Default for Settings {
@@ -38,6 +67,30 @@ impl Default for Settings {
             recorder_resolution: None,
             recorder_hardware_accel: Some(false),
             recorder_audio_device: None,
+            auto_rotation: Some(false),
+            rotation_output: None,
+            night_light_mode: Some("Off".to_string()),
+            night_light_temperature: Some(4000),
+            night_light_latitude: None,
+            night_light_longitude: None,
+            night_light_start: Some("22:00".to_string()),
+            night_light_end: Some("06:00".to_string()),
+            power_policy: Some(false),
+            power_policy_delay_secs: Some(10),
+            auto_brightness: Some(false),
+            auto_brightness_external: Some(false),
+            auto_brightness_curve: None,
+            stage_layout_changes: Some(false),
+            docking: Some(false),
+            screenshot_custom_aspect: None,
+            screenshot_delay_secs: Some(0),
+            screenshot_sinks: None,
+            screenshot_editor: None,
+            upload_provider: None,
+            upload_providers: None,
+            screenshot_processing: None,
+            ocr_languages: Some("eng".to_string()),
+            screenshot_redaction: None,
         }
     }
 }
@@ -0,0 +1,758 @@
+use crate::screenshot::postprocess::{self, OutputFormat};
+use crate::ui::main_window::MAIN_WINDOW;
+use anyhow::{anyhow, Result};
+use futures::channel::oneshot;
+use gtk::cairo;
+use gtk::gdk;
+use gtk::gdk_pixbuf::Pixbuf;
+use gtk::glib;
+use image::{DynamicImage, RgbaImage};
+use libadwaita as adw;
+use adw::prelude::*;
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+// Snapshots kept for undo
+const UNDO_LIMIT: usize = 100;
+// Drags shorter than this (image pixels) add nothing
+const MIN_DRAG: f64 = 3.0;
+// Size of the blocks a pixelated region is made of
+const PIXELATE_BLOCK: f64 = 12.0;
+// Downscale factor behind the blur; larger is blurrier
+const BLUR_FACTOR: f64 = 10.0;
+const HIGHLIGHTER_ALPHA: f64 = 0.35;
+const QUALITY: u8 = 90;
+
+type Point = (f64, f64);
+type Color = (f64, f64, f64);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tool {
+    Arrow,
+    Rectangle,
+    Ellipse,
+    Freehand,
+    Highlighter,
+    Text,
+    Step,
+    Pixelate,
+    Blur,
+    Crop,
+}
+
+const TOOLS: [(Tool, &str, &str); 10] = [
+    (Tool::Arrow, "Arrow", "Drag from tail to head"),
+    (Tool::Rectangle, "Box", "Drag a rectangle"),
+    (Tool::Ellipse, "Ellipse", "Drag an ellipse"),
+    (Tool::Freehand, "Pen", "Draw freehand"),
+    (Tool::Highlighter, "Marker", "Translucent highlighter"),
+    (Tool::Text, "Text", "Click to place the text typed in the toolbar"),
+    (Tool::Step, "Step", "Click to place the next numbered marker"),
+    (Tool::Pixelate, "Pixelate", "Drag over a region to pixelate it"),
+    (Tool::Blur, "Blur", "Drag over a region to blur it"),
+    (Tool::Crop, "Crop", "Drag the area to keep"),
+];
+
+// In image pixels, width/height >= 0
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Rect {
+    fn from_points(a: Point, b: Point) -> Rect {
+        Rect {
+            x: a.0.min(b.0),
+            y: a.1.min(b.1),
+            width: (a.0 - b.0).abs(),
+            height: (a.1 - b.1).abs(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.width < MIN_DRAG || self.height < MIN_DRAG
+    }
+
+    // Whole pixels, for crops and exports
+    fn snapped(&self) -> Rect {
+        let (x, y) = (self.x.round(), self.y.round());
+        Rect {
+            x,
+            y,
+            width: (self.x + self.width).round() - x,
+            height: (self.y + self.height).round() - y,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Shape {
+    Arrow { from: Point, to: Point, color: Color, width: f64 },
+    Rectangle { rect: Rect, color: Color, width: f64 },
+    Ellipse { rect: Rect, color: Color, width: f64 },
+    Freehand { points: Vec<Point>, color: Color, width: f64 },
+    Highlighter { points: Vec<Point>, color: Color, width: f64 },
+    Text { at: Point, text: String, color: Color, size: f64 },
+    // Numbered by position among the document's steps, so undo renumbers
+    Step { at: Point, color: Color, size: f64 },
+    Pixelate { rect: Rect },
+    Blur { rect: Rect },
+}
+
+impl Shape {
+    // Shape for a drag of `tool` from `start` to `end`, None for tools
+    // placed by a click or handled separately
+    fn for_drag(tool: Tool, start: Point, end: Point, color: Color, width: f64) -> Option<Shape> {
+        let rect = Rect::from_points(start, end);
+        match tool {
+            Tool::Arrow => Some(Shape::Arrow { from: start, to: end, color, width }),
+            Tool::Rectangle => Some(Shape::Rectangle { rect, color, width }),
+            Tool::Ellipse => Some(Shape::Ellipse { rect, color, width }),
+            Tool::Freehand => Some(Shape::Freehand { points: vec![start, end], color, width }),
+            Tool::Highlighter => Some(Shape::Highlighter { points: vec![start, end], color, width: width * 4.0 }),
+            Tool::Pixelate => Some(Shape::Pixelate { rect }),
+            Tool::Blur => Some(Shape::Blur { rect }),
+            Tool::Text | Tool::Step | Tool::Crop => None,
+        }
+    }
+
+    // Whether the finished drag drew anything worth keeping
+    fn is_visible(&self) -> bool {
+        match self {
+            Shape::Arrow { from, to, .. } => (from.0 - to.0).hypot(from.1 - to.1) >= MIN_DRAG,
+            Shape::Rectangle { rect, .. } | Shape::Ellipse { rect, .. } => !rect.is_empty(),
+            Shape::Pixelate { rect } | Shape::Blur { rect } => !rect.is_empty(),
+            Shape::Freehand { points, .. } | Shape::Highlighter { points, .. } => points.len() > 1,
+            Shape::Text { text, .. } => !text.is_empty(),
+            Shape::Step { .. } => true,
+        }
+    }
+}
+
+// Everything undo restores
+#[derive(Debug, Clone, Default)]
+struct Document {
+    shapes: Vec<Shape>,
+    crop: Option<Rect>,
+}
+
+struct Editor {
+    path: PathBuf,
+    copy_dir: PathBuf,
+    image: cairo::ImageSurface,
+    doc: Document,
+    undo: Vec<Document>,
+    redo: Vec<Document>,
+    tool: Tool,
+    color: Color,
+    width: f64,
+    text: String,
+    // Drag in progress, in image coordinates
+    drag_start: Option<Point>,
+    pending: Option<Shape>,
+    pending_crop: Option<Rect>,
+    // Widget coordinates = offset + image coordinates * scale
+    scale: f64,
+    offset: Point,
+    area: gtk::DrawingArea,
+    title: adw::WindowTitle,
+    undo_btn: gtk::Button,
+    redo_btn: gtk::Button,
+    result: Option<oneshot::Sender<Option<PathBuf>>>,
+}
+
+impl Editor {
+    fn image_size(&self) -> (f64, f64) {
+        (self.image.width() as f64, self.image.height() as f64)
+    }
+
+    fn to_image(&self, x: f64, y: f64) -> Point {
+        let (width, height) = self.image_size();
+        (
+            ((x - self.offset.0) / self.scale).clamp(0.0, width),
+            ((y - self.offset.1) / self.scale).clamp(0.0, height),
+        )
+    }
+
+    fn is_dirty(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    fn commit(&mut self, doc: Document) {
+        self.undo.push(std::mem::replace(&mut self.doc, doc));
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+        self.changed();
+    }
+
+    fn add_shape(&mut self, shape: Shape) {
+        let mut doc = self.doc.clone();
+        doc.shapes.push(shape);
+        self.commit(doc);
+    }
+
+    fn undo(&mut self) {
+        if let Some(doc) = self.undo.pop() {
+            self.redo.push(std::mem::replace(&mut self.doc, doc));
+            self.changed();
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(doc) = self.redo.pop() {
+            self.undo.push(std::mem::replace(&mut self.doc, doc));
+            self.changed();
+        }
+    }
+
+    fn changed(&self) {
+        self.undo_btn.set_sensitive(!self.undo.is_empty());
+        self.redo_btn.set_sensitive(!self.redo.is_empty());
+        let name = self.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let subtitle = if self.is_dirty() { format!("{} (unsaved changes)", name) } else { name };
+        self.title.set_subtitle(&subtitle);
+        self.area.queue_draw();
+    }
+
+    fn begin(&mut self, point: Point) {
+        match self.tool {
+            Tool::Text => {
+                let text = self.text.trim().to_string();
+                if text.is_empty() {
+                    crate::ui::toasts::show_toast("Type the text in the toolbar first");
+                } else {
+                    let size = (self.width * 6.0).max(14.0);
+                    self.add_shape(Shape::Text { at: point, text, color: self.color, size });
+                }
+            }
+            Tool::Step => {
+                let size = (self.width * 4.0).max(14.0);
+                self.add_shape(Shape::Step { at: point, color: self.color, size });
+            }
+            _ => self.drag_start = Some(point),
+        }
+    }
+
+    fn update(&mut self, point: Point) {
+        let Some(start) = self.drag_start else { return };
+        if let Some(Shape::Freehand { points, .. } | Shape::Highlighter { points, .. }) = &mut self.pending {
+            points.push(point);
+        } else if self.tool == Tool::Crop {
+            self.pending_crop = Some(Rect::from_points(start, point));
+        } else {
+            self.pending = Shape::for_drag(self.tool, start, point, self.color, self.width);
+        }
+        self.area.queue_draw();
+    }
+
+    fn end(&mut self) {
+        self.drag_start = None;
+        if let Some(shape) = self.pending.take().filter(Shape::is_visible) {
+            self.add_shape(shape);
+        }
+        if let Some(rect) = self.pending_crop.take() {
+            // A click without a drag removes the crop
+            let crop = (!rect.is_empty()).then(|| rect.snapped());
+            if crop != self.doc.crop {
+                let mut doc = self.doc.clone();
+                doc.crop = crop;
+                self.commit(doc);
+            }
+        }
+        self.area.queue_draw();
+    }
+
+    fn finish(&mut self, saved: Option<PathBuf>) {
+        if let Some(sender) = self.result.take() {
+            let _ = sender.send(saved);
+        }
+    }
+}
+
+// Decode the file into a cairo surface once, as the region selector does
+fn load_surface(path: &Path) -> Result<cairo::ImageSurface> {
+    let pixbuf = Pixbuf::from_file(path)?;
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, pixbuf.width(), pixbuf.height())?;
+    let cr = cairo::Context::new(&surface)?;
+    cr.set_source_pixbuf(&pixbuf, 0.0, 0.0);
+    cr.paint()?;
+    drop(cr);
+    Ok(surface)
+}
+
+fn set_color(cr: &cairo::Context, color: Color, alpha: f64) {
+    cr.set_source_rgba(color.0, color.1, color.2, alpha);
+}
+
+fn stroke_path(cr: &cairo::Context, points: &[Point]) {
+    let Some((first, rest)) = points.split_first() else { return };
+    cr.move_to(first.0, first.1);
+    for point in rest {
+        cr.line_to(point.0, point.1);
+    }
+    let _ = cr.stroke();
+}
+
+// Scale `rect` of the base image down by `factor` and back up with
+// `filter`: Nearest gives blocks, Bilinear a blur
+fn draw_obscured(cr: &cairo::Context, image: &cairo::ImageSurface, rect: &Rect, factor: f64, filter: cairo::Filter) {
+    let width = (rect.width / factor).ceil().max(1.0) as i32;
+    let height = (rect.height / factor).ceil().max(1.0) as i32;
+    let Ok(small) = cairo::ImageSurface::create(cairo::Format::ARgb32, width, height) else { return };
+    if let Ok(scr) = cairo::Context::new(&small) {
+        scr.scale(1.0 / factor, 1.0 / factor);
+        let _ = scr.set_source_surface(image, -rect.x, -rect.y);
+        let _ = scr.paint();
+    }
+    let _ = cr.save();
+    cr.rectangle(rect.x, rect.y, rect.width, rect.height);
+    cr.clip();
+    cr.translate(rect.x, rect.y);
+    cr.scale(factor, factor);
+    if cr.set_source_surface(&small, 0.0, 0.0).is_ok() {
+        cr.source().set_filter(filter);
+        // Repeat the edge pixels rather than fading into transparency
+        cr.source().set_extend(cairo::Extend::Pad);
+        let _ = cr.paint();
+    }
+    let _ = cr.restore();
+}
+
+fn draw_arrow(cr: &cairo::Context, from: Point, to: Point, color: Color, width: f64) {
+    let angle = (to.1 - from.1).atan2(to.0 - from.0);
+    let head = (width * 4.0).max(12.0);
+    let spread = PI / 7.0;
+    set_color(cr, color, 1.0);
+    cr.set_line_width(width);
+    // Stop the shaft inside the head so its end doesn't poke through
+    cr.move_to(from.0, from.1);
+    cr.line_to(to.0 - head * 0.5 * angle.cos(), to.1 - head * 0.5 * angle.sin());
+    let _ = cr.stroke();
+    cr.move_to(to.0, to.1);
+    cr.line_to(to.0 - head * (angle - spread).cos(), to.1 - head * (angle - spread).sin());
+    cr.line_to(to.0 - head * (angle + spread).cos(), to.1 - head * (angle + spread).sin());
+    cr.close_path();
+    let _ = cr.fill();
+}
+
+fn draw_ellipse(cr: &cairo::Context, rect: &Rect, color: Color, width: f64) {
+    if rect.width <= 0.0 || rect.height <= 0.0 {
+        return;
+    }
+    let _ = cr.save();
+    cr.translate(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+    cr.scale(rect.width / 2.0, rect.height / 2.0);
+    cr.arc(0.0, 0.0, 1.0, 0.0, 2.0 * PI);
+    let _ = cr.restore();
+    // Stroked after restore so the line width isn't scaled with the ellipse
+    set_color(cr, color, 1.0);
+    cr.set_line_width(width);
+    let _ = cr.stroke();
+}
+
+fn draw_step(cr: &cairo::Context, at: Point, number: usize, color: Color, size: f64) {
+    set_color(cr, color, 1.0);
+    cr.arc(at.0, at.1, size, 0.0, 2.0 * PI);
+    let _ = cr.fill();
+    cr.set_source_rgb(1.0, 1.0, 1.0);
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+    cr.set_font_size(size * 1.1);
+    let label = number.to_string();
+    if let Ok(extents) = cr.text_extents(&label) {
+        cr.move_to(
+            at.0 - extents.width() / 2.0 - extents.x_bearing(),
+            at.1 - extents.height() / 2.0 - extents.y_bearing(),
+        );
+        let _ = cr.show_text(&label);
+    }
+}
+
+fn draw_shape(cr: &cairo::Context, image: &cairo::ImageSurface, shape: &Shape, step: usize) {
+    cr.set_line_cap(cairo::LineCap::Round);
+    cr.set_line_join(cairo::LineJoin::Round);
+    match shape {
+        Shape::Arrow { from, to, color, width } => draw_arrow(cr, *from, *to, *color, *width),
+        Shape::Rectangle { rect, color, width } => {
+            set_color(cr, *color, 1.0);
+            cr.set_line_width(*width);
+            cr.rectangle(rect.x, rect.y, rect.width, rect.height);
+            let _ = cr.stroke();
+        }
+        Shape::Ellipse { rect, color, width } => draw_ellipse(cr, rect, *color, *width),
+        Shape::Freehand { points, color, width } => {
+            set_color(cr, *color, 1.0);
+            cr.set_line_width(*width);
+            stroke_path(cr, points);
+        }
+        Shape::Highlighter { points, color, width } => {
+            // One translucent stroke, so overlapping segments don't darken
+            set_color(cr, *color, HIGHLIGHTER_ALPHA);
+            cr.set_line_width(*width);
+            cr.set_line_cap(cairo::LineCap::Square);
+            stroke_path(cr, points);
+        }
+        Shape::Text { at, text, color, size } => {
+            set_color(cr, *color, 1.0);
+            cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+            cr.set_font_size(*size);
+            cr.move_to(at.0, at.1 + *size);
+            let _ = cr.show_text(text);
+        }
+        Shape::Step { at, color, size } => draw_step(cr, *at, step, *color, *size),
+        Shape::Pixelate { rect } => draw_obscured(cr, image, rect, PIXELATE_BLOCK, cairo::Filter::Nearest),
+        Shape::Blur { rect } => draw_obscured(cr, image, rect, BLUR_FACTOR, cairo::Filter::Bilinear),
+    }
+}
+
+// The image and its annotations, in image coordinates
+fn draw_document(cr: &cairo::Context, image: &cairo::ImageSurface, doc: &Document, pending: Option<&Shape>) {
+    let _ = cr.set_source_surface(image, 0.0, 0.0);
+    let _ = cr.paint();
+    let mut step = 0;
+    for shape in doc.shapes.iter().chain(pending) {
+        if matches!(shape, Shape::Step { .. }) {
+            step += 1;
+        }
+        draw_shape(cr, image, shape, step);
+    }
+}
+
+fn draw_editor(cr: &cairo::Context, editor: &mut Editor, width: i32, height: i32) {
+    cr.set_source_rgb(0.15, 0.15, 0.15);
+    let _ = cr.paint();
+
+    // Fit the image to the area, never enlarging it
+    let (image_width, image_height) = editor.image_size();
+    let scale = (width as f64 / image_width).min(height as f64 / image_height).min(1.0);
+    editor.scale = scale;
+    editor.offset = (
+        ((width as f64 - image_width * scale) / 2.0).max(0.0),
+        ((height as f64 - image_height * scale) / 2.0).max(0.0),
+    );
+
+    let _ = cr.save();
+    cr.translate(editor.offset.0, editor.offset.1);
+    cr.scale(scale, scale);
+    draw_document(cr, &editor.image, &editor.doc, editor.pending.as_ref());
+
+    // Dim what the crop leaves out, with the crop being dragged taking precedence
+    if let Some(crop) = editor.pending_crop.or(editor.doc.crop) {
+        cr.set_source_rgba(0.0, 0.0, 0.0, 0.55);
+        cr.set_fill_rule(cairo::FillRule::EvenOdd);
+        cr.rectangle(0.0, 0.0, image_width, image_height);
+        cr.rectangle(crop.x, crop.y, crop.width, crop.height);
+        let _ = cr.fill();
+        cr.set_fill_rule(cairo::FillRule::Winding);
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        cr.set_line_width(1.0 / scale);
+        cr.set_dash(&[6.0 / scale, 4.0 / scale], 0.0);
+        cr.rectangle(crop.x, crop.y, crop.width, crop.height);
+        let _ = cr.stroke();
+        cr.set_dash(&[], 0.0);
+    }
+    let _ = cr.restore();
+}
+
+// Flatten the document at full resolution, cropped
+fn render(image: &cairo::ImageSurface, doc: &Document) -> Result<RgbaImage> {
+    let full = Rect { x: 0.0, y: 0.0, width: image.width() as f64, height: image.height() as f64 };
+    let crop = doc.crop.unwrap_or(full);
+    let (width, height) = (crop.width.max(1.0) as i32, crop.height.max(1.0) as i32);
+    let mut surface = cairo::ImageSurface::create(cairo::Format::ARgb32, width, height)?;
+    {
+        let cr = cairo::Context::new(&surface)?;
+        cr.translate(-crop.x, -crop.y);
+        draw_document(&cr, image, doc, None);
+    }
+    surface.flush();
+    let stride = surface.stride() as usize;
+    let data = surface.data().map_err(|e| anyhow!("Failed to read rendered image: {}", e))?;
+
+    // Cairo stores premultiplied ARGB in native-endian words
+    let mut rgba = RgbaImage::new(width as u32, height as u32);
+    for (y, row) in data.chunks(stride).take(height as usize).enumerate() {
+        for (x, word) in row.chunks_exact(4).take(width as usize).enumerate() {
+            let argb = u32::from_ne_bytes([word[0], word[1], word[2], word[3]]);
+            let alpha = argb >> 24;
+            let channel = |shift: u32| -> u8 {
+                let value = (argb >> shift) & 0xff;
+                if alpha == 0 { 0 } else { ((value * 255 + alpha / 2) / alpha).min(255) as u8 }
+            };
+            rgba.put_pixel(x as u32, y as u32, image::Rgba([channel(16), channel(8), channel(0), alpha as u8]));
+        }
+    }
+    Ok(rgba)
+}
+
+// `<stem>_annotated.<ext>` in `dir`, numbered if that is taken
+fn copy_path(original: &Path, dir: &Path) -> PathBuf {
+    let stem = original.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "screenshot".into());
+    let extension = OutputFormat::from_path(original).extension();
+    let mut candidate = dir.join(format!("{}_annotated.{}", stem, extension));
+    let mut n = 2;
+    while candidate.exists() {
+        candidate = dir.join(format!("{}_annotated_{}.{}", stem, n, extension));
+        n += 1;
+    }
+    candidate
+}
+
+// Render and encode off the main thread, keeping the original's format
+async fn save(editor: &Rc<RefCell<Editor>>, as_copy: bool) -> Result<PathBuf> {
+    let (rgba, target) = {
+        let editor = editor.borrow();
+        let target = if as_copy {
+            std::fs::create_dir_all(&editor.copy_dir)?;
+            copy_path(&editor.path, &editor.copy_dir)
+        } else {
+            editor.path.clone()
+        };
+        (render(&editor.image, &editor.doc)?, target)
+    };
+    let path = target.clone();
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let data = postprocess::encode(&DynamicImage::ImageRgba8(rgba), OutputFormat::from_path(&path), QUALITY)?;
+        std::fs::write(&path, data)?;
+        Ok(())
+    })
+    .await??;
+    Ok(target)
+}
+
+fn tool_buttons(editor: &Rc<RefCell<Editor>>) -> gtk::Box {
+    let tools_box = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+    tools_box.add_css_class("linked");
+    let mut group: Option<gtk::ToggleButton> = None;
+    for (tool, label, tooltip) in TOOLS {
+        let button = gtk::ToggleButton::with_label(label);
+        button.set_tooltip_text(Some(tooltip));
+        match &group {
+            Some(first) => button.set_group(Some(first)),
+            None => {
+                button.set_active(true);
+                group = Some(button.clone());
+            }
+        }
+        let editor = editor.clone();
+        button.connect_toggled(move |button| {
+            if button.is_active() {
+                editor.borrow_mut().tool = tool;
+            }
+        });
+        tools_box.append(&button);
+    }
+    tools_box
+}
+
+// Open `path` in the editor. Returns where the result was saved: `path`
+// itself or a copy in `copy_dir`, or None if the editor was closed without
+// saving.
+pub async fn annotate(path: &Path, copy_dir: &Path) -> Option<PathBuf> {
+    let image = match load_surface(path) {
+        Ok(image) => image,
+        Err(e) => {
+            crate::ui::toasts::show_toast(&format!("Failed to open {}: {}", path.display(), e));
+            return None;
+        }
+    };
+
+    let window = adw::Window::new();
+    window.set_title(Some("Annotate Screenshot"));
+    window.set_modal(true);
+    window.set_default_size(1100, 760);
+    MAIN_WINDOW.with(|w| {
+        if let Some(main_window) = w.borrow().as_ref() {
+            window.set_transient_for(Some(main_window));
+        }
+    });
+
+    let area = gtk::DrawingArea::new();
+    area.set_hexpand(true);
+    area.set_vexpand(true);
+    area.set_cursor_from_name(Some("crosshair"));
+    let title = adw::WindowTitle::new("Annotate Screenshot", "");
+    let undo_btn = gtk::Button::from_icon_name("edit-undo-symbolic");
+    undo_btn.set_tooltip_text(Some("Undo (Ctrl+Z)"));
+    let redo_btn = gtk::Button::from_icon_name("edit-redo-symbolic");
+    redo_btn.set_tooltip_text(Some("Redo (Ctrl+Shift+Z)"));
+
+    let (sender, receiver) = oneshot::channel();
+    let editor = Rc::new(RefCell::new(Editor {
+        path: path.to_path_buf(),
+        copy_dir: copy_dir.to_path_buf(),
+        image,
+        doc: Document::default(),
+        undo: Vec::new(),
+        redo: Vec::new(),
+        tool: Tool::Arrow,
+        color: (0.9, 0.1, 0.1),
+        width: 4.0,
+        text: String::new(),
+        drag_start: None,
+        pending: None,
+        pending_crop: None,
+        scale: 1.0,
+        offset: (0.0, 0.0),
+        area: area.clone(),
+        title: title.clone(),
+        undo_btn: undo_btn.clone(),
+        redo_btn: redo_btn.clone(),
+        result: Some(sender),
+    }));
+    editor.borrow().changed();
+
+    let header = adw::HeaderBar::new();
+    header.set_title_widget(Some(&title));
+    let undo_redo = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+    undo_redo.add_css_class("linked");
+    undo_redo.append(&undo_btn);
+    undo_redo.append(&redo_btn);
+    header.pack_start(&undo_redo);
+    let save_btn = gtk::Button::with_label("Save");
+    save_btn.add_css_class("suggested-action");
+    save_btn.set_tooltip_text(Some("Overwrite the screenshot (Ctrl+S)"));
+    let save_copy_btn = gtk::Button::with_label("Save Copy");
+    save_copy_btn.set_tooltip_text(Some("Save as a new file in the screenshot directory (Ctrl+Shift+S)"));
+    header.pack_end(&save_btn);
+    header.pack_end(&save_copy_btn);
+
+    // Tools, then the style they draw with
+    let toolbar = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    toolbar.set_margin_top(6);
+    toolbar.set_margin_bottom(6);
+    toolbar.set_margin_start(12);
+    toolbar.set_margin_end(12);
+    toolbar.append(&tool_buttons(&editor));
+    let color_btn = gtk::ColorButton::with_rgba(&gdk::RGBA::new(0.9, 0.1, 0.1, 1.0));
+    color_btn.set_tooltip_text(Some("Colour"));
+    let editor_for_color = editor.clone();
+    color_btn.connect_color_set(move |button| {
+        let rgba = button.rgba();
+        editor_for_color.borrow_mut().color = (rgba.red() as f64, rgba.green() as f64, rgba.blue() as f64);
+    });
+    toolbar.append(&color_btn);
+    let width_spin = gtk::SpinButton::with_range(1.0, 32.0, 1.0);
+    width_spin.set_value(4.0);
+    width_spin.set_tooltip_text(Some("Line width; also sets text and marker size"));
+    let editor_for_width = editor.clone();
+    width_spin.connect_value_changed(move |spin| {
+        editor_for_width.borrow_mut().width = spin.value();
+    });
+    toolbar.append(&width_spin);
+    let text_entry = gtk::Entry::new();
+    text_entry.set_placeholder_text(Some("Text to place"));
+    text_entry.set_hexpand(true);
+    let editor_for_text = editor.clone();
+    text_entry.connect_changed(move |entry| {
+        editor_for_text.borrow_mut().text = entry.text().to_string();
+    });
+    toolbar.append(&text_entry);
+
+    let editor_for_draw = editor.clone();
+    area.set_draw_func(move |_, cr, width, height| {
+        draw_editor(cr, &mut editor_for_draw.borrow_mut(), width, height);
+    });
+
+    let drag = gtk::GestureDrag::new();
+    let editor_for_begin = editor.clone();
+    drag.connect_drag_begin(move |_, x, y| {
+        let mut editor = editor_for_begin.borrow_mut();
+        let point = editor.to_image(x, y);
+        editor.begin(point);
+    });
+    let editor_for_update = editor.clone();
+    drag.connect_drag_update(move |gesture, dx, dy| {
+        if let Some((x, y)) = gesture.start_point() {
+            let mut editor = editor_for_update.borrow_mut();
+            let point = editor.to_image(x + dx, y + dy);
+            editor.update(point);
+        }
+    });
+    let editor_for_end = editor.clone();
+    drag.connect_drag_end(move |_, _, _| editor_for_end.borrow_mut().end());
+    area.add_controller(drag);
+
+    let editor_for_undo = editor.clone();
+    undo_btn.connect_clicked(move |_| editor_for_undo.borrow_mut().undo());
+    let editor_for_redo = editor.clone();
+    redo_btn.connect_clicked(move |_| editor_for_redo.borrow_mut().redo());
+
+    let start_save = {
+        let editor = editor.clone();
+        let window = window.clone();
+        let save_btn = save_btn.clone();
+        let save_copy_btn = save_copy_btn.clone();
+        move |as_copy: bool| {
+            if !save_btn.is_sensitive() {
+                return;
+            }
+            save_btn.set_sensitive(false);
+            save_copy_btn.set_sensitive(false);
+            let editor = editor.clone();
+            let window = window.clone();
+            let save_btn = save_btn.clone();
+            let save_copy_btn = save_copy_btn.clone();
+            glib::MainContext::default().spawn_local(async move {
+                match save(&editor, as_copy).await {
+                    Ok(saved) => {
+                        editor.borrow_mut().finish(Some(saved));
+                        window.close();
+                    }
+                    Err(e) => {
+                        crate::ui::toasts::show_toast(&format!("Failed to save annotations: {}", e));
+                        save_btn.set_sensitive(true);
+                        save_copy_btn.set_sensitive(true);
+                    }
+                }
+            });
+        }
+    };
+    let save_for_btn = start_save.clone();
+    save_btn.connect_clicked(move |_| save_for_btn(false));
+    let save_copy_for_btn = start_save.clone();
+    save_copy_btn.connect_clicked(move |_| save_copy_for_btn(true));
+
+    let key_controller = gtk::EventControllerKey::new();
+    let editor_for_key = editor.clone();
+    let window_for_key = window.clone();
+    key_controller.connect_key_pressed(move |_, keyval, _, modifiers| {
+        let ctrl = modifiers.contains(gdk::ModifierType::CONTROL_MASK);
+        let shift = modifiers.contains(gdk::ModifierType::SHIFT_MASK);
+        match keyval {
+            gdk::Key::z | gdk::Key::Z if ctrl && shift => editor_for_key.borrow_mut().redo(),
+            gdk::Key::z | gdk::Key::Z if ctrl => editor_for_key.borrow_mut().undo(),
+            gdk::Key::y | gdk::Key::Y if ctrl => editor_for_key.borrow_mut().redo(),
+            gdk::Key::s | gdk::Key::S if ctrl => start_save(shift),
+            // Escape doesn't throw away unsaved work; the close button does
+            gdk::Key::Escape if !editor_for_key.borrow().is_dirty() => window_for_key.close(),
+            _ => return glib::Propagation::Proceed,
+        }
+        glib::Propagation::Stop
+    });
+    window.add_controller(key_controller);
+
+    // Closing without saving returns None
+    let editor_for_close = editor.clone();
+    window.connect_close_request(move |_| {
+        if let Ok(mut editor) = editor_for_close.try_borrow_mut() {
+            editor.finish(None);
+        }
+        glib::Propagation::Proceed
+    });
+
+    let vbox = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    vbox.append(&header);
+    vbox.append(&toolbar);
+    vbox.append(&area);
+    window.set_content(Some(&vbox));
+    window.present();
+
+    receiver.await.ok().flatten()
+}
@@ -0,0 +1,66 @@
+use crate::display_manager::auto_brightness;
+use crate::ui::main_window::{update_settings, SharedSettings};
+use gtk::prelude::*;
+use libadwaita as adw;
+
+pub fn add_auto_brightness_section(content: &gtk::Box, settings: SharedSettings) {
+    let (enabled, external) = {
+        let settings = settings.borrow();
+        (settings.auto_brightness.unwrap_or(false), settings.auto_brightness_external.unwrap_or(false))
+    };
+    let clamp = adw::Clamp::new();
+    clamp.set_maximum_size(900);
+    clamp.set_margin_start(24);
+    clamp.set_margin_end(24);
+
+    let frame = gtk::Frame::new(Some("Auto-Brightness"));
+    let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    hbox.set_margin_top(8);
+    hbox.set_margin_bottom(8);
+    hbox.set_margin_start(8);
+    hbox.set_margin_end(8);
+    let enable_switch = gtk::Switch::new();
+    enable_switch.set_valign(gtk::Align::Center);
+    enable_switch.set_active(enabled);
+    let external_check = gtk::CheckButton::with_label("Include external monitors (DDC)");
+    external_check.set_active(external);
+    hbox.append(&gtk::Label::new(Some("Follow ambient light sensor")));
+    hbox.append(&enable_switch);
+    hbox.append(&external_check);
+    frame.set_child(Some(&hbox));
+    clamp.set_child(Some(&frame));
+    content.append(&clamp);
+
+    let curve = settings
+        .borrow()
+        .auto_brightness_curve
+        .clone()
+        .unwrap_or_else(|| auto_brightness::DEFAULT_CURVE.to_vec());
+    if enabled {
+        auto_brightness::start_auto_brightness(curve.clone(), external);
+    }
+
+    let save = move |enabled: bool, external: bool| {
+        // Restart so the external toggle takes effect
+        auto_brightness::stop_auto_brightness();
+        if enabled {
+            auto_brightness::start_auto_brightness(curve.clone(), external);
+        }
+        let saved = update_settings(&settings, |s| {
+            s.auto_brightness = Some(enabled);
+            s.auto_brightness_external = Some(external);
+        });
+        if let Err(e) = saved {
+            crate::ui::toasts::show_toast(&format!("Failed to save settings: {}", e));
+        }
+    };
+    let save_for_switch = save.clone();
+    let external_for_switch = external_check.clone();
+    enable_switch.connect_active_notify(move |switch| {
+        save_for_switch(switch.is_active(), external_for_switch.is_active());
+    });
+    let switch_for_external = enable_switch.clone();
+    external_check.connect_toggled(move |check| {
+        save(switch_for_external.is_active(), check.is_active());
+    });
+}
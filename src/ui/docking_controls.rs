@@ -0,0 +1,45 @@
+use crate::display_manager::docking;
+use crate::ui::main_window::{update_settings, SharedSettings};
+use gtk::prelude::*;
+use libadwaita as adw;
+
+pub fn add_docking_section(content: &gtk::Box, settings: SharedSettings) {
+    let clamp = adw::Clamp::new();
+    clamp.set_maximum_size(900);
+    clamp.set_margin_start(24);
+    clamp.set_margin_end(24);
+
+    let frame = gtk::Frame::new(Some("Docking"));
+    let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    hbox.set_margin_top(8);
+    hbox.set_margin_bottom(8);
+    hbox.set_margin_start(8);
+    hbox.set_margin_end(8);
+    let enable_switch = gtk::Switch::new();
+    enable_switch.set_valign(gtk::Align::Center);
+    enable_switch.set_active(settings.borrow().docking.unwrap_or(false));
+    enable_switch.set_tooltip_text(Some(
+        "Turn the laptop panel off when the lid closes while an external monitor is connected, and back on when it opens",
+    ));
+    hbox.append(&gtk::Label::new(Some("Laptop panel follows the lid when docked")));
+    hbox.append(&enable_switch);
+    frame.set_child(Some(&hbox));
+    clamp.set_child(Some(&frame));
+    content.append(&clamp);
+
+    if settings.borrow().docking.unwrap_or(false) {
+        docking::start_docking();
+    }
+
+    enable_switch.connect_active_notify(move |switch| {
+        let enabled = switch.is_active();
+        if enabled {
+            docking::start_docking();
+        } else {
+            docking::stop_docking();
+        }
+        if let Err(e) = update_settings(&settings, |s| s.docking = Some(enabled)) {
+            crate::ui::toasts::show_toast(&format!("Failed to save settings: {}", e));
+        }
+    });
+}
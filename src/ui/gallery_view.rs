@@ -0,0 +1,365 @@
+use crate::gallery::{self, GalleryEntry, EXTERNAL_MODE};
+use crate::output_sinks;
+use crate::ui::main_window::{SharedSettings, MAIN_WINDOW};
+use chrono::Local;
+use gtk::gdk_pixbuf::Pixbuf;
+use gtk::{gdk, gio, glib, pango};
+use libadwaita as adw;
+use adw::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::rc::Rc;
+
+const THUMBNAIL_WIDTH: i32 = 200;
+const THUMBNAIL_HEIGHT: i32 = 130;
+
+const MODE_FILTERS: [(&str, &str); 9] = [
+    ("All modes", ""),
+    ("Fullscreen", "fullscreen"),
+    ("Region", "region"),
+    ("Window", "window"),
+    ("Monitor", "monitor"),
+    ("Choose Monitor", "choose_monitor"),
+    ("Each Monitor", "each_monitor"),
+    ("Text", "capture_text"),
+    ("Other apps", EXTERNAL_MODE),
+];
+
+// Label and maximum age in days
+const DATE_FILTERS: [(&str, Option<i64>); 4] = [
+    ("Any time", None),
+    ("Past day", Some(1)),
+    ("Past week", Some(7)),
+    ("Past month", Some(30)),
+];
+
+thread_local! {
+    static REFRESH: RefCell<Option<Rc<dyn Fn()>>> = RefCell::new(None);
+}
+
+// Reload the gallery after the index changed elsewhere, e.g. a new capture
+pub fn refresh() {
+    if let Some(refresh) = REFRESH.with(|r| r.borrow().clone()) {
+        refresh();
+    }
+}
+
+fn mode_label(mode: &str) -> &str {
+    MODE_FILTERS.iter().find(|(_, key)| *key == mode).map(|(label, _)| *label).unwrap_or(mode)
+}
+
+// Thumbnail from the cache, generated on first use. Decodes the full
+// capture, so run it off the main thread
+fn thumbnail(path: &Path) -> Option<PathBuf> {
+    let thumb = gallery::thumbnail_path(path);
+    if thumb.exists() {
+        return Some(thumb);
+    }
+    let pixbuf = Pixbuf::from_file_at_scale(path, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT, true).ok()?;
+    if let Some(dir) = thumb.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    match pixbuf.savev(&thumb, "png", &[]) {
+        Ok(()) => Some(thumb),
+        Err(e) => {
+            tracing::warn!("Failed to cache thumbnail for {:?}: {}", path, e);
+            Some(path.to_path_buf())
+        }
+    }
+}
+
+fn build_item(entry: &GalleryEntry) -> (gtk::Box, gtk::Picture) {
+    let item = gtk::Box::new(gtk::Orientation::Vertical, 4);
+    let picture = gtk::Picture::new();
+    picture.set_can_shrink(true);
+    picture.set_size_request(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT);
+    picture.set_alternative_text(Some(&entry.file_name()));
+    item.append(&picture);
+    let name = gtk::Label::new(Some(&entry.file_name()));
+    name.set_ellipsize(pango::EllipsizeMode::Middle);
+    name.set_max_width_chars(24);
+    item.append(&name);
+    let details = gtk::Label::new(Some(&format!(
+        "{}  ·  {}",
+        mode_label(&entry.mode),
+        entry.timestamp.format("%Y-%m-%d %H:%M")
+    )));
+    details.add_css_class("dim-label");
+    details.add_css_class("caption");
+    item.append(&details);
+
+    let mut tooltip = vec![entry.path.display().to_string()];
+    if let Some(monitor) = &entry.monitor {
+        tooltip.push(format!("Monitor: {}", monitor));
+    }
+    if let Some(class) = &entry.window_class {
+        let title = entry.window_title.as_deref().unwrap_or_default();
+        tooltip.push(format!("Window: {} — {}", class, title));
+    }
+    item.set_tooltip_text(Some(&tooltip.join("\n")));
+    (item, picture)
+}
+
+pub fn add_gallery_section(content: &gtk::Box, settings: SharedSettings) {
+    let clamp = adw::Clamp::new();
+    clamp.set_maximum_size(900);
+    clamp.set_margin_start(24);
+    clamp.set_margin_end(24);
+
+    let frame = gtk::Frame::new(Some("Gallery"));
+    let vbox = gtk::Box::new(gtk::Orientation::Vertical, 8);
+    vbox.set_margin_top(8);
+    vbox.set_margin_bottom(8);
+    vbox.set_margin_start(8);
+    vbox.set_margin_end(8);
+
+    let filter_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    let search = gtk::SearchEntry::new();
+    search.set_placeholder_text(Some("Search file, monitor or window"));
+    search.set_hexpand(true);
+    let mode_combo = gtk::ComboBoxText::new();
+    for (label, key) in MODE_FILTERS {
+        mode_combo.append(Some(key), label);
+    }
+    mode_combo.set_active(Some(0));
+    let date_combo = gtk::ComboBoxText::new();
+    for (label, _) in DATE_FILTERS {
+        date_combo.append_text(label);
+    }
+    date_combo.set_active(Some(0));
+    filter_box.append(&search);
+    filter_box.append(&mode_combo);
+    filter_box.append(&date_combo);
+    vbox.append(&filter_box);
+
+    let flow = gtk::FlowBox::new();
+    flow.set_selection_mode(gtk::SelectionMode::Multiple);
+    flow.set_activate_on_single_click(false);
+    flow.set_homogeneous(true);
+    flow.set_max_children_per_line(4);
+    flow.set_column_spacing(8);
+    flow.set_row_spacing(8);
+    let scrolled = gtk::ScrolledWindow::new();
+    scrolled.set_min_content_height(360);
+    scrolled.set_child(Some(&flow));
+    vbox.append(&scrolled);
+
+    let actions_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    let count_label = gtk::Label::new(None);
+    count_label.set_hexpand(true);
+    count_label.set_xalign(0.0);
+    let open_btn = gtk::Button::with_label("Open");
+    let move_btn = gtk::Button::with_label("Move To…");
+    let trash_btn = gtk::Button::with_label("Move to Trash");
+    trash_btn.add_css_class("destructive-action");
+    actions_box.append(&count_label);
+    actions_box.append(&open_btn);
+    actions_box.append(&move_btn);
+    actions_box.append(&trash_btn);
+    vbox.append(&actions_box);
+
+    frame.set_child(Some(&vbox));
+    clamp.set_child(Some(&frame));
+    content.append(&clamp);
+
+    // The whole index, loaded once per refresh and filtered in memory
+    let entries: Rc<RefCell<Vec<GalleryEntry>>> = Rc::new(RefCell::new(Vec::new()));
+    // Entries currently shown, in flow box order (newest first)
+    let shown: Rc<RefCell<Vec<GalleryEntry>>> = Rc::new(RefCell::new(Vec::new()));
+    // Bumped on every reload so stale thumbnail loaders stop
+    let generation = Rc::new(Cell::new(0u64));
+
+    let selected_paths = {
+        let flow = flow.clone();
+        let shown = shown.clone();
+        move || -> Vec<PathBuf> {
+            let shown = shown.borrow();
+            flow.selected_children()
+                .iter()
+                .filter_map(|child| shown.get(child.index() as usize).map(|e| e.path.clone()))
+                .collect()
+        }
+    };
+
+    let apply_filters: Rc<dyn Fn()> = {
+        let flow = flow.clone();
+        let entries = entries.clone();
+        let shown = shown.clone();
+        let search = search.clone();
+        let mode_combo = mode_combo.clone();
+        let date_combo = date_combo.clone();
+        let count_label = count_label.clone();
+        let selected_paths = selected_paths.clone();
+        Rc::new(move || {
+            let entries = entries.borrow();
+            let total = entries.len();
+            let mode = mode_combo.active_id().map(|id| id.to_string()).unwrap_or_default();
+            let max_age = date_combo
+                .active()
+                .and_then(|i| DATE_FILTERS.get(i as usize))
+                .and_then(|(_, days)| *days);
+            let query = search.text().to_string();
+            let now = Local::now();
+            let filtered: Vec<GalleryEntry> = entries
+                .iter()
+                .rev()
+                .filter(|e| mode.is_empty() || e.mode == mode)
+                .filter(|e| max_age.is_none_or(|days| now.signed_duration_since(e.timestamp).num_days() < days))
+                .filter(|e| e.matches(&query))
+                .cloned()
+                .collect();
+
+            while let Some(child) = flow.first_child() {
+                flow.remove(&child);
+            }
+            let mut pictures = Vec::new();
+            for entry in &filtered {
+                let (item, picture) = build_item(entry);
+                // Drag the selection out to other apps, or just this item
+                // when it is not part of the selection
+                let drag = gtk::DragSource::new();
+                drag.set_actions(gdk::DragAction::COPY);
+                let path = entry.path.clone();
+                let selected_paths = selected_paths.clone();
+                drag.connect_prepare(move |_, _, _| {
+                    let mut paths = selected_paths();
+                    if !paths.contains(&path) {
+                        paths = vec![path.clone()];
+                    }
+                    let list: String = paths
+                        .iter()
+                        .map(|p| format!("{}\r\n", output_sinks::file_uri(p)))
+                        .collect();
+                    Some(gdk::ContentProvider::for_bytes("text/uri-list", &glib::Bytes::from_owned(list.into_bytes())))
+                });
+                item.add_controller(drag);
+                flow.insert(&item, -1);
+                pictures.push((picture, entry.path.clone()));
+            }
+            count_label.set_text(&format!("{} of {} screenshots", filtered.len(), total));
+            *shown.borrow_mut() = filtered;
+
+            generation.set(generation.get() + 1);
+            let current = generation.get();
+            let generation = generation.clone();
+            glib::MainContext::default().spawn_local(async move {
+                for (picture, path) in pictures {
+                    if generation.get() != current {
+                        return;
+                    }
+                    let thumb = gio::spawn_blocking(move || thumbnail(&path)).await.ok().flatten();
+                    // The view may have been rebuilt while decoding
+                    if generation.get() != current {
+                        return;
+                    }
+                    if let Some(thumb) = thumb {
+                        picture.set_filename(Some(&thumb));
+                    }
+                }
+            });
+        })
+    };
+
+    // Re-read the index and pick up files added or removed on disk
+    let populate: Rc<dyn Fn()> = {
+        let apply_filters = apply_filters.clone();
+        Rc::new(move || {
+            let mut loaded = gallery::load();
+            if gallery::sync_with_dir(&mut loaded, &settings.borrow().screenshot_dir) {
+                if let Err(e) = gallery::save(&loaded) {
+                    tracing::warn!("Failed to save gallery index: {}", e);
+                }
+            }
+            *entries.borrow_mut() = loaded;
+            apply_filters();
+        })
+    };
+    REFRESH.with(|r| *r.borrow_mut() = Some(populate.clone()));
+
+    let filter_for_search = apply_filters.clone();
+    search.connect_search_changed(move |_| filter_for_search());
+    let filter_for_mode = apply_filters.clone();
+    mode_combo.connect_changed(move |_| filter_for_mode());
+    let filter_for_date = apply_filters;
+    date_combo.connect_changed(move |_| filter_for_date());
+
+    let shown_for_activate = shown.clone();
+    flow.connect_child_activated(move |_, child| {
+        if let Some(entry) = shown_for_activate.borrow().get(child.index() as usize) {
+            let _ = Command::new("xdg-open").arg(&entry.path).spawn();
+        }
+    });
+
+    let selected_for_open = selected_paths.clone();
+    open_btn.connect_clicked(move |_| {
+        for path in selected_for_open() {
+            let _ = Command::new("xdg-open").arg(path).spawn();
+        }
+    });
+
+    // Trash rather than delete, so a mistaken selection can be recovered
+    let selected_for_trash = selected_paths.clone();
+    let populate_for_trash = populate.clone();
+    trash_btn.connect_clicked(move |_| {
+        let paths = selected_for_trash();
+        if paths.is_empty() {
+            return;
+        }
+        let mut trashed = Vec::new();
+        for path in &paths {
+            match gio::File::for_path(path).trash(gio::Cancellable::NONE) {
+                Ok(()) => trashed.push(path.clone()),
+                Err(e) => tracing::warn!("Failed to trash {:?}: {}", path, e),
+            }
+        }
+        if let Err(e) = gallery::forget(&trashed) {
+            crate::ui::toasts::show_toast(&format!("Failed to update gallery: {}", e));
+        }
+        crate::ui::toasts::show_toast(&format!("Moved {} of {} to the trash", trashed.len(), paths.len()));
+        populate_for_trash();
+    });
+
+    let selected_for_move = selected_paths;
+    let populate_for_move = populate.clone();
+    // Native dialogs are not kept alive by GTK
+    let chooser_slot: Rc<RefCell<Option<gtk::FileChooserNative>>> = Rc::new(RefCell::new(None));
+    move_btn.connect_clicked(move |_| {
+        let paths = selected_for_move();
+        if paths.is_empty() {
+            return;
+        }
+        let parent = MAIN_WINDOW.with(|w| w.borrow().clone());
+        let chooser = gtk::FileChooserNative::new(
+            Some("Move Screenshots"),
+            parent.as_ref(),
+            gtk::FileChooserAction::SelectFolder,
+            Some("Move"),
+            Some("Cancel"),
+        );
+        let populate = populate_for_move.clone();
+        chooser.connect_response(move |chooser, response| {
+            let dir = chooser.file().and_then(|f| f.path());
+            chooser.destroy();
+            let (gtk::ResponseType::Accept, Some(dir)) = (response, dir) else {
+                return;
+            };
+            match gallery::move_to(&paths, &dir) {
+                Ok(failed) if failed.is_empty() => {
+                    crate::ui::toasts::show_toast(&format!("Moved {} to {}", paths.len(), dir.display()))
+                }
+                Ok(failed) => crate::ui::toasts::show_toast(&format!(
+                    "Could not move {} of {} (name taken or unreadable)",
+                    failed.len(),
+                    paths.len()
+                )),
+                Err(e) => crate::ui::toasts::show_toast(&format!("Move failed: {}", e)),
+            }
+            populate();
+        });
+        chooser.show();
+        *chooser_slot.borrow_mut() = Some(chooser);
+    });
+
+    populate();
+}
@@ -0,0 +1,123 @@
+use crate::display_manager::input_mapping::{self, InputBinding, InputDevice, InputKind};
+use crate::display_manager::monitor_control;
+use crate::display_manager::monitor_profiles::profile_key;
+use crate::display_manager::monitor_types::Monitor;
+use gtk::glib;
+use gtk::prelude::*;
+use libadwaita as adw;
+
+fn build_device_row(kind: InputKind, device: &InputDevice, monitors: &[Monitor], bindings: &[InputBinding]) -> gtk::ListBoxRow {
+    let row = gtk::ListBoxRow::new();
+    let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    let icon = match kind {
+        InputKind::Tablet => "input-tablet-symbolic",
+        InputKind::Touch => "input-touchpad-symbolic",
+    };
+    hbox.append(&gtk::Image::from_icon_name(icon));
+    let label = gtk::Label::new(Some(&device.name));
+    label.set_hexpand(true);
+    label.set_xalign(0.0);
+    hbox.append(&label);
+
+    let existing = bindings.iter().find(|b| b.device == device.name);
+    let output_combo = gtk::ComboBoxText::new();
+    output_combo.append(Some(""), "All monitors");
+    for monitor in monitors {
+        output_combo.append(Some(&profile_key(monitor)), &monitor.name);
+    }
+    output_combo.set_active_id(Some(existing.map(|b| b.monitor.as_str()).unwrap_or("")));
+    hbox.append(&output_combo);
+
+    let region_entry = gtk::Entry::new();
+    region_entry.set_placeholder_text(Some("Region: x y w h"));
+    region_entry.set_width_chars(14);
+    if let Some([x, y, w, h]) = existing.and_then(|b| b.region) {
+        region_entry.set_text(&format!("{} {} {} {}", x, y, w, h));
+    }
+    // Mapped regions only exist for tablets
+    region_entry.set_visible(kind == InputKind::Tablet);
+    hbox.append(&region_entry);
+
+    let apply_btn = gtk::Button::with_label("Apply");
+    hbox.append(&apply_btn);
+    let device_name = device.name.clone();
+    apply_btn.connect_clicked(move |_| {
+        let monitor = output_combo.active_id().map(|id| id.to_string()).unwrap_or_default();
+        let region = match kind {
+            InputKind::Tablet => match input_mapping::parse_region(&region_entry.text()) {
+                Ok(region) => region,
+                Err(e) => {
+                    crate::ui::toasts::show_toast(&e.to_string());
+                    return;
+                }
+            },
+            InputKind::Touch => None,
+        };
+        let device_name = device_name.clone();
+        glib::MainContext::default().spawn_local(async move {
+            let result = if monitor.is_empty() {
+                input_mapping::unbind_device(&device_name).await
+            } else {
+                input_mapping::bind_device(InputBinding { device: device_name.clone(), kind, monitor, region }).await
+            };
+            if let Err(e) = result {
+                crate::ui::toasts::show_toast(&format!("Input mapping error: {}", e));
+            } else {
+                crate::ui::toasts::show_toast(&format!("Mapping applied for {}", device_name));
+            }
+        });
+    });
+
+    row.set_child(Some(&hbox));
+    row
+}
+
+fn refresh_device_list(list_box: &gtk::ListBox) {
+    let list_box = list_box.clone();
+    glib::MainContext::default().spawn_local(async move {
+        let devices = match input_mapping::list_input_devices().await {
+            Ok(devices) => devices,
+            Err(e) => {
+                crate::ui::toasts::show_toast(&format!("Failed to list input devices: {}", e));
+                return;
+            }
+        };
+        let monitors = monitor_control::get_monitors().await.unwrap_or_default();
+        let bindings = input_mapping::load_bindings();
+        while let Some(child) = list_box.first_child() {
+            list_box.remove(&child);
+        }
+        if devices.is_empty() {
+            list_box.append(&gtk::Label::new(Some("No tablets or touchscreens found")));
+        }
+        for (kind, device) in &devices {
+            list_box.append(&build_device_row(*kind, device, &monitors, &bindings));
+        }
+    });
+}
+
+pub fn add_input_mapping_section(content: &gtk::Box) {
+    let clamp = adw::Clamp::new();
+    clamp.set_maximum_size(900);
+    clamp.set_margin_start(24);
+    clamp.set_margin_end(24);
+
+    let frame = gtk::Frame::new(Some("Tablets & Touchscreens"));
+    let vbox = gtk::Box::new(gtk::Orientation::Vertical, 8);
+    vbox.set_margin_top(8);
+    vbox.set_margin_bottom(8);
+    vbox.set_margin_start(8);
+    vbox.set_margin_end(8);
+    let device_list = gtk::ListBox::new();
+    device_list.set_selection_mode(gtk::SelectionMode::None);
+    vbox.append(&device_list);
+    let refresh_btn = gtk::Button::with_label("Refresh Devices");
+    refresh_btn.set_halign(gtk::Align::Start);
+    vbox.append(&refresh_btn);
+    frame.set_child(Some(&vbox));
+    clamp.set_child(Some(&frame));
+    content.append(&clamp);
+
+    refresh_device_list(&device_list);
+    refresh_btn.connect_clicked(move |_| refresh_device_list(&device_list));
+}
@@ -0,0 +1,113 @@
+use crate::display_manager::layout_plan;
+use crate::display_manager::monitor_control;
+use crate::display_manager::monitor_types::Monitor;
+use crate::ui::main_window::MAIN_WINDOW;
+use gtk::gdk;
+use gtk::glib;
+use libadwaita as adw;
+use adw::prelude::*;
+
+// Show what applying `desired` would change, monitor by monitor, together
+// with the exact hyprctl commands, and only apply once confirmed.
+pub fn show_layout_preview(desired: Vec<Monitor>) {
+    glib::MainContext::default().spawn_local(async move {
+        let current = match monitor_control::get_all_monitors().await {
+            Ok(monitors) => monitors,
+            Err(e) => {
+                crate::ui::toasts::show_toast(&format!("Failed to load monitors: {}", e));
+                return;
+            }
+        };
+        let diffs = layout_plan::layout_diff(&current, &desired);
+        if diffs.is_empty() {
+            crate::ui::toasts::show_toast("Layout unchanged, nothing to apply");
+            return;
+        }
+        present_preview(diffs, desired);
+    });
+}
+
+fn present_preview(diffs: Vec<layout_plan::MonitorDiff>, desired: Vec<Monitor>) {
+    let window = adw::Window::new();
+    window.set_title(Some("Apply Layout"));
+    window.set_modal(true);
+    window.set_default_size(560, -1);
+    MAIN_WINDOW.with(|w| {
+        if let Some(main_window) = w.borrow().as_ref() {
+            window.set_transient_for(Some(main_window));
+        }
+    });
+
+    let vbox = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    vbox.set_margin_top(24);
+    vbox.set_margin_bottom(24);
+    vbox.set_margin_start(24);
+    vbox.set_margin_end(24);
+    let header = gtk::Label::new(Some("Changes"));
+    header.add_css_class("title-2");
+    header.set_halign(gtk::Align::Start);
+    vbox.append(&header);
+
+    let list = gtk::ListBox::new();
+    list.add_css_class("boxed-list");
+    list.set_selection_mode(gtk::SelectionMode::None);
+    for diff in &diffs {
+        let row = adw::ActionRow::new();
+        row.set_title(&diff.name);
+        let changes: Vec<String> = diff.changes.iter().map(|c| c.to_string()).collect();
+        row.set_subtitle(&changes.join("\n"));
+        list.append(&row);
+    }
+    vbox.append(&list);
+
+    let commands: Vec<String> = diffs.iter().map(|d| d.planned.rule()).collect();
+    let commands_label = gtk::Label::new(Some(&commands.join("\n")));
+    commands_label.add_css_class("monospace");
+    commands_label.set_selectable(true);
+    commands_label.set_wrap(true);
+    commands_label.set_xalign(0.0);
+    let expander = gtk::Expander::new(Some("hyprctl commands"));
+    expander.set_child(Some(&commands_label));
+    vbox.append(&expander);
+
+    let btn_box = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    btn_box.set_halign(gtk::Align::End);
+    let cancel_btn = gtk::Button::with_label("Cancel");
+    let apply_btn = gtk::Button::with_label(if monitor_control::is_dry_run() { "Print Commands" } else { "Apply" });
+    apply_btn.add_css_class("suggested-action");
+    btn_box.append(&cancel_btn);
+    btn_box.append(&apply_btn);
+    vbox.append(&btn_box);
+    window.set_content(Some(&vbox));
+
+    let window_for_cancel = window.clone();
+    cancel_btn.connect_clicked(move |_| window_for_cancel.close());
+    let window_for_apply = window.clone();
+    apply_btn.connect_clicked(move |_| {
+        window_for_apply.close();
+        let desired = desired.clone();
+        glib::MainContext::default().spawn_local(async move {
+            if let Err(e) = monitor_control::apply_monitor_layout(&desired).await {
+                crate::ui::toasts::show_toast(&format!("Monitor layout error: {}", e));
+            } else if monitor_control::is_dry_run() {
+                crate::ui::toasts::show_toast("Dry run: layout commands printed to stdout");
+            } else {
+                crate::ui::toasts::show_toast("Monitor layout applied");
+            }
+        });
+    });
+
+    let key_controller = gtk::EventControllerKey::new();
+    let window_for_key = window.clone();
+    key_controller.connect_key_pressed(move |_, keyval, _, _| {
+        if keyval == gdk::Key::Escape {
+            window_for_key.close();
+            glib::Propagation::Stop
+        } else {
+            glib::Propagation::Proceed
+        }
+    });
+    window.add_controller(key_controller);
+
+    window.present();
+}
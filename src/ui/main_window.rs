@@ -2,7 +2,9 @@ use libadwaita as adw;
 use adw::prelude::*;
 use std::sync::{Arc, Mutex};
 use crate::settings::Settings;
-use crate::ui::{monitor_arrangement, network_settings, toasts, screenshot_controls, recording_controls};
+use crate::ui::{monitor_arrangement, network_settings, toasts, screenshot_controls, gallery_view, recording_controls, projection_switcher, presentation_controls, rotation_controls, input_mapping_controls, night_light_controls, power_policy_controls, auto_brightness_controls, docking_controls};
+use crate::event_listener::{self, HyprlandEvent};
+use crate::display_manager::{docking, input_mapping, projection, spacing, wallpaper};
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -12,6 +14,17 @@ thread_local! {
 
 pub type AppState = Arc<Mutex<Vec<()>>>; // Replace Vec<()> with your Monitor type if needed
 
+// The one copy of the settings every tab reads and saves. Read fields at
+// the time they are used, and save through `update_settings` so a save
+// from one tab keeps what the others changed.
+pub type SharedSettings = Rc<RefCell<Settings>>;
+
+pub fn update_settings(settings: &SharedSettings, update: impl FnOnce(&mut Settings)) -> std::io::Result<()> {
+    let mut settings = settings.borrow_mut();
+    update(&mut settings);
+    settings.save()
+}
+
 #[derive(Clone)]
 pub struct AppDevices {
     pub wireless: Rc<RefCell<Vec<crate::wireless::WirelessDisplay>>>,
@@ -19,66 +32,98 @@ pub struct AppDevices {
 }
 
 // --- Helper: Each section returns its main widget ---
-fn build_monitor_section(app_state: AppState) -> gtk::Box {
+fn build_monitor_section(app_state: AppState, settings: SharedSettings) -> gtk::Box {
     let box_ = gtk::Box::new(gtk::Orientation::Vertical, 12);
-    monitor_arrangement::add_monitor_arrangement_section(&box_, app_state);
+    monitor_arrangement::add_monitor_arrangement_section(&box_, app_state, settings.clone());
+    presentation_controls::add_presentation_controls_section(&box_, settings.clone());
+    rotation_controls::add_rotation_controls_section(&box_, settings.clone());
+    night_light_controls::add_night_light_section(&box_, settings.clone());
+    power_policy_controls::add_power_policy_section(&box_, settings.clone());
+    auto_brightness_controls::add_auto_brightness_section(&box_, settings.clone());
+    docking_controls::add_docking_section(&box_, settings);
+    input_mapping_controls::add_input_mapping_section(&box_);
     box_
 }
-fn build_network_section(app_state: AppState, settings: Arc<Settings>, devices: AppDevices) -> gtk::Box {
+fn build_network_section(app_state: AppState, settings: SharedSettings, devices: AppDevices) -> gtk::Box {
     let box_ = gtk::Box::new(gtk::Orientation::Vertical, 12);
     network_settings::add_network_settings_section(&box_, app_state, settings, devices);
     box_
 }
-fn build_screenshot_section(settings: Arc<Settings>) -> gtk::Box {
+fn build_screenshot_section(settings: SharedSettings) -> gtk::Box {
     let box_ = gtk::Box::new(gtk::Orientation::Vertical, 12);
-    screenshot_controls::add_screenshot_controls_section(&box_, settings);
+    screenshot_controls::add_screenshot_controls_section(&box_, settings.clone());
+    gallery_view::add_gallery_section(&box_, settings);
     box_
 }
-fn build_recording_section(settings: Arc<Settings>) -> gtk::Box {
+fn build_recording_section(settings: SharedSettings) -> gtk::Box {
     let box_ = gtk::Box::new(gtk::Orientation::Vertical, 12);
     recording_controls::add_recording_controls_section(&box_, settings);
     box_
 }
 // TODO: Add a real settings section if you have one
-fn build_settings_section(settings: Arc<Settings>) -> gtk::Box {
+fn build_settings_section(settings: SharedSettings) -> gtk::Box {
     let box_ = gtk::Box::new(gtk::Orientation::Vertical, 12);
     let label = gtk::Label::new(Some("Settings"));
     box_.append(&label);
+    let current = settings.borrow().clone();
     // Screenshot directory
     let screenshot_dir_entry = gtk::Entry::new();
-    screenshot_dir_entry.set_text(settings.screenshot_dir.to_str().unwrap_or(""));
+    screenshot_dir_entry.set_text(current.screenshot_dir.to_str().unwrap_or(""));
     box_.append(&gtk::Label::new(Some("Screenshot Directory:")));
     box_.append(&screenshot_dir_entry);
     // Screenshot filename format
     let screenshot_fmt_entry = gtk::Entry::new();
-    screenshot_fmt_entry.set_text(&settings.screenshot_filename_format);
+    screenshot_fmt_entry.set_text(&current.screenshot_filename_format);
     box_.append(&gtk::Label::new(Some("Screenshot Filename Format:")));
     box_.append(&screenshot_fmt_entry);
     // Recorder directory
     let recorder_dir_entry = gtk::Entry::new();
-    recorder_dir_entry.set_text(settings.recorder_dir.to_str().unwrap_or(""));
+    recorder_dir_entry.set_text(current.recorder_dir.to_str().unwrap_or(""));
     box_.append(&gtk::Label::new(Some("Recorder Directory:")));
     box_.append(&recorder_dir_entry);
     // Recorder filename format
     let recorder_fmt_entry = gtk::Entry::new();
-    recorder_fmt_entry.set_text(&settings.recorder_filename_format);
+    recorder_fmt_entry.set_text(&current.recorder_filename_format);
     box_.append(&gtk::Label::new(Some("Recorder Filename Format:")));
     box_.append(&recorder_fmt_entry);
     // Save button
     let save_btn = gtk::Button::with_label("Save Settings");
-    let settings_clone = settings.clone();
     save_btn.connect_clicked(move |_| {
-        let mut new_settings = (*settings_clone).clone();
-        new_settings.screenshot_dir = std::path::PathBuf::from(screenshot_dir_entry.text().to_string());
-        new_settings.screenshot_filename_format = screenshot_fmt_entry.text().to_string();
-        new_settings.recorder_dir = std::path::PathBuf::from(recorder_dir_entry.text().to_string());
-        new_settings.recorder_filename_format = recorder_fmt_entry.text().to_string();
-        let _ = new_settings.save();
+        let _ = update_settings(&settings, |s| {
+            s.screenshot_dir = std::path::PathBuf::from(screenshot_dir_entry.text().to_string());
+            s.screenshot_filename_format = screenshot_fmt_entry.text().to_string();
+            s.recorder_dir = std::path::PathBuf::from(recorder_dir_entry.text().to_string());
+            s.recorder_filename_format = recorder_fmt_entry.text().to_string();
+        });
     });
     box_.append(&save_btn);
     box_
 }
 
+fn reapply_input_bindings() {
+    gtk::glib::MainContext::default().spawn_local(async move {
+        if let Err(e) = input_mapping::reapply_bindings().await {
+            toasts::show_toast(&format!("Failed to re-apply input bindings: {}", e));
+        }
+    });
+}
+
+fn reapply_wallpapers() {
+    gtk::glib::MainContext::default().spawn_local(async move {
+        if let Err(e) = wallpaper::reapply_wallpapers().await {
+            toasts::show_toast(&format!("Failed to restore wallpapers: {}", e));
+        }
+    });
+}
+
+fn reapply_spacing() {
+    gtk::glib::MainContext::default().spawn_local(async move {
+        if let Err(e) = spacing::reapply_spacing().await {
+            toasts::show_toast(&format!("Failed to restore reserved space and gaps: {}", e));
+        }
+    });
+}
+
 pub fn build_ui(app: &adw::Application, app_state: AppState, settings: Arc<Settings>) {
     let window = adw::ApplicationWindow::new(app);
     window.set_title(Some("Hypr-XDisplay Manager"));
@@ -96,8 +141,10 @@ pub fn build_ui(app: &adw::Application, app_state: AppState, settings: Arc<Setti
         android: Rc::new(RefCell::new(vec![])),
     };
 
+    let settings: SharedSettings = Rc::new(RefCell::new((*settings).clone()));
+
     // Add real sections as notebook pages
-    let monitor_box = build_monitor_section(app_state.clone());
+    let monitor_box = build_monitor_section(app_state.clone(), settings.clone());
     let network_box = build_network_section(app_state.clone(), settings.clone(), devices.clone());
     let screenshot_box = build_screenshot_section(settings.clone());
     let recording_box = build_recording_section(settings.clone());
@@ -137,6 +184,46 @@ pub fn build_ui(app: &adw::Application, app_state: AppState, settings: Arc<Setti
         }
     });
 
+    // --- Hyprland events (monitor hotplug) ---
+    let (event_sender, event_receiver) = async_channel::unbounded::<HyprlandEvent>();
+    tokio::spawn(async move {
+        if let Err(e) = event_listener::listen(event_sender).await {
+            tracing::error!("Hyprland event listener failed: {}", e);
+        }
+    });
+    gtk::glib::MainContext::default().spawn_local(async move {
+        if let Err(e) = projection::refresh_known_outputs().await {
+            tracing::warn!("Failed to list outputs: {}", e);
+        }
+        input_mapping::start_device_watch();
+        while let Ok(event) = event_receiver.recv().await {
+            match event {
+                HyprlandEvent::MonitorAdded(name) => {
+                    // Docking goes first and projection waits for it, so a
+                    // remembered choice can't fight the lid rule
+                    if let Err(e) = docking::recheck_now().await {
+                        tracing::warn!("Failed to apply docking rule: {}", e);
+                    }
+                    projection_switcher::on_monitor_added(name).await;
+                    reapply_input_bindings();
+                    reapply_wallpapers();
+                    reapply_spacing();
+                }
+                HyprlandEvent::MonitorRemoved(_) => {
+                    docking::recheck();
+                    if let Err(e) = projection::refresh_known_outputs().await {
+                        tracing::warn!("Failed to list outputs: {}", e);
+                    }
+                }
+                HyprlandEvent::ConfigReloaded => {
+                    reapply_input_bindings();
+                    reapply_spacing();
+                }
+                _ => {}
+            }
+        }
+    });
+
     // --- Toasts and status polling ---
     toasts::setup_toast_overlay(&window, &overlay_box);
     // status_polling::setup_status_polling(&window, app_state.clone());
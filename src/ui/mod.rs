@@ -4,4 +4,20 @@ pub mod network_settings;
 pub mod toasts;
 pub mod status_polling;
 pub mod screenshot_controls;
-pub mod recording_controls; 
\ No newline at end of file
+pub mod recording_controls;
+pub mod projection_switcher;
+pub mod presentation_controls;
+pub mod rotation_controls;
+pub mod input_mapping_controls;
+pub mod night_light_controls;
+pub mod power_policy_controls;
+pub mod auto_brightness_controls;
+pub mod test_patterns;
+pub mod layout_preview;
+pub mod docking_controls;
+pub mod region_selector;
+pub mod window_picker; 
+pub mod gallery_view;
+pub mod annotation_editor;
+pub mod redaction_preview;
+pub mod scan_results;
@@ -1,25 +1,341 @@
-use crate::ui::main_window::AppState;
+use crate::ui::main_window::{update_settings, AppState, SharedSettings};
+use crate::ui::layout_preview;
+use crate::ui::test_patterns;
 use crate::display_manager::monitor_types::Monitor;
 use crate::display_manager::monitor_control;
+use crate::display_manager::layout_history::LayoutHistory;
+use crate::display_manager::auto_brightness;
+use crate::display_manager::monitor_profiles;
+use crate::display_manager::power_policy::ModeOverride;
+use crate::display_manager::spacing::{self, GapOverride, ReservedArea};
+use crate::display_manager::wallpaper::{self, FitMode, Wallpaper};
 use gtk::prelude::*;
 use gtk::{DropTarget, DragSource};
 use gtk::gdk::{DragAction, ContentProvider};
 use glib::value::ToValue;
 use glib::types::Type;
 use libadwaita as adw;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use async_channel;
 
-fn update_grid(
-    grid: &gtk::Box,
-    monitors: &[Monitor],
+// Wallpaper preview, picker and fit mode for one monitor (hyprpaper)
+fn build_wallpaper_controls(monitor: &Monitor) -> gtk::Box {
+    let wallpaper_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+    wallpaper_box.append(&gtk::Label::new(Some("Wallpaper:")));
+    let current = monitor_profiles::get_profile(monitor).wallpaper;
+    let preview = gtk::Picture::new();
+    preview.set_can_shrink(true);
+    preview.set_size_request(160, 90);
+    if let Some(ref wallpaper) = current {
+        preview.set_filename(Some(&wallpaper.path));
+    }
+    wallpaper_box.append(&preview);
+
+    let fit_combo = gtk::ComboBoxText::new();
+    for fit in FitMode::ALL {
+        fit_combo.append(Some(fit.label()), fit.label());
+    }
+    fit_combo.set_active_id(Some(current.as_ref().map(|w| w.fit).unwrap_or_default().label()));
+    let choose_btn = gtk::Button::with_label("Choose…");
+    let row = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+    row.append(&choose_btn);
+    row.append(&fit_combo);
+    wallpaper_box.append(&row);
+
+    let selected_path = std::rc::Rc::new(std::cell::RefCell::new(current.map(|w| w.path)));
+    let apply = {
+        let monitor = monitor.clone();
+        let preview = preview.clone();
+        let fit_combo = fit_combo.clone();
+        let selected_path = selected_path.clone();
+        move || {
+            let Some(path) = selected_path.borrow().clone() else {
+                return;
+            };
+            let fit = FitMode::ALL
+                .into_iter()
+                .find(|f| fit_combo.active_id().as_deref() == Some(f.label()))
+                .unwrap_or_default();
+            preview.set_filename(Some(&path));
+            let monitor = monitor.clone();
+            gtk::glib::MainContext::default().spawn_local(async move {
+                if let Err(e) = wallpaper::set_monitor_wallpaper(&monitor, Wallpaper { path, fit }).await {
+                    crate::ui::toasts::show_toast(&format!("Wallpaper error: {}", e));
+                } else {
+                    crate::ui::toasts::show_toast("Wallpaper set");
+                }
+            });
+        }
+    };
+
+    let apply_for_fit = apply.clone();
+    fit_combo.connect_changed(move |_| apply_for_fit());
+    choose_btn.connect_clicked(move |btn| {
+        let parent = btn.root().and_downcast::<gtk::Window>();
+        let chooser = gtk::FileChooserNative::new(
+            Some("Choose Wallpaper"),
+            parent.as_ref(),
+            gtk::FileChooserAction::Open,
+            Some("Set"),
+            Some("Cancel"),
+        );
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("Images"));
+        filter.add_mime_type("image/*");
+        chooser.add_filter(&filter);
+        let selected_path = selected_path.clone();
+        let apply = apply.clone();
+        // The native dialog is not a widget; keep it alive until it answers
+        let chooser_ref = chooser.clone();
+        chooser.connect_response(move |_, response| {
+            if response == gtk::ResponseType::Accept {
+                if let Some(path) = chooser_ref.file().and_then(|f| f.path()) {
+                    *selected_path.borrow_mut() = Some(path);
+                    apply();
+                }
+            }
+            chooser_ref.destroy();
+        });
+        chooser.show();
+    });
+    wallpaper_box
+}
+
+// Per-monitor mode overrides for battery and AC power
+fn build_power_controls(monitor: &Monitor) -> gtk::Box {
+    let power_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+    let profile = monitor_profiles::get_profile(monitor);
+    let mode_combo = |current: Option<&ModeOverride>| {
+        let combo = gtk::ComboBoxText::new();
+        combo.append(Some(""), "Automatic");
+        for mode in &monitor.available_modes {
+            let mode = mode.trim_end_matches("Hz");
+            combo.append(Some(mode), mode);
+        }
+        let active = current.and_then(|ov| ov.mode.clone()).unwrap_or_default();
+        if !combo.set_active_id(Some(&active)) {
+            combo.set_active_id(Some(""));
+        }
+        combo
+    };
+    let battery_combo = mode_combo(profile.battery_mode.as_ref());
+    let ac_combo = mode_combo(profile.ac_mode.as_ref());
+    let vrr_check = gtk::CheckButton::with_label("VRR on battery");
+    vrr_check.set_active(profile.battery_mode.as_ref().and_then(|ov| ov.vrr).unwrap_or(false));
+    power_box.append(&gtk::Label::new(Some("On battery:")));
+    power_box.append(&battery_combo);
+    power_box.append(&vrr_check);
+    power_box.append(&gtk::Label::new(Some("On AC:")));
+    power_box.append(&ac_combo);
+
+    let save = {
+        let monitor = monitor.clone();
+        let battery_combo = battery_combo.clone();
+        let ac_combo = ac_combo.clone();
+        let vrr_check = vrr_check.clone();
+        move || {
+            let selected = |combo: &gtk::ComboBoxText| {
+                combo.active_id().map(|id| id.to_string()).filter(|id| !id.is_empty())
+            };
+            let battery_mode = selected(&battery_combo);
+            let vrr = vrr_check.is_active();
+            let battery = (battery_mode.is_some() || vrr).then(|| ModeOverride { mode: battery_mode, vrr: Some(vrr) });
+            let ac = selected(&ac_combo).map(|mode| ModeOverride { mode: Some(mode), vrr: None });
+            if let Err(e) = monitor_profiles::update_profile(&monitor, |p| {
+                p.battery_mode = battery;
+                p.ac_mode = ac;
+            }) {
+                crate::ui::toasts::show_toast(&format!("Failed to save monitor profile: {}", e));
+            }
+        }
+    };
+    let save_for_battery = save.clone();
+    battery_combo.connect_changed(move |_| save_for_battery());
+    let save_for_ac = save.clone();
+    ac_combo.connect_changed(move |_| save_for_ac());
+    vrr_check.connect_toggled(move |_| save());
+    power_box
+}
+
+// The arrangement shown in the grid plus its undo history. Every edit
+// goes through `change`, which applies it right away or, when changes are
+// staged, only updates the grid until "Apply Layout" is pressed.
+#[derive(Clone)]
+struct Arrangement {
+    monitors: Arc<Mutex<Vec<Monitor>>>,
+    history: Rc<RefCell<LayoutHistory>>,
+    staged: Rc<Cell<bool>>,
     sender: Arc<async_channel::Sender<Vec<Monitor>>>,
-) {
+    apply_btn: gtk::Button,
+    undo_btn: gtk::Button,
+    redo_btn: gtk::Button,
+}
+
+impl Arrangement {
+    fn current(&self) -> Vec<Monitor> {
+        self.monitors.lock().unwrap().clone()
+    }
+
+    // Replace the displayed layout without touching history (initial load, Reset)
+    fn load(&self, layout: Vec<Monitor>) {
+        *self.monitors.lock().unwrap() = layout.clone();
+        self.apply_btn.remove_css_class("suggested-action");
+        let sender = self.sender.clone();
+        gtk::glib::MainContext::default().spawn_local(async move {
+            sender.send(layout).await.ok();
+        });
+    }
+
+    fn switch_to(&self, layout: Vec<Monitor>) {
+        *self.monitors.lock().unwrap() = layout.clone();
+        self.sync_buttons();
+        if self.staged.get() {
+            self.apply_btn.add_css_class("suggested-action");
+        }
+        let staged = self.staged.get();
+        let sender = self.sender.clone();
+        gtk::glib::MainContext::default().spawn_local(async move {
+            sender.send(layout.clone()).await.ok();
+            if staged {
+                return;
+            }
+            if let Err(e) = monitor_control::apply_monitor_layout(&layout).await {
+                crate::ui::toasts::show_toast(&format!("Monitor layout error: {}", e));
+            } else {
+                crate::ui::toasts::show_toast("Monitor layout applied");
+            }
+        });
+    }
+
+    fn change(&self, layout: Vec<Monitor>) {
+        self.history.borrow_mut().record(self.current());
+        self.switch_to(layout);
+    }
+
+    fn undo(&self) {
+        let previous = self.history.borrow_mut().undo(self.current());
+        match previous {
+            Some(layout) => self.switch_to(layout),
+            None => crate::ui::toasts::show_toast("Nothing to undo"),
+        }
+    }
+
+    fn redo(&self) {
+        let next = self.history.borrow_mut().redo(self.current());
+        match next {
+            Some(layout) => self.switch_to(layout),
+            None => crate::ui::toasts::show_toast("Nothing to redo"),
+        }
+    }
+
+    fn sync_buttons(&self) {
+        let history = self.history.borrow();
+        self.undo_btn.set_sensitive(history.can_undo());
+        self.redo_btn.set_sensitive(history.can_redo());
+    }
+}
+
+// Extra reserved space (addreserved) and gap/border overrides for the
+// workspaces on one monitor
+fn build_spacing_controls(monitor: &Monitor) -> gtk::Box {
+    let spacing_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+    let profile = monitor_profiles::get_profile(monitor);
+    let current = ReservedArea::from_hyprctl(monitor.reserved);
+    let reserved_label = gtk::Label::new(Some(&format!(
+        "Reserved now (incl. bars): top {}, bottom {}, left {}, right {}",
+        current.top, current.bottom, current.left, current.right
+    )));
+    reserved_label.add_css_class("dim-label");
+    spacing_box.append(&gtk::Label::new(Some("Extra reserved space (px):")));
+    spacing_box.append(&reserved_label);
+
+    let spin = |value: i32| {
+        let spin = gtk::SpinButton::with_range(0.0, 1000.0, 1.0);
+        spin.set_value(value as f64);
+        spin
+    };
+    let reserved = profile.reserved.unwrap_or_default();
+    let reserved_spins = [
+        ("Top", spin(reserved.top)),
+        ("Bottom", spin(reserved.bottom)),
+        ("Left", spin(reserved.left)),
+        ("Right", spin(reserved.right)),
+    ];
+    let reserved_grid = gtk::Grid::new();
+    reserved_grid.set_column_spacing(8);
+    reserved_grid.set_row_spacing(4);
+    for (i, (label, spin)) in reserved_spins.iter().enumerate() {
+        reserved_grid.attach(&gtk::Label::new(Some(label)), (i % 2) as i32 * 2, (i / 2) as i32, 1, 1);
+        reserved_grid.attach(spin, (i % 2) as i32 * 2 + 1, (i / 2) as i32, 1, 1);
+    }
+    spacing_box.append(&reserved_grid);
+
+    let gaps = profile.gaps.unwrap_or_default();
+    let gaps_check = gtk::CheckButton::with_label("Override gaps and borders");
+    gaps_check.set_active(profile.gaps.is_some());
+    let gaps_spins = [
+        ("Gaps in", spin(gaps.gaps_in.unwrap_or(0))),
+        ("Gaps out", spin(gaps.gaps_out.unwrap_or(0))),
+        ("Border", spin(gaps.border_size.unwrap_or(0))),
+    ];
+    let gaps_grid = gtk::Grid::new();
+    gaps_grid.set_column_spacing(8);
+    gaps_grid.set_row_spacing(4);
+    gaps_grid.set_sensitive(gaps_check.is_active());
+    for (i, (label, spin)) in gaps_spins.iter().enumerate() {
+        gaps_grid.attach(&gtk::Label::new(Some(label)), 0, i as i32, 1, 1);
+        gaps_grid.attach(spin, 1, i as i32, 1, 1);
+    }
+    spacing_box.append(&gaps_check);
+    spacing_box.append(&gaps_grid);
+
+    let save = {
+        let monitor = monitor.clone();
+        let reserved_spins = reserved_spins.clone();
+        let gaps_spins = gaps_spins.clone();
+        let gaps_check = gaps_check.clone();
+        move || {
+            let value = |spin: &gtk::SpinButton| spin.value() as i32;
+            let reserved = ReservedArea {
+                top: value(&reserved_spins[0].1),
+                bottom: value(&reserved_spins[1].1),
+                left: value(&reserved_spins[2].1),
+                right: value(&reserved_spins[3].1),
+            };
+            let reserved = (reserved != ReservedArea::default()).then_some(reserved);
+            let gaps = gaps_check.is_active().then(|| GapOverride {
+                gaps_in: Some(value(&gaps_spins[0].1)),
+                gaps_out: Some(value(&gaps_spins[1].1)),
+                border_size: Some(value(&gaps_spins[2].1)),
+            });
+            let monitor = monitor.clone();
+            gtk::glib::MainContext::default().spawn_local(async move {
+                if let Err(e) = spacing::set_monitor_spacing(&monitor, reserved, gaps).await {
+                    crate::ui::toasts::show_toast(&format!("Failed to apply reserved space and gaps: {}", e));
+                }
+            });
+        }
+    };
+    for (_, spin) in reserved_spins.iter().chain(gaps_spins.iter()) {
+        let save = save.clone();
+        spin.connect_value_changed(move |_| save());
+    }
+    gaps_check.connect_toggled(move |check| {
+        gaps_grid.set_sensitive(check.is_active());
+        save();
+    });
+    spacing_box
+}
+
+fn update_grid(grid: &gtk::Box, monitors: &[Monitor], arrangement: &Arrangement) {
     while let Some(child) = grid.first_child() {
         grid.remove(&child);
     }
     for (idx, monitor) in monitors.iter().enumerate() {
-        let frame = gtk::Frame::new(Some(&monitor.name));
+        // Numbered to match the Identify overlay
+        let frame = gtk::Frame::new(Some(&format!("{}. {}", idx + 1, monitor.name)));
         frame.set_margin_bottom(8);
         frame.set_margin_top(8);
         frame.set_margin_start(8);
@@ -69,36 +385,44 @@ fn update_grid(
         popover_box.append(&brightness_scale);
         popover_box.append(&gtk::Label::new(Some("Orientation:")));
         popover_box.append(&orientation_combo);
+        popover_box.append(&build_power_controls(monitor));
+        popover_box.append(&build_spacing_controls(monitor));
+        popover_box.append(&build_wallpaper_controls(monitor));
+        let patterns_btn = gtk::Button::with_label("Test Patterns");
+        let output_for_patterns = monitor.name.clone();
+        patterns_btn.connect_clicked(move |_| test_patterns::show_test_patterns(&output_for_patterns));
+        popover_box.append(&patterns_btn);
         popover.set_child(Some(&popover_box));
         popover.set_parent(&popover_btn);
-        let monitor_for_scale = monitor.clone();
-        let sender_for_scale = sender.clone();
+        let arrangement_for_scale = arrangement.clone();
         scale_spin.connect_value_changed(move |spin| {
-            let value = spin.value();
-            let mut updated = monitor_for_scale.clone();
-            updated.scaling = Some(value);
-            let sender = sender_for_scale.clone();
-            gtk::glib::MainContext::default().spawn_local(async move {
-                let result = monitor_control::apply_monitor_layout(&[updated.clone()]).await;
-                let monitors = monitor_control::get_monitors().await.unwrap_or_default();
-                sender.send(monitors).await.ok();
-                if let Err(e) = result {
-                    crate::ui::toasts::show_toast(&format!("Monitor layout error: {}", e));
-                } else {
-                    crate::ui::toasts::show_toast("Monitor layout applied");
-                }
-            });
+            let mut layout = arrangement_for_scale.current();
+            if let Some(updated) = layout.get_mut(idx) {
+                updated.scaling = Some(spin.value());
+            }
+            arrangement_for_scale.change(layout);
         });
         let monitor_for_brightness = monitor.clone();
-        let sender_for_brightness = sender.clone();
+        let arrangement_for_brightness = arrangement.clone();
         brightness_scale.connect_value_changed(move |scale| {
             let value = scale.value();
+            // Shift the auto-brightness curve instead of fighting the user.
+            // The curve drives the panel backlight, so external screens
+            // must not move it.
+            if monitor_for_brightness.is_internal() {
+                auto_brightness::note_manual_brightness(value);
+            }
+            // Not a layout change: no history entry and no grid rebuild
+            if let Some(updated) = arrangement_for_brightness.monitors.lock().unwrap().get_mut(idx) {
+                updated.brightness = Some(value);
+            }
             let monitor = monitor_for_brightness.clone();
-            let sender = sender_for_brightness.clone();
             gtk::glib::MainContext::default().spawn_local(async move {
-                let result = monitor_control::set_brightness(&monitor, value).await;
-                let monitors = monitor_control::get_monitors().await.unwrap_or_default();
-                sender.send(monitors).await.ok();
+                let result = if monitor.is_internal() {
+                    auto_brightness::set_backlight(value).await
+                } else {
+                    monitor_control::set_brightness(&monitor, value).await
+                };
                 if let Err(e) = result {
                     crate::ui::toasts::show_toast(&format!("Brightness error: {}", e));
                 } else {
@@ -106,25 +430,15 @@ fn update_grid(
                 }
             });
         });
-        let monitor_for_orientation = monitor.clone();
-        let sender_for_orientation = sender.clone();
+        let arrangement_for_orientation = arrangement.clone();
         orientation_combo.connect_changed(move |combo| {
-            let orientation = match combo.active() {
-                Some(1) => "Portrait",
-                _ => "Landscape",
-            };
-            let monitor = monitor_for_orientation.clone();
-            let sender = sender_for_orientation.clone();
-            gtk::glib::MainContext::default().spawn_local(async move {
-                let result = monitor_control::set_rotation(&monitor, orientation).await;
-                let monitors = monitor_control::get_monitors().await.unwrap_or_default();
-                sender.send(monitors).await.ok();
-                if let Err(e) = result {
-                    crate::ui::toasts::show_toast(&format!("Rotation error: {}", e));
-                } else {
-                    crate::ui::toasts::show_toast("Rotation set");
-                }
-            });
+            let portrait = combo.active() == Some(1);
+            let mut layout = arrangement_for_orientation.current();
+            if let Some(updated) = layout.get_mut(idx) {
+                updated.orientation = Some(if portrait { "Portrait" } else { "Landscape" }.to_string());
+                updated.transform = if portrait { 1 } else { 0 };
+            }
+            arrangement_for_orientation.change(layout);
         });
         popover_btn.connect_clicked(move |_| {
             popover.popup();
@@ -143,23 +457,16 @@ fn update_grid(
             Some(ContentProvider::for_value(&idx.to_string().to_value()))
         });
 
-        let monitors_vec = monitors.to_vec();
-        let sender_clone = sender.clone();
+        let arrangement_for_drop = arrangement.clone();
         drop_target.connect_drop(move |_, value, _, _| {
-            if let Ok(target_idx) = value.get::<String>() {
-                if let Ok(target_idx) = target_idx.parse::<usize>() {
-                    let mut new_order = monitors_vec.clone();
-                    let monitor = new_order.remove(idx);
-                    new_order.insert(target_idx, monitor);
-                    let sender_clone = sender_clone.clone();
-                    gtk::glib::MainContext::default().spawn_local(async move {
-                        if let Err(e) = monitor_control::apply_monitor_layout(&new_order).await {
-                            crate::ui::toasts::show_toast(&format!("Monitor layout error: {}", e));
-                        } else {
-                            crate::ui::toasts::show_toast("Monitor layout applied");
-                            sender_clone.send(new_order).await.ok();
-                        }
-                    });
+            if let Ok(source_idx) = value.get::<String>() {
+                if let Ok(source_idx) = source_idx.parse::<usize>() {
+                    let mut new_order = arrangement_for_drop.current();
+                    if source_idx != idx && source_idx < new_order.len() && idx < new_order.len() {
+                        let monitor = new_order.remove(source_idx);
+                        new_order.insert(idx, monitor);
+                        arrangement_for_drop.change(new_order);
+                    }
                 }
             }
             true
@@ -169,7 +476,7 @@ fn update_grid(
     }
 }
 
-pub fn add_monitor_arrangement_section(content: &gtk::Box, _app_state: AppState) {
+pub fn add_monitor_arrangement_section(content: &gtk::Box, _app_state: AppState, settings: SharedSettings) {
     let clamp = adw::Clamp::new();
     clamp.set_maximum_size(900);
     clamp.set_margin_top(24);
@@ -180,6 +487,14 @@ pub fn add_monitor_arrangement_section(content: &gtk::Box, _app_state: AppState)
     let vbox = gtk::Box::new(gtk::Orientation::Vertical, 16);
     let header = adw::HeaderBar::new();
     header.set_title_widget(Some(&gtk::Label::new(Some("Monitor Arrangement"))));
+    let undo_btn = gtk::Button::from_icon_name("edit-undo-symbolic");
+    undo_btn.set_tooltip_text(Some("Undo (Ctrl+Z)"));
+    undo_btn.set_sensitive(false);
+    let redo_btn = gtk::Button::from_icon_name("edit-redo-symbolic");
+    redo_btn.set_tooltip_text(Some("Redo (Ctrl+Shift+Z)"));
+    redo_btn.set_sensitive(false);
+    header.pack_start(&undo_btn);
+    header.pack_start(&redo_btn);
     vbox.append(&header);
 
     // Monitor grid
@@ -190,87 +505,110 @@ pub fn add_monitor_arrangement_section(content: &gtk::Box, _app_state: AppState)
     let btn_box = gtk::Box::new(gtk::Orientation::Horizontal, 12);
     let apply_btn = gtk::Button::with_label("Apply Layout");
     let reset_btn = gtk::Button::with_label("Reset");
+    let identify_btn = gtk::Button::with_label("Identify");
+    let stage_check = gtk::CheckButton::with_label("Stage changes until applied");
+    stage_check.set_active(settings.borrow().stage_layout_changes.unwrap_or(false));
     btn_box.append(&apply_btn);
     btn_box.append(&reset_btn);
+    btn_box.append(&identify_btn);
+    btn_box.append(&stage_check);
     vbox.append(&btn_box);
 
     clamp.set_child(Some(&vbox));
     content.append(&clamp);
 
-    // State for monitors (thread-safe)
-    let monitors_state: Arc<Mutex<Vec<Monitor>>> = Arc::new(Mutex::new(vec![]));
-    let grid_clone = grid.clone();
-
     // Use async-channel for thread-to-main communication
     let (sender, receiver) = async_channel::unbounded::<Vec<Monitor>>();
-    let sender = Arc::new(sender);
-    let sender_for_grid = sender.clone();
+    let arrangement = Arrangement {
+        monitors: Arc::new(Mutex::new(vec![])),
+        history: Rc::new(RefCell::new(LayoutHistory::default())),
+        staged: Rc::new(Cell::new(stage_check.is_active())),
+        sender: Arc::new(sender),
+        apply_btn: apply_btn.clone(),
+        undo_btn: undo_btn.clone(),
+        redo_btn: redo_btn.clone(),
+    };
+    let grid_clone = grid.clone();
+    let arrangement_for_grid = arrangement.clone();
     gtk::glib::MainContext::default().spawn_local(async move {
         while let Ok(monitors) = receiver.recv().await {
-            update_grid(&grid_clone, &monitors, sender_for_grid.clone());
+            update_grid(&grid_clone, &monitors, &arrangement_for_grid);
         }
     });
 
     // Initial load: async, non-blocking
-    let monitors_state_clone = Arc::clone(&monitors_state);
-    let sender_clone2 = sender.clone();
+    let arrangement_for_load = arrangement.clone();
     gtk::glib::MainContext::default().spawn_local(async move {
         match monitor_control::get_monitors().await {
-            Ok(monitors) => {
-                {
-                    let mut guard = monitors_state_clone.lock().unwrap();
-                    guard.clear();
-                    guard.extend(monitors.clone());
-                }
-                sender_clone2.send(monitors).await.expect("Failed to send monitors");
-            }
+            Ok(monitors) => arrangement_for_load.load(monitors),
             Err(e) => {
                 crate::ui::toasts::show_toast(&format!("Failed to load monitors: {}", e));
             }
         }
     });
 
-    // Apply button (async)
-    let monitors_state_apply = Arc::clone(&monitors_state);
-    apply_btn.connect_clicked(move |_| {
-        let monitors = {
-            let guard = monitors_state_apply.lock().unwrap();
-            guard.clone()
-        };
+    // Undo/redo, from the header buttons or Ctrl+Z / Ctrl+Shift+Z
+    let arrangement_for_undo = arrangement.clone();
+    undo_btn.connect_clicked(move |_| arrangement_for_undo.undo());
+    let arrangement_for_redo = arrangement.clone();
+    redo_btn.connect_clicked(move |_| arrangement_for_redo.redo());
+    // Local: only while focus is on the Monitors page, so Ctrl+Z on
+    // another tab never undoes (and possibly applies) a layout
+    let shortcuts = gtk::ShortcutController::new();
+    shortcuts.set_scope(gtk::ShortcutScope::Local);
+    for (trigger, redo) in [("<Control>z", false), ("<Control><Shift>z", true), ("<Control>y", true)] {
+        let arrangement = arrangement.clone();
+        shortcuts.add_shortcut(gtk::Shortcut::new(
+            gtk::ShortcutTrigger::parse_string(trigger),
+            Some(gtk::CallbackAction::new(move |_, _| {
+                if redo {
+                    arrangement.redo();
+                } else {
+                    arrangement.undo();
+                }
+                gtk::glib::Propagation::Stop
+            })),
+        ));
+    }
+    vbox.add_controller(shortcuts);
+
+    // Staging only changes what edits do from now on; already staged
+    // changes stay pending until applied or reset
+    let staged_for_check = arrangement.staged.clone();
+    stage_check.connect_toggled(move |check| {
+        staged_for_check.set(check.is_active());
+        if let Err(e) = update_settings(&settings, |s| s.stage_layout_changes = Some(check.is_active())) {
+            crate::ui::toasts::show_toast(&format!("Failed to save settings: {}", e));
+        }
+    });
+
+    // Apply button: preview the changes, then apply on confirmation
+    let arrangement_for_apply = arrangement.clone();
+    apply_btn.connect_clicked(move |btn| {
+        btn.remove_css_class("suggested-action");
+        layout_preview::show_layout_preview(arrangement_for_apply.current());
+    });
+
+    // Identify button: label every output with its index and name
+    identify_btn.connect_clicked(move |_| {
         gtk::glib::MainContext::default().spawn_local(async move {
-            let _ = monitor_control::apply_monitor_layout(&monitors).await;
+            match monitor_control::get_monitors().await {
+                Ok(monitors) => test_patterns::identify_monitors(&monitors),
+                Err(e) => crate::ui::toasts::show_toast(&format!("Failed to load monitors: {}", e)),
+            }
         });
     });
 
-    // Reset button (async)
-    let monitors_state_reset = Arc::clone(&monitors_state);
-    let grid_reset = grid.clone();
-    let (reset_sender, reset_receiver) = async_channel::unbounded::<Vec<Monitor>>();
-    let reset_sender = Arc::new(reset_sender);
-    let reset_sender_for_async = reset_sender.clone();
-    let reset_sender_for_btn = reset_sender.clone();
-    gtk::glib::MainContext::default().spawn_local(async move {
-        while let Ok(monitors) = reset_receiver.recv().await {
-            update_grid(&grid_reset, &monitors, reset_sender_for_async.clone());
-        }
-    });
+    // Reset button: drop staged changes and re-read the live layout
     reset_btn.connect_clicked(move |_| {
-        let monitors_state_reset = Arc::clone(&monitors_state_reset);
-        let reset_sender_clone2 = reset_sender_for_btn.clone();
+        let arrangement = arrangement.clone();
         gtk::glib::MainContext::default().spawn_local(async move {
             match monitor_control::get_monitors().await {
-                Ok(monitors) => {
-                    {
-                        let mut guard = monitors_state_reset.lock().unwrap();
-                        guard.clear();
-                        guard.extend(monitors.clone());
-                    }
-                    reset_sender_clone2.send(monitors).await.expect("Failed to send monitors");
-                }
+                Ok(monitors) => arrangement.load(monitors),
                 Err(e) => {
                     crate::ui::toasts::show_toast(&format!("Failed to reset monitors: {}", e));
                 }
             }
         });
     });
-}
\ No newline at end of file
+}
@@ -1,6 +1,4 @@
-use std::sync::Arc;
-use crate::settings::Settings;
-use crate::ui::main_window::{AppState, AppDevices};
+use crate::ui::main_window::{AppState, AppDevices, SharedSettings};
 use gtk::prelude::*;
 use libadwaita as adw;
 use crate::network_display::protocol_manager;
@@ -16,7 +14,7 @@ fn clear_list_box(list_box: &gtk::ListBox) {
     }
 }
 
-pub fn add_network_settings_section(content: &gtk::Box, _app_state: AppState, _settings: Arc<Settings>, _devices: AppDevices) {
+pub fn add_network_settings_section(content: &gtk::Box, _app_state: AppState, _settings: SharedSettings, _devices: AppDevices) {
     let clamp = adw::Clamp::new();
     clamp.set_maximum_size(900);
     clamp.set_margin_top(24);
@@ -0,0 +1,135 @@
+use crate::display_manager::night_light::{self, NightLightMode};
+use crate::ui::main_window::{update_settings, SharedSettings};
+use gtk::prelude::*;
+use libadwaita as adw;
+
+pub fn add_night_light_section(content: &gtk::Box, settings: SharedSettings) {
+    let initial = settings.borrow().clone();
+    let clamp = adw::Clamp::new();
+    clamp.set_maximum_size(900);
+    clamp.set_margin_start(24);
+    clamp.set_margin_end(24);
+
+    let frame = gtk::Frame::new(Some("Night Light"));
+    let vbox = gtk::Box::new(gtk::Orientation::Vertical, 8);
+    vbox.set_margin_top(8);
+    vbox.set_margin_bottom(8);
+    vbox.set_margin_start(8);
+    vbox.set_margin_end(8);
+
+    let mode_combo = gtk::ComboBoxText::new();
+    for mode in ["Off", "Manual", "Schedule", "Fixed"] {
+        mode_combo.append(Some(mode), mode);
+    }
+    mode_combo.set_active_id(Some(initial.night_light_mode.as_deref().unwrap_or("Off")));
+    let temperature_scale = gtk::Scale::with_range(
+        gtk::Orientation::Horizontal,
+        1000.0,
+        night_light::IDENTITY_TEMPERATURE as f64,
+        100.0,
+    );
+    temperature_scale.set_value(initial.night_light_temperature.unwrap_or(4000) as f64);
+    temperature_scale.set_draw_value(true);
+    temperature_scale.set_hexpand(true);
+    let latitude_entry = gtk::Entry::new();
+    latitude_entry.set_placeholder_text(Some("Latitude (e.g. -1.29)"));
+    let longitude_entry = gtk::Entry::new();
+    longitude_entry.set_placeholder_text(Some("Longitude (e.g. 36.82)"));
+    if let Some(lat) = initial.night_light_latitude {
+        latitude_entry.set_text(&lat.to_string());
+    }
+    if let Some(lon) = initial.night_light_longitude {
+        longitude_entry.set_text(&lon.to_string());
+    }
+    let schedule_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    schedule_box.append(&latitude_entry);
+    schedule_box.append(&longitude_entry);
+    schedule_box.set_visible(initial.night_light_mode.as_deref() == Some("Schedule"));
+    let start_entry = gtk::Entry::new();
+    start_entry.set_placeholder_text(Some("Night from (22:00)"));
+    start_entry.set_text(initial.night_light_start.as_deref().unwrap_or(""));
+    let end_entry = gtk::Entry::new();
+    end_entry.set_placeholder_text(Some("Until (06:00)"));
+    end_entry.set_text(initial.night_light_end.as_deref().unwrap_or(""));
+    let fixed_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    fixed_box.append(&start_entry);
+    fixed_box.append(&end_entry);
+    fixed_box.set_visible(initial.night_light_mode.as_deref() == Some("Fixed"));
+
+    let mode_row = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    mode_row.append(&gtk::Label::new(Some("Mode:")));
+    mode_row.append(&mode_combo);
+    let temperature_row = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    temperature_row.append(&gtk::Label::new(Some("Temperature (K):")));
+    temperature_row.append(&temperature_scale);
+    vbox.append(&mode_row);
+    vbox.append(&temperature_row);
+    vbox.append(&schedule_box);
+    vbox.append(&fixed_box);
+    let save_btn = gtk::Button::with_label("Save Night Light");
+    save_btn.set_halign(gtk::Align::Start);
+    vbox.append(&save_btn);
+    frame.set_child(Some(&vbox));
+    clamp.set_child(Some(&frame));
+    content.append(&clamp);
+
+    night_light::set_mode(NightLightMode::from_settings(&initial));
+    night_light::start_night_light();
+
+    // Build settings from the current widget values
+    let read_widgets = {
+        let settings = settings.clone();
+        let mode_combo = mode_combo.clone();
+        let temperature_scale = temperature_scale.clone();
+        let latitude_entry = latitude_entry.clone();
+        let longitude_entry = longitude_entry.clone();
+        let start_entry = start_entry.clone();
+        let end_entry = end_entry.clone();
+        move || {
+            let mut new_settings = settings.borrow().clone();
+            new_settings.night_light_mode = mode_combo.active_id().map(|id| id.to_string());
+            new_settings.night_light_temperature = Some(temperature_scale.value().round() as u32);
+            new_settings.night_light_latitude = latitude_entry.text().trim().parse().ok();
+            new_settings.night_light_longitude = longitude_entry.text().trim().parse().ok();
+            new_settings.night_light_start = Some(start_entry.text().trim().to_string());
+            new_settings.night_light_end = Some(end_entry.text().trim().to_string());
+            new_settings
+        }
+    };
+
+    // Live preview while adjusting; Save persists
+    let read_for_mode = read_widgets.clone();
+    let schedule_box_clone = schedule_box.clone();
+    let fixed_box_clone = fixed_box.clone();
+    mode_combo.connect_changed(move |combo| {
+        schedule_box_clone.set_visible(combo.active_id().as_deref() == Some("Schedule"));
+        fixed_box_clone.set_visible(combo.active_id().as_deref() == Some("Fixed"));
+        night_light::set_mode(NightLightMode::from_settings(&read_for_mode()));
+    });
+    let read_for_scale = read_widgets.clone();
+    temperature_scale.connect_value_changed(move |_| {
+        night_light::set_mode(NightLightMode::from_settings(&read_for_scale()));
+    });
+    save_btn.connect_clicked(move |_| {
+        let new_settings = read_widgets();
+        if new_settings.night_light_mode.as_deref() == Some("Schedule")
+            && (new_settings.night_light_latitude.is_none() || new_settings.night_light_longitude.is_none())
+        {
+            crate::ui::toasts::show_toast("Enter latitude and longitude for the schedule");
+            return;
+        }
+        let valid_time = |time: &Option<String>| time.as_deref().and_then(night_light::parse_time).is_some();
+        if new_settings.night_light_mode.as_deref() == Some("Fixed")
+            && !(valid_time(&new_settings.night_light_start) && valid_time(&new_settings.night_light_end))
+        {
+            crate::ui::toasts::show_toast("Enter start and end times as HH:MM");
+            return;
+        }
+        night_light::set_mode(NightLightMode::from_settings(&new_settings));
+        if let Err(e) = update_settings(&settings, |s| *s = new_settings) {
+            crate::ui::toasts::show_toast(&format!("Failed to save settings: {}", e));
+        } else {
+            crate::ui::toasts::show_toast("Night light saved");
+        }
+    });
+}
@@ -0,0 +1,64 @@
+use crate::display_manager::power_policy;
+use crate::ui::main_window::{update_settings, SharedSettings};
+use gtk::prelude::*;
+use libadwaita as adw;
+use std::time::Duration;
+
+pub fn add_power_policy_section(content: &gtk::Box, settings: SharedSettings) {
+    let (enabled, delay_secs) = {
+        let settings = settings.borrow();
+        (settings.power_policy.unwrap_or(false), settings.power_policy_delay_secs.unwrap_or(10))
+    };
+    let clamp = adw::Clamp::new();
+    clamp.set_maximum_size(900);
+    clamp.set_margin_start(24);
+    clamp.set_margin_end(24);
+
+    let frame = gtk::Frame::new(Some("Power-Aware Refresh Rate"));
+    let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    hbox.set_margin_top(8);
+    hbox.set_margin_bottom(8);
+    hbox.set_margin_start(8);
+    hbox.set_margin_end(8);
+    let enable_switch = gtk::Switch::new();
+    enable_switch.set_valign(gtk::Align::Center);
+    enable_switch.set_active(enabled);
+    let delay_spin = gtk::SpinButton::with_range(0.0, 300.0, 1.0);
+    delay_spin.set_value(delay_secs as f64);
+    delay_spin.set_tooltip_text(Some("Seconds a power change must last before modes switch"));
+    hbox.append(&gtk::Label::new(Some("Switch modes on battery/AC")));
+    hbox.append(&enable_switch);
+    hbox.append(&gtk::Label::new(Some("Delay (s):")));
+    hbox.append(&delay_spin);
+    frame.set_child(Some(&hbox));
+    clamp.set_child(Some(&frame));
+    content.append(&clamp);
+
+    if enabled {
+        power_policy::start_power_policy(Duration::from_secs(delay_secs));
+    }
+
+    let save = move |enabled: bool, delay_secs: u64| {
+        // Restart so a new delay takes effect
+        power_policy::stop_power_policy();
+        if enabled {
+            power_policy::start_power_policy(Duration::from_secs(delay_secs));
+        }
+        let saved = update_settings(&settings, |s| {
+            s.power_policy = Some(enabled);
+            s.power_policy_delay_secs = Some(delay_secs);
+        });
+        if let Err(e) = saved {
+            crate::ui::toasts::show_toast(&format!("Failed to save settings: {}", e));
+        }
+    };
+    let save_for_switch = save.clone();
+    let delay_for_switch = delay_spin.clone();
+    enable_switch.connect_active_notify(move |switch| {
+        save_for_switch(switch.is_active(), delay_for_switch.value() as u64);
+    });
+    let switch_for_delay = enable_switch.clone();
+    delay_spin.connect_value_changed(move |spin| {
+        save(switch_for_delay.is_active(), spin.value() as u64);
+    });
+}
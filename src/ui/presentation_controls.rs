@@ -0,0 +1,78 @@
+use crate::display_manager::monitor_control;
+use crate::display_manager::projection;
+use crate::presentation::{self, PresentationOptions};
+use crate::ui::main_window::SharedSettings;
+use gtk::glib;
+use gtk::prelude::*;
+use libadwaita as adw;
+
+pub fn add_presentation_controls_section(content: &gtk::Box, settings: SharedSettings) {
+    let clamp = adw::Clamp::new();
+    clamp.set_maximum_size(900);
+    clamp.set_margin_start(24);
+    clamp.set_margin_end(24);
+
+    let frame = gtk::Frame::new(Some("Presentation Mode"));
+    let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    hbox.set_margin_top(8);
+    hbox.set_margin_bottom(8);
+    hbox.set_margin_start(8);
+    hbox.set_margin_end(8);
+    let projector_combo = gtk::ComboBoxText::new();
+    projector_combo.set_tooltip_text(Some("Projector output"));
+    let record_check = gtk::CheckButton::with_label("Record");
+    let toggle_btn = gtk::Button::with_label("Start Presentation");
+    toggle_btn.add_css_class("suggested-action");
+    hbox.append(&gtk::Label::new(Some("Projector:")));
+    hbox.append(&projector_combo);
+    hbox.append(&record_check);
+    hbox.append(&toggle_btn);
+    frame.set_child(Some(&hbox));
+    clamp.set_child(Some(&frame));
+    content.append(&clamp);
+
+    // Offer every output except the built-in panel, newest first
+    let projector_combo_load = projector_combo.clone();
+    glib::MainContext::default().spawn_local(async move {
+        if let Ok(mut monitors) = monitor_control::get_monitors().await {
+            let primary = projection::pick_primary(&monitors, "").map(|m| m.name.clone());
+            monitors.sort_by_key(|m| std::cmp::Reverse(m.id));
+            for monitor in monitors.iter().filter(|m| Some(&m.name) != primary.as_ref()) {
+                projector_combo_load.append(Some(&monitor.name), &monitor.name);
+            }
+            projector_combo_load.set_active(Some(0));
+        }
+    });
+
+    toggle_btn.connect_clicked(move |btn| {
+        let settings = settings.borrow().clone();
+        let btn = btn.clone();
+        let options = PresentationOptions {
+            projector: projector_combo.active_id().map(|id| id.to_string()).unwrap_or_default(),
+            start_recording: record_check.is_active(),
+        };
+        btn.set_sensitive(false);
+        glib::MainContext::default().spawn_local(async move {
+            if presentation::is_active().await {
+                let _ = presentation::disable_presentation().await;
+                crate::ui::toasts::show_toast("Presentation mode off");
+            } else if options.projector.is_empty() {
+                crate::ui::toasts::show_toast("Connect a projector first");
+            } else if let Err(e) = presentation::enable_presentation(&settings, &options).await {
+                crate::ui::toasts::show_toast(&format!("Presentation mode error: {}", e));
+            } else {
+                crate::ui::toasts::show_toast("Presentation mode on");
+            }
+            if presentation::is_active().await {
+                btn.set_label("Stop Presentation");
+                btn.remove_css_class("suggested-action");
+                btn.add_css_class("destructive-action");
+            } else {
+                btn.set_label("Start Presentation");
+                btn.remove_css_class("destructive-action");
+                btn.add_css_class("suggested-action");
+            }
+            btn.set_sensitive(true);
+        });
+    });
+}
@@ -0,0 +1,155 @@
+use crate::display_manager::monitor_control;
+use crate::display_manager::monitor_profiles;
+use crate::display_manager::monitor_types::Monitor;
+use crate::display_manager::projection::{self, ProjectionMode};
+use crate::ui::main_window::MAIN_WINDOW;
+use gtk::gdk;
+use gtk::glib;
+use libadwaita as adw;
+use adw::prelude::*;
+
+// Hotplug entry point: apply the remembered choice for this monitor, or
+// ask the user if we have never seen it before. Call after docking has
+// been re-checked so the panel is where the lid rule wants it.
+pub async fn on_monitor_added(name: String) {
+    let monitors = match monitor_control::get_all_monitors().await {
+        Ok(monitors) => monitors,
+        Err(e) => {
+            crate::ui::toasts::show_toast(&format!("Failed to load monitors: {}", e));
+            return;
+        }
+    };
+    let known = projection::remember_outputs(&monitors);
+    // The panel coming back, or an output we only turned back on
+    if known.is_some_and(|known| known.contains(&name)) {
+        return;
+    }
+    let Some(second) = monitors.iter().find(|m| m.name == name && !m.is_internal()).cloned() else {
+        return;
+    };
+    // Nothing to choose when the new output is the only one
+    let Some(primary) = projection::pick_primary(&monitors, &name).cloned() else {
+        return;
+    };
+    match monitor_profiles::get_profile(&second).projection {
+        Some(mode) => {
+            if let Err(e) = projection::apply_projection(mode, &primary, &second).await {
+                crate::ui::toasts::show_toast(&format!("Projection error: {}", e));
+            } else {
+                crate::ui::toasts::show_toast(&format!("{}: {}", second.name, mode.label()));
+            }
+        }
+        None => present_switcher(primary, second),
+    }
+}
+
+// Keybinding entry point (`hypr-xdisplay --project`): target the most
+// recently connected external output.
+pub fn show_projection_switcher() {
+    glib::MainContext::default().spawn_local(async move {
+        let monitors = match monitor_control::get_all_monitors().await {
+            Ok(monitors) => monitors,
+            Err(e) => {
+                crate::ui::toasts::show_toast(&format!("Failed to load monitors: {}", e));
+                return;
+            }
+        };
+        let Some(primary) = projection::pick_primary(&monitors, "").cloned() else {
+            crate::ui::toasts::show_toast("No monitors found");
+            return;
+        };
+        let second = monitors
+            .iter()
+            .filter(|m| m.name != primary.name)
+            .max_by_key(|m| m.id)
+            .cloned();
+        match second {
+            Some(second) => present_switcher(primary, second),
+            None => crate::ui::toasts::show_toast("No second screen connected"),
+        }
+    });
+}
+
+fn present_switcher(primary: Monitor, second: Monitor) {
+    let window = adw::Window::new();
+    window.set_title(Some("Project"));
+    window.set_modal(true);
+    window.set_resizable(false);
+    MAIN_WINDOW.with(|w| {
+        if let Some(main_window) = w.borrow().as_ref() {
+            window.set_transient_for(Some(main_window));
+        }
+    });
+
+    let vbox = gtk::Box::new(gtk::Orientation::Vertical, 16);
+    vbox.set_margin_top(24);
+    vbox.set_margin_bottom(24);
+    vbox.set_margin_start(24);
+    vbox.set_margin_end(24);
+    let title = if second.description.is_empty() { second.name.clone() } else { second.description.clone() };
+    let header = gtk::Label::new(Some(&format!("Project to {}", title)));
+    header.add_css_class("title-2");
+    vbox.append(&header);
+
+    let current = monitor_profiles::get_profile(&second).projection;
+    let remember_check = gtk::CheckButton::with_label("Remember for this monitor");
+    remember_check.set_active(true);
+
+    let mode_box = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    mode_box.set_homogeneous(true);
+    for mode in ProjectionMode::ALL {
+        let btn_box = gtk::Box::new(gtk::Orientation::Vertical, 8);
+        let icon = gtk::Image::from_icon_name(mode.icon_name());
+        icon.set_pixel_size(48);
+        btn_box.append(&icon);
+        btn_box.append(&gtk::Label::new(Some(mode.label())));
+        let btn = gtk::Button::new();
+        btn.set_child(Some(&btn_box));
+        btn.set_width_request(140);
+        btn.set_height_request(120);
+        if current == Some(mode) {
+            btn.add_css_class("suggested-action");
+        }
+        let primary = primary.clone();
+        let second = second.clone();
+        let remember_check = remember_check.clone();
+        let window_clone = window.clone();
+        btn.connect_clicked(move |_| {
+            let primary = primary.clone();
+            let second = second.clone();
+            let remember = remember_check.is_active();
+            window_clone.close();
+            glib::MainContext::default().spawn_local(async move {
+                if let Err(e) = projection::apply_projection(mode, &primary, &second).await {
+                    crate::ui::toasts::show_toast(&format!("Projection error: {}", e));
+                    return;
+                }
+                if remember {
+                    if let Err(e) = monitor_profiles::update_profile(&second, |p| p.projection = Some(mode)) {
+                        crate::ui::toasts::show_toast(&format!("Failed to save monitor profile: {}", e));
+                    }
+                }
+                crate::ui::toasts::show_toast(&format!("{}: {}", second.name, mode.label()));
+            });
+        });
+        mode_box.append(&btn);
+    }
+    vbox.append(&mode_box);
+    vbox.append(&remember_check);
+    window.set_content(Some(&vbox));
+
+    // Escape dismisses the switcher without changing anything
+    let key_controller = gtk::EventControllerKey::new();
+    let window_for_key = window.clone();
+    key_controller.connect_key_pressed(move |_, keyval, _, _| {
+        if keyval == gdk::Key::Escape {
+            window_for_key.close();
+            glib::Propagation::Stop
+        } else {
+            glib::Propagation::Proceed
+        }
+    });
+    window.add_controller(key_controller);
+
+    window.present();
+}
@@ -1,12 +1,12 @@
 use gtk::prelude::*;
-use std::sync::Arc;
-use crate::settings::Settings;
+use crate::ui::main_window::SharedSettings;
 use crate::recorder;
+use crate::uploader;
 use libadwaita as adw;
 use gtk::glib;
 use std::sync::mpsc;
 
-pub fn add_recording_controls_section(content: &gtk::Box, settings: Arc<Settings>) {
+pub fn add_recording_controls_section(content: &gtk::Box, settings: SharedSettings) {
     let clamp = adw::Clamp::new();
     clamp.set_maximum_size(700);
     clamp.set_margin_top(24);
@@ -28,11 +28,14 @@ pub fn add_recording_controls_section(content: &gtk::Box, settings: Arc<Settings
     start_btn.set_tooltip_text(Some("Start Recording"));
     let stop_btn = gtk::Button::from_icon_name("media-playback-stop-symbolic");
     stop_btn.set_tooltip_text(Some("Stop Recording"));
+    let upload_btn = gtk::Button::from_icon_name("send-to-symbolic");
+    upload_btn.set_tooltip_text(Some("Upload last recording and copy the link"));
     let status_icon = gtk::Image::from_icon_name("media-record-symbolic");
     let status_label = gtk::Label::new(Some("Idle"));
     let timer_label = gtk::Label::new(Some("00:00:00"));
     quick_box.append(&start_btn);
     quick_box.append(&stop_btn);
+    quick_box.append(&upload_btn);
     quick_box.append(&status_icon);
     quick_box.append(&status_label);
     quick_box.append(&timer_label);
@@ -76,17 +79,17 @@ pub fn add_recording_controls_section(content: &gtk::Box, settings: Arc<Settings
     let advanced_entry = gtk::Entry::new();
     advanced_entry.set_placeholder_text(Some("Extra encoder args (optional)"));
     let framerate_entry = gtk::SpinButton::with_range(1.0, 240.0, 1.0);
-    framerate_entry.set_value(settings.recorder_framerate.unwrap_or(30) as f64);
+    framerate_entry.set_value(settings.borrow().recorder_framerate.unwrap_or(30) as f64);
     let resolution_entry = gtk::Entry::new();
     resolution_entry.set_placeholder_text(Some("e.g. 1920x1080"));
-    if let Some(ref res) = settings.recorder_resolution {
+    if let Some(ref res) = settings.borrow().recorder_resolution {
         resolution_entry.set_text(res);
     }
     let hwaccel_switch = gtk::Switch::new();
-    hwaccel_switch.set_active(settings.recorder_hardware_accel.unwrap_or(false));
+    hwaccel_switch.set_active(settings.borrow().recorder_hardware_accel.unwrap_or(false));
     let audio_device_entry = gtk::Entry::new();
     audio_device_entry.set_placeholder_text(Some("Audio device (optional)"));
-    if let Some(ref dev) = settings.recorder_audio_device {
+    if let Some(ref dev) = settings.borrow().recorder_audio_device {
         audio_device_entry.set_text(dev);
     }
     adv_box.append(&gtk::Label::new(Some("Format:")));
@@ -121,7 +124,7 @@ pub fn add_recording_controls_section(content: &gtk::Box, settings: Arc<Settings
         let timer_label = timer_label_clone.clone();
         move |_| {
             let (tx, rx) = mpsc::channel();
-            let settings = settings.clone();
+            let settings = settings.borrow().clone();
             std::thread::spawn(move || {
                 let runtime = tokio::runtime::Runtime::new().unwrap();
                 let result = runtime.block_on(recorder::start_recording(&settings));
@@ -176,4 +179,23 @@ pub fn add_recording_controls_section(content: &gtk::Box, settings: Arc<Settings
             });
         }
     });
-} 
\ No newline at end of file
+
+    upload_btn.connect_clicked(move |_| {
+        let settings = settings.borrow().clone();
+        glib::MainContext::default().spawn_local(async move {
+            if recorder::is_recording().await {
+                crate::ui::toasts::show_toast("Stop the recording before uploading it");
+                return;
+            }
+            let Some(path) = recorder::last_recording() else {
+                crate::ui::toasts::show_toast("Nothing recorded yet");
+                return;
+            };
+            crate::ui::toasts::show_toast("Uploading…");
+            match uploader::upload_and_copy(&settings, &path).await {
+                Ok(link) => crate::ui::toasts::show_toast(&format!("Link copied: {}", link)),
+                Err(e) => crate::ui::toasts::show_toast(&format!("Upload failed: {}", e)),
+            }
+        });
+    });
+}
@@ -0,0 +1,141 @@
+use crate::redaction::Redaction;
+use crate::ui::main_window::MAIN_WINDOW;
+use futures::channel::oneshot;
+use gtk::gdk;
+use gtk::gdk_pixbuf::Pixbuf;
+use gtk::glib;
+use libadwaita as adw;
+use adw::prelude::*;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+// Widest a redacted image is shown, in layout pixels
+const PREVIEW_WIDTH: f64 = 640.0;
+
+// Enough of a match to recognise it without repeating the secret
+fn masked(text: &str) -> String {
+    let count = text.chars().count();
+    let shown: String = text.chars().take(3.min(count / 4)).collect();
+    format!("{}… ({} characters)", shown, count)
+}
+
+// The redacted image with each painted area outlined
+fn redacted_image(path: &Path, redactions: &[&Redaction]) -> gtk::Widget {
+    let Ok(pixbuf) = Pixbuf::from_file(path) else {
+        return gtk::Label::new(Some(&format!("Failed to load {}", path.display()))).upcast();
+    };
+    let scale = (PREVIEW_WIDTH / pixbuf.width() as f64).min(1.0);
+    let area = gtk::DrawingArea::new();
+    area.set_content_width((pixbuf.width() as f64 * scale).round() as i32);
+    area.set_content_height((pixbuf.height() as f64 * scale).round() as i32);
+    area.set_halign(gtk::Align::Center);
+    let areas: Vec<_> = redactions.iter().map(|r| r.area).collect();
+    area.set_draw_func(move |_, cr, _, _| {
+        cr.scale(scale, scale);
+        cr.set_source_pixbuf(&pixbuf, 0.0, 0.0);
+        let _ = cr.paint();
+        cr.set_source_rgb(0.95, 0.2, 0.2);
+        cr.set_line_width(2.0 / scale);
+        for area in &areas {
+            cr.rectangle(area.x as f64, area.y as f64, area.width as f64, area.height as f64);
+        }
+        let _ = cr.stroke();
+    });
+    area.upcast()
+}
+
+// Show what was redacted before the capture is delivered. Returns whether
+// to go ahead; false discards the capture.
+pub async fn confirm(files: &[PathBuf], redactions: &[Redaction]) -> bool {
+    let window = adw::Window::new();
+    window.set_title(Some("Review Redactions"));
+    window.set_modal(true);
+    window.set_default_size(720, 640);
+    MAIN_WINDOW.with(|w| {
+        if let Some(main_window) = w.borrow().as_ref() {
+            window.set_transient_for(Some(main_window));
+        }
+    });
+
+    let (sender, receiver) = oneshot::channel();
+    let sender = Rc::new(RefCell::new(Some(sender)));
+    let choose = {
+        let window = window.clone();
+        let sender = sender.clone();
+        move |proceed: bool| {
+            if let Some(sender) = sender.borrow_mut().take() {
+                let _ = sender.send(proceed);
+            }
+            window.close();
+        }
+    };
+
+    let header = adw::HeaderBar::new();
+    let title = format!("{} redacted", redactions.len());
+    header.set_title_widget(Some(&adw::WindowTitle::new("Review Redactions", &title)));
+    let discard_btn = gtk::Button::with_label("Discard");
+    discard_btn.add_css_class("destructive-action");
+    discard_btn.set_tooltip_text(Some("Delete the capture"));
+    let choose_for_discard = choose.clone();
+    discard_btn.connect_clicked(move |_| choose_for_discard(false));
+    header.pack_start(&discard_btn);
+    let continue_btn = gtk::Button::with_label("Continue");
+    continue_btn.add_css_class("suggested-action");
+    let choose_for_continue = choose.clone();
+    continue_btn.connect_clicked(move |_| choose_for_continue(true));
+    header.pack_end(&continue_btn);
+
+    let list_box = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    list_box.set_margin_top(24);
+    list_box.set_margin_bottom(24);
+    list_box.set_margin_start(24);
+    list_box.set_margin_end(24);
+    for file in files {
+        let in_file: Vec<&Redaction> = redactions.iter().filter(|r| &r.file == file).collect();
+        if in_file.is_empty() {
+            continue;
+        }
+        list_box.append(&redacted_image(file, &in_file));
+        let list = gtk::ListBox::new();
+        list.add_css_class("boxed-list");
+        list.set_selection_mode(gtk::SelectionMode::None);
+        for redaction in in_file {
+            let row = adw::ActionRow::new();
+            row.set_title(&glib::markup_escape_text(&redaction.rule));
+            row.set_subtitle(&glib::markup_escape_text(&masked(&redaction.text)));
+            list.append(&row);
+        }
+        list_box.append(&list);
+    }
+    let scrolled = gtk::ScrolledWindow::new();
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&list_box));
+
+    let vbox = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    vbox.append(&header);
+    vbox.append(&scrolled);
+    window.set_content(Some(&vbox));
+
+    // Escape or closing the window keeps the capture, as it's already redacted
+    let key_controller = gtk::EventControllerKey::new();
+    let choose_for_key = choose.clone();
+    key_controller.connect_key_pressed(move |_, keyval, _, _| {
+        if keyval == gdk::Key::Escape {
+            choose_for_key(true);
+            glib::Propagation::Stop
+        } else {
+            glib::Propagation::Proceed
+        }
+    });
+    window.add_controller(key_controller);
+    window.connect_close_request(move |_| {
+        if let Some(sender) = sender.borrow_mut().take() {
+            let _ = sender.send(true);
+        }
+        glib::Propagation::Proceed
+    });
+    window.present();
+
+    receiver.await.unwrap_or(true)
+}
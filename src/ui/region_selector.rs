@@ -0,0 +1,521 @@
+use crate::display_manager::monitor_control;
+use crate::screenshot::{self, Client, Region};
+use futures::channel::oneshot;
+use gtk::cairo;
+use gtk::gdk;
+use gtk::gdk_pixbuf::Pixbuf;
+use gtk::glib;
+use gtk::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use tracing::warn;
+
+// Corners snap to window edges closer than this (layout pixels)
+const SNAP_DISTANCE: f64 = 10.0;
+// A press that moves less than this is a click: pick the window under it
+const CLICK_SLOP: f64 = 3.0;
+const LOUPE_SIZE: f64 = 144.0;
+const LOUPE_ZOOM: f64 = 8.0;
+const REGION_HINT: &str = "Drag to select · Click a window · Enter to capture · Arrows nudge (Shift ×10, Alt resizes) · R aspect · Esc cancel";
+const WINDOW_HINT: &str = "Click a window to capture it · Esc cancel";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AspectLock {
+    Free,
+    Ratio(u32, u32),
+}
+
+impl AspectLock {
+    // "16:9", "1:1", ...
+    pub fn parse(text: &str) -> Option<AspectLock> {
+        let (w, h) = text.trim().split_once(':')?;
+        let (w, h) = (w.trim().parse().ok()?, h.trim().parse().ok()?);
+        (w > 0 && h > 0).then_some(AspectLock::Ratio(w, h))
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            AspectLock::Free => "free".to_string(),
+            AspectLock::Ratio(w, h) => format!("{}:{}", w, h),
+        }
+    }
+}
+
+// One monitor of the overlay, in layout coordinates, with the frozen frame
+// captured just before the overlay opened
+struct Output {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    // Captured pixels per layout pixel
+    scale: f64,
+    frozen: Option<cairo::ImageSurface>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OverlayMode {
+    // Free rectangle, snapping to window edges
+    Region,
+    // Whole windows only: hover highlights, click picks
+    Window,
+}
+
+struct Selection {
+    mode: OverlayMode,
+    outputs: Vec<Output>,
+    areas: Vec<gtk::DrawingArea>,
+    windows: Vec<gtk::Window>,
+    snap_targets: Vec<Region>,
+    aspects: Vec<AspectLock>,
+    aspect: usize,
+    anchor: Option<(f64, f64)>,
+    // x, y, width, height in layout coordinates, width/height >= 0
+    rect: Option<(f64, f64, f64, f64)>,
+    pointer: Option<(f64, f64)>,
+    result: Option<oneshot::Sender<Option<Region>>>,
+}
+
+impl Selection {
+    fn redraw(&self) {
+        for area in &self.areas {
+            area.queue_draw();
+        }
+    }
+
+    fn snap(&self, value: f64, horizontal: bool) -> f64 {
+        self.snap_targets
+            .iter()
+            .flat_map(|r| {
+                if horizontal {
+                    [r.x as f64, (r.x + r.width) as f64]
+                } else {
+                    [r.y as f64, (r.y + r.height) as f64]
+                }
+            })
+            .filter(|edge| (edge - value).abs() <= SNAP_DISTANCE)
+            .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()))
+            .unwrap_or(value)
+    }
+
+    fn drag_to(&mut self, x: f64, y: f64) {
+        let Some((ax, ay)) = self.anchor else {
+            return;
+        };
+        let x = self.snap(x, true);
+        let mut y = self.snap(y, false);
+        if let AspectLock::Ratio(w, h) = self.aspects[self.aspect] {
+            let height = (x - ax).abs() * h as f64 / w as f64;
+            y = if y < ay { ay - height } else { ay + height };
+        }
+        self.rect = Some((ax.min(x), ay.min(y), (x - ax).abs(), (y - ay).abs()));
+    }
+
+    // Smallest window under the point, so a dialog wins over its parent
+    fn pick_window(&mut self, x: f64, y: f64) {
+        if let Some(region) = self
+            .snap_targets
+            .iter()
+            .filter(|r| r.contains(x, y))
+            .min_by_key(|r| r.area())
+        {
+            self.rect = Some((region.x as f64, region.y as f64, region.width as f64, region.height as f64));
+        }
+    }
+
+    fn nudge(&mut self, dx: f64, dy: f64, resize: bool) {
+        if let Some((x, y, w, h)) = self.rect {
+            self.rect = Some(if resize {
+                (x, y, (w + dx).max(1.0), (h + dy).max(1.0))
+            } else {
+                (x + dx, y + dy, w, h)
+            });
+        }
+    }
+
+    fn region(&self) -> Option<Region> {
+        let (x, y, w, h) = self.rect?;
+        let region = Region {
+            x: x.round() as i32,
+            y: y.round() as i32,
+            width: w.round() as i32,
+            height: h.round() as i32,
+        };
+        (region.width > 0 && region.height > 0).then_some(region)
+    }
+
+    fn finish(&mut self, confirmed: bool) {
+        let region = if confirmed { self.region() } else { None };
+        if confirmed && region.is_none() {
+            return;
+        }
+        for window in self.windows.drain(..) {
+            window.close();
+        }
+        self.areas.clear();
+        if let Some(sender) = self.result.take() {
+            let _ = sender.send(region);
+        }
+    }
+}
+
+// Where an output's freeze frame is written. It holds whatever is on
+// screen, so it goes in the per-user runtime directory (mode 0700), or the
+// cache directory without one, never the shared temp directory.
+fn freeze_path(output: &str) -> std::io::Result<std::path::PathBuf> {
+    let dir = dirs::runtime_dir()
+        .or_else(dirs::cache_dir)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no runtime or cache directory"))?
+        .join("hypr-xdisplay");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join(format!("freeze-{}.png", output)))
+}
+
+// Decode a grim capture into a cairo surface once, so every redraw is a
+// cheap surface paint
+fn load_frozen(path: &std::path::Path) -> Option<cairo::ImageSurface> {
+    let pixbuf = Pixbuf::from_file(path).ok()?;
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, pixbuf.width(), pixbuf.height()).ok()?;
+    let cr = cairo::Context::new(&surface).ok()?;
+    cr.set_source_pixbuf(&pixbuf, 0.0, 0.0);
+    cr.paint().ok()?;
+    drop(cr);
+    Some(surface)
+}
+
+fn draw_output(cr: &cairo::Context, selection: &Selection, output: &Output) {
+    let (w, h) = (output.width, output.height);
+    match output.frozen {
+        Some(ref frozen) => {
+            let _ = cr.save();
+            cr.scale(1.0 / output.scale, 1.0 / output.scale);
+            let _ = cr.set_source_surface(frozen, 0.0, 0.0);
+            let _ = cr.paint();
+            let _ = cr.restore();
+        }
+        None => {
+            cr.set_source_rgb(0.1, 0.1, 0.1);
+            let _ = cr.paint();
+        }
+    }
+
+    // Dim everything outside the selection
+    cr.set_source_rgba(0.0, 0.0, 0.0, 0.45);
+    cr.set_fill_rule(cairo::FillRule::EvenOdd);
+    cr.rectangle(0.0, 0.0, w, h);
+    let local = selection.rect.map(|(x, y, sw, sh)| (x - output.x, y - output.y, sw, sh));
+    if let Some((x, y, sw, sh)) = local {
+        cr.rectangle(x, y, sw, sh);
+    }
+    let _ = cr.fill();
+    cr.set_fill_rule(cairo::FillRule::Winding);
+
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+    cr.set_font_size(14.0);
+    if let (Some((x, y, sw, sh)), Some(region)) = (local, selection.region()) {
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        cr.set_line_width(1.0);
+        cr.rectangle(x.floor() + 0.5, y.floor() + 0.5, sw.round(), sh.round());
+        let _ = cr.stroke();
+        let readout = format!(
+            "{}×{}  at {},{}  ({})",
+            region.width,
+            region.height,
+            region.x,
+            region.y,
+            selection.aspects[selection.aspect].label()
+        );
+        let label_y = if y + sh + 28.0 < h { y + sh + 20.0 } else { (y - 8.0).max(16.0) };
+        draw_label(cr, &readout, x.max(4.0), label_y);
+    }
+
+    let hint = match selection.mode {
+        OverlayMode::Region => REGION_HINT,
+        OverlayMode::Window => WINDOW_HINT,
+    };
+    draw_label(cr, hint, 12.0, 24.0);
+
+    if let (Some((px, py)), Some(ref frozen)) = (selection.pointer, &output.frozen) {
+        let (lx, ly) = (px - output.x, py - output.y);
+        if lx >= 0.0 && ly >= 0.0 && lx < w && ly < h {
+            draw_loupe(cr, frozen, output, lx, ly, (px.round() as i32, py.round() as i32));
+        }
+    }
+}
+
+fn draw_label(cr: &cairo::Context, text: &str, x: f64, y: f64) {
+    if let Ok(extents) = cr.text_extents(text) {
+        cr.set_source_rgba(0.0, 0.0, 0.0, 0.7);
+        cr.rectangle(x - 6.0, y - 16.0, extents.width() + 12.0, 22.0);
+        let _ = cr.fill();
+    }
+    cr.set_source_rgb(1.0, 1.0, 1.0);
+    cr.move_to(x, y);
+    let _ = cr.show_text(text);
+}
+
+// Magnified view of the captured pixels around the pointer, kept on screen
+fn draw_loupe(cr: &cairo::Context, frozen: &cairo::ImageSurface, output: &Output, lx: f64, ly: f64, global: (i32, i32)) {
+    let offset = 24.0;
+    let mut cx = lx + offset + LOUPE_SIZE / 2.0;
+    let mut cy = ly + offset + LOUPE_SIZE / 2.0;
+    if cx + LOUPE_SIZE / 2.0 > output.width {
+        cx = lx - offset - LOUPE_SIZE / 2.0;
+    }
+    if cy + LOUPE_SIZE / 2.0 + 24.0 > output.height {
+        cy = ly - offset - LOUPE_SIZE / 2.0;
+    }
+    let (left, top) = (cx - LOUPE_SIZE / 2.0, cy - LOUPE_SIZE / 2.0);
+
+    let _ = cr.save();
+    cr.rectangle(left, top, LOUPE_SIZE, LOUPE_SIZE);
+    cr.clip();
+    cr.set_source_rgb(0.0, 0.0, 0.0);
+    let _ = cr.paint();
+    cr.translate(cx, cy);
+    cr.scale(LOUPE_ZOOM, LOUPE_ZOOM);
+    cr.translate(-(lx * output.scale).floor() - 0.5, -(ly * output.scale).floor() - 0.5);
+    if cr.set_source_surface(frozen, 0.0, 0.0).is_ok() {
+        cr.source().set_filter(cairo::Filter::Nearest);
+        let _ = cr.paint();
+    }
+    let _ = cr.restore();
+
+    // Crosshair on the pixel under the pointer, then the frame
+    cr.set_line_width(1.0);
+    cr.set_source_rgba(1.0, 0.2, 0.2, 0.9);
+    cr.rectangle(cx - LOUPE_ZOOM / 2.0, cy - LOUPE_ZOOM / 2.0, LOUPE_ZOOM, LOUPE_ZOOM);
+    let _ = cr.stroke();
+    cr.set_source_rgb(1.0, 1.0, 1.0);
+    cr.rectangle(left + 0.5, top + 0.5, LOUPE_SIZE - 1.0, LOUPE_SIZE - 1.0);
+    let _ = cr.stroke();
+    draw_label(cr, &format!("{}, {}", global.0, global.1), left + 6.0, top + LOUPE_SIZE + 20.0);
+}
+
+fn gdk_monitor(name: &str) -> Option<gdk::Monitor> {
+    let display = gdk::Display::default()?;
+    let monitors = display.monitors();
+    (0..monitors.n_items())
+        .filter_map(|i| monitors.item(i).and_downcast::<gdk::Monitor>())
+        .find(|m| m.connector().as_deref() == Some(name))
+}
+
+// Freeze every output, let the user pick a rectangle and return it, or
+// None if the selection was cancelled. `custom_aspect` joins the 16:9 and
+// 1:1 presets that R cycles through.
+pub async fn select_region(custom_aspect: Option<AspectLock>) -> Option<Region> {
+    let clients = screenshot::visible_clients().await.unwrap_or_default();
+    run_overlay(OverlayMode::Region, &clients, custom_aspect).await
+}
+
+// "Click to pick": highlight the window under the pointer and return the
+// one clicked
+pub async fn pick_window() -> Option<Client> {
+    let clients = match screenshot::visible_clients().await {
+        Ok(clients) => clients,
+        Err(e) => {
+            crate::ui::toasts::show_toast(&format!("Failed to list windows: {}", e));
+            return None;
+        }
+    };
+    let region = run_overlay(OverlayMode::Window, &clients, None).await?;
+    clients.into_iter().find(|c| c.region() == region)
+}
+
+async fn run_overlay(mode: OverlayMode, clients: &[Client], custom_aspect: Option<AspectLock>) -> Option<Region> {
+    let monitors = match monitor_control::get_monitors().await {
+        Ok(monitors) => monitors,
+        Err(e) => {
+            crate::ui::toasts::show_toast(&format!("Failed to load monitors: {}", e));
+            return None;
+        }
+    };
+    let snap_targets = clients.iter().map(|c| c.region()).collect();
+
+    let mut outputs = Vec::new();
+    for monitor in &monitors {
+        let frozen = match freeze_path(&monitor.name) {
+            Ok(path) => {
+                let frozen = match screenshot::capture_output(&monitor.name, &path).await {
+                    Ok(()) => load_frozen(&path),
+                    Err(e) => {
+                        warn!("Could not freeze {}: {}", monitor.name, e);
+                        None
+                    }
+                };
+                let _ = std::fs::remove_file(&path);
+                frozen
+            }
+            Err(e) => {
+                warn!("Could not freeze {}: {}", monitor.name, e);
+                None
+            }
+        };
+        let (width, height) = monitor.logical_size();
+        let scale = frozen.as_ref().map(|f| f.width() as f64 / width.max(1) as f64).unwrap_or(monitor.scale);
+        outputs.push(Output {
+            x: monitor.x as f64,
+            y: monitor.y as f64,
+            width: width as f64,
+            height: height as f64,
+            scale,
+            frozen,
+        });
+    }
+
+    let mut aspects = vec![AspectLock::Free, AspectLock::Ratio(16, 9), AspectLock::Ratio(1, 1)];
+    if let Some(custom) = custom_aspect.filter(|a| !aspects.contains(a)) {
+        aspects.push(custom);
+    }
+    let (sender, receiver) = oneshot::channel();
+    let state = Rc::new(RefCell::new(Selection {
+        mode,
+        outputs,
+        areas: Vec::new(),
+        windows: Vec::new(),
+        snap_targets,
+        aspects,
+        aspect: 0,
+        anchor: None,
+        rect: None,
+        pointer: None,
+        result: Some(sender),
+    }));
+
+    for (idx, monitor) in monitors.iter().enumerate() {
+        let window = gtk::Window::new();
+        window.set_decorated(false);
+        window.set_title(Some("hypr-xdisplay: select region"));
+        match gdk_monitor(&monitor.name) {
+            Some(gdk_monitor) => window.fullscreen_on_monitor(&gdk_monitor),
+            None => window.fullscreen(),
+        }
+        window.set_cursor_from_name(Some("crosshair"));
+        let area = gtk::DrawingArea::new();
+        let state_for_draw = state.clone();
+        area.set_draw_func(move |_, cr, _, _| {
+            let selection = state_for_draw.borrow();
+            if let Some(output) = selection.outputs.get(idx) {
+                draw_output(cr, &selection, output);
+            }
+        });
+        let (origin_x, origin_y) = (monitor.x as f64, monitor.y as f64);
+
+        let motion = gtk::EventControllerMotion::new();
+        let state_for_motion = state.clone();
+        motion.connect_motion(move |_, x, y| {
+            let mut selection = state_for_motion.borrow_mut();
+            selection.pointer = Some((origin_x + x, origin_y + y));
+            if selection.mode == OverlayMode::Window {
+                selection.rect = None;
+                selection.pick_window(origin_x + x, origin_y + y);
+            }
+            selection.redraw();
+        });
+        area.add_controller(motion);
+
+        // Coordinates stay relative to this window for the whole drag, even
+        // when the pointer crosses onto another monitor
+        let drag = gtk::GestureDrag::new();
+        let state_for_begin = state.clone();
+        drag.connect_drag_begin(move |_, x, y| {
+            let mut selection = state_for_begin.borrow_mut();
+            if selection.mode == OverlayMode::Window {
+                return;
+            }
+            let (gx, gy) = (origin_x + x, origin_y + y);
+            selection.anchor = Some((selection.snap(gx, true), selection.snap(gy, false)));
+        });
+        let state_for_update = state.clone();
+        drag.connect_drag_update(move |gesture, dx, dy| {
+            if let Some((x, y)) = gesture.start_point() {
+                let mut selection = state_for_update.borrow_mut();
+                let (gx, gy) = (origin_x + x + dx, origin_y + y + dy);
+                selection.pointer = Some((gx, gy));
+                if selection.mode == OverlayMode::Region && (dx.abs() > CLICK_SLOP || dy.abs() > CLICK_SLOP) {
+                    selection.drag_to(gx, gy);
+                }
+                selection.redraw();
+            }
+        });
+        let state_for_end = state.clone();
+        drag.connect_drag_end(move |gesture, dx, dy| {
+            let mut selection = state_for_end.borrow_mut();
+            if dx.abs() <= CLICK_SLOP && dy.abs() <= CLICK_SLOP {
+                if let Some((x, y)) = gesture.start_point() {
+                    selection.pick_window(origin_x + x, origin_y + y);
+                    if selection.mode == OverlayMode::Window {
+                        selection.finish(true);
+                        return;
+                    }
+                }
+            }
+            selection.anchor = None;
+            selection.redraw();
+        });
+        area.add_controller(drag);
+
+        // Double-click confirms, like Enter
+        let click = gtk::GestureClick::new();
+        let state_for_click = state.clone();
+        click.connect_pressed(move |_, n_press, _, _| {
+            if n_press == 2 {
+                state_for_click.borrow_mut().finish(true);
+            }
+        });
+        area.add_controller(click);
+
+        let key_controller = gtk::EventControllerKey::new();
+        let state_for_key = state.clone();
+        key_controller.connect_key_pressed(move |_, keyval, _, modifiers| {
+            let mut selection = state_for_key.borrow_mut();
+            let step = if modifiers.contains(gdk::ModifierType::SHIFT_MASK) { 10.0 } else { 1.0 };
+            let resize = modifiers.contains(gdk::ModifierType::ALT_MASK);
+            if selection.mode == OverlayMode::Window && keyval != gdk::Key::Escape {
+                return glib::Propagation::Proceed;
+            }
+            match keyval {
+                gdk::Key::Escape => selection.finish(false),
+                gdk::Key::Return | gdk::Key::KP_Enter | gdk::Key::space => selection.finish(true),
+                gdk::Key::Left => selection.nudge(-step, 0.0, resize),
+                gdk::Key::Right => selection.nudge(step, 0.0, resize),
+                gdk::Key::Up => selection.nudge(0.0, -step, resize),
+                gdk::Key::Down => selection.nudge(0.0, step, resize),
+                gdk::Key::r | gdk::Key::R => {
+                    selection.aspect = (selection.aspect + 1) % selection.aspects.len();
+                    // Re-shape the current selection around its top-left corner
+                    if let (Some((x, y, w, _)), AspectLock::Ratio(rw, rh)) =
+                        (selection.rect, selection.aspects[selection.aspect])
+                    {
+                        selection.rect = Some((x, y, w, w * rh as f64 / rw as f64));
+                    }
+                }
+                _ => return glib::Propagation::Proceed,
+            }
+            selection.redraw();
+            glib::Propagation::Stop
+        });
+        window.add_controller(key_controller);
+
+        // Closing any overlay window (e.g. killactive) cancels the selection
+        let state_for_close = state.clone();
+        window.connect_close_request(move |_| {
+            if let Ok(mut selection) = state_for_close.try_borrow_mut() {
+                selection.finish(false);
+            }
+            glib::Propagation::Proceed
+        });
+
+        window.set_child(Some(&area));
+        let mut selection = state.borrow_mut();
+        selection.areas.push(area);
+        selection.windows.push(window.clone());
+        drop(selection);
+        window.present();
+    }
+
+    let region = receiver.await.ok().flatten();
+    // Give the compositor a moment to unmap the overlay before grim runs
+    glib::timeout_future(std::time::Duration::from_millis(150)).await;
+    region
+}
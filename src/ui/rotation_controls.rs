@@ -0,0 +1,57 @@
+use crate::display_manager::auto_rotation;
+use crate::ui::main_window::{update_settings, SharedSettings};
+use gtk::glib;
+use gtk::prelude::*;
+use libadwaita as adw;
+
+pub fn add_rotation_controls_section(content: &gtk::Box, settings: SharedSettings) {
+    let clamp = adw::Clamp::new();
+    clamp.set_maximum_size(900);
+    clamp.set_margin_start(24);
+    clamp.set_margin_end(24);
+
+    let frame = gtk::Frame::new(Some("Auto-Rotation"));
+    let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    hbox.set_margin_top(8);
+    hbox.set_margin_bottom(8);
+    hbox.set_margin_start(8);
+    hbox.set_margin_end(8);
+    let enable_switch = gtk::Switch::new();
+    enable_switch.set_valign(gtk::Align::Center);
+    enable_switch.set_active(settings.borrow().auto_rotation.unwrap_or(false));
+    let lock_btn = gtk::ToggleButton::new();
+    lock_btn.set_icon_name("rotation-locked-symbolic");
+    lock_btn.set_tooltip_text(Some("Lock rotation"));
+    lock_btn.set_active(auto_rotation::is_rotation_locked());
+    hbox.append(&gtk::Label::new(Some("Follow device orientation")));
+    hbox.append(&enable_switch);
+    hbox.append(&lock_btn);
+    frame.set_child(Some(&hbox));
+    clamp.set_child(Some(&frame));
+    content.append(&clamp);
+
+    if settings.borrow().auto_rotation.unwrap_or(false) {
+        auto_rotation::start_auto_rotation(settings.borrow().rotation_output.clone());
+    }
+
+    enable_switch.connect_active_notify(move |switch| {
+        let enabled = switch.is_active();
+        if enabled {
+            auto_rotation::start_auto_rotation(settings.borrow().rotation_output.clone());
+        } else {
+            auto_rotation::stop_auto_rotation();
+        }
+        if let Err(e) = update_settings(&settings, |s| s.auto_rotation = Some(enabled)) {
+            crate::ui::toasts::show_toast(&format!("Failed to save settings: {}", e));
+        }
+    });
+
+    lock_btn.connect_toggled(move |btn| {
+        let locked = btn.is_active();
+        glib::MainContext::default().spawn_local(async move {
+            if let Err(e) = auto_rotation::set_rotation_locked(locked).await {
+                crate::ui::toasts::show_toast(&format!("Rotation error: {}", e));
+            }
+        });
+    });
+}
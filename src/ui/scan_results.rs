@@ -0,0 +1,100 @@
+use crate::code_scanner::ScannedCode;
+use crate::output_sinks;
+use crate::ui::main_window::MAIN_WINDOW;
+use gtk::gdk;
+use gtk::glib;
+use libadwaita as adw;
+use adw::prelude::*;
+use std::process::Command;
+
+// Decoded payloads with copy and open actions. Nothing is opened unless
+// the user clicks Open, and only web links get that button.
+pub fn show_codes(codes: Vec<ScannedCode>) {
+    let window = adw::Window::new();
+    window.set_title(Some("Scanned Codes"));
+    window.set_modal(true);
+    window.set_default_size(520, 360);
+    MAIN_WINDOW.with(|w| {
+        if let Some(main_window) = w.borrow().as_ref() {
+            window.set_transient_for(Some(main_window));
+        }
+    });
+
+    let header = adw::HeaderBar::new();
+    let subtitle = match codes.len() {
+        1 => "1 code found".to_string(),
+        n => format!("{} codes found", n),
+    };
+    header.set_title_widget(Some(&adw::WindowTitle::new("Scanned Codes", &subtitle)));
+
+    let list = gtk::ListBox::new();
+    list.add_css_class("boxed-list");
+    list.set_selection_mode(gtk::SelectionMode::None);
+    list.set_valign(gtk::Align::Start);
+    for code in codes {
+        let row = adw::ActionRow::new();
+        row.set_title(&glib::markup_escape_text(&code.text));
+        row.set_title_lines(4);
+        let kind = match code.description() {
+            Some(description) => format!("{} · {}", code.format, description),
+            None => code.format.to_string(),
+        };
+        row.set_subtitle(&glib::markup_escape_text(&kind));
+
+        if let Some(url) = code.url() {
+            let open_btn = gtk::Button::from_icon_name("web-browser-symbolic");
+            open_btn.set_tooltip_text(Some(&format!("Open {}", url)));
+            open_btn.set_valign(gtk::Align::Center);
+            let url = url.to_string();
+            open_btn.connect_clicked(move |_| {
+                if let Err(e) = Command::new("xdg-open").arg(&url).spawn() {
+                    crate::ui::toasts::show_toast(&format!("Failed to open link: {}", e));
+                }
+            });
+            row.add_suffix(&open_btn);
+        }
+        let copy_btn = gtk::Button::from_icon_name("edit-copy-symbolic");
+        copy_btn.set_tooltip_text(Some("Copy to clipboard"));
+        copy_btn.set_valign(gtk::Align::Center);
+        let text = code.text.clone();
+        copy_btn.connect_clicked(move |_| {
+            let text = text.clone();
+            glib::MainContext::default().spawn_local(async move {
+                match output_sinks::copy_text(&text).await {
+                    Ok(()) => crate::ui::toasts::show_toast("Copied to clipboard"),
+                    Err(e) => crate::ui::toasts::show_toast(&format!("Failed to copy: {}", e)),
+                }
+            });
+        });
+        row.add_suffix(&copy_btn);
+        list.append(&row);
+    }
+
+    let scrolled = gtk::ScrolledWindow::new();
+    scrolled.set_vexpand(true);
+    let clamp = adw::Clamp::new();
+    clamp.set_margin_top(24);
+    clamp.set_margin_bottom(24);
+    clamp.set_margin_start(24);
+    clamp.set_margin_end(24);
+    clamp.set_child(Some(&list));
+    scrolled.set_child(Some(&clamp));
+
+    let vbox = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    vbox.append(&header);
+    vbox.append(&scrolled);
+    window.set_content(Some(&vbox));
+
+    let key_controller = gtk::EventControllerKey::new();
+    let window_for_key = window.clone();
+    key_controller.connect_key_pressed(move |_, keyval, _, _| {
+        if keyval == gdk::Key::Escape {
+            window_for_key.close();
+            glib::Propagation::Stop
+        } else {
+            glib::Propagation::Proceed
+        }
+    });
+    window.add_controller(key_controller);
+    window.present();
+}
@@ -1,12 +1,50 @@
 use gtk::prelude::*;
 use std::sync::{Arc, Mutex};
 use crate::settings::Settings;
-use crate::screenshot;
+use crate::display_manager::{monitor_control, monitor_types::Monitor};
+use crate::screenshot::{self, Capture, CaptureTarget, DelayHandle};
+use crate::screenshot::postprocess::{self, FrameOptions, OutputFormat, ProcessOptions};
+use crate::output_sinks::{self, OutputSink};
+use crate::uploader;
+use crate::gallery;
+use crate::ocr;
+use crate::code_scanner;
+use crate::redaction::{self, RedactionOptions};
+use crate::ui::gallery_view;
+use crate::ui::main_window::{update_settings, SharedSettings, MAIN_WINDOW};
+use futures::channel::oneshot;
+use crate::ui::{annotation_editor, redaction_preview, region_selector, scan_results, window_picker};
 use libadwaita as adw;
-use std::path::PathBuf;
+use adw::prelude::*;
 use std::process::Command;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+const COUNTDOWN_NOTIFICATION: &str = "screenshot-countdown";
+const HISTORY_SEED: usize = 20;
+// Region captures whose text is recognised and copied
+const TEXT_MODE: &str = "capture_text";
+// Region captures decoded as QR codes and barcodes, never kept
+const SCAN_MODE: &str = "scan_code";
+
+thread_local! {
+    // Delay of the capture currently counting down, if any
+    static PENDING_DELAY: RefCell<Option<DelayHandle>> = const { RefCell::new(None) };
+}
 
-pub fn add_screenshot_controls_section(content: &gtk::Box, settings: Arc<Settings>) {
+// Cancel a capture that is still counting down. Also bound to the
+// `app.cancel-screenshot` action used by the countdown notification.
+pub fn cancel_delayed_capture() {
+    PENDING_DELAY.with(|pending| {
+        if let Some(handle) = pending.borrow().as_ref() {
+            handle.cancel();
+        }
+    });
+}
+
+pub fn add_screenshot_controls_section(content: &gtk::Box, settings: SharedSettings) {
     let clamp = adw::Clamp::new();
     clamp.set_maximum_size(700);
     clamp.set_margin_top(24);
@@ -20,54 +58,266 @@ pub fn add_screenshot_controls_section(content: &gtk::Box, settings: Arc<Setting
     vbox.append(&header);
 
     // Screenshot history dropdown
-    let history: Arc<Mutex<Vec<PathBuf>>> = Arc::new(Mutex::new(Vec::new()));
+    let history: Arc<Mutex<Vec<Capture>>> = Arc::new(Mutex::new(Vec::new()));
     let history_combo = gtk::ComboBoxText::new();
     history_combo.set_hexpand(false);
     history_combo.set_valign(gtk::Align::Center);
     history_combo.set_tooltip_text(Some("Screenshot history"));
     vbox.append(&history_combo);
 
-    // Screenshot mode cards
+    // Capture delay, for menus and hover states that close on focus loss
+    let delay_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    delay_box.append(&gtk::Label::new(Some("Delay")));
+    let delay_combo = gtk::ComboBoxText::new();
+    for (id, text) in [("0", "No delay"), ("3", "3 seconds"), ("5", "5 seconds"), ("10", "10 seconds")] {
+        delay_combo.append(Some(id), text);
+    }
+    let delay = settings.borrow().screenshot_delay_secs.unwrap_or(0).to_string();
+    if !delay_combo.set_active_id(Some(&delay)) {
+        delay_combo.set_active_id(Some("0"));
+    }
+    delay_combo.set_tooltip_text(Some("Wait before capturing, after any region or window is chosen"));
+    let settings_for_delay = settings.clone();
+    delay_combo.connect_changed(move |combo| {
+        let seconds = combo.active_id().and_then(|id| id.parse().ok()).unwrap_or(0);
+        if let Err(e) = update_settings(&settings_for_delay, |s| s.screenshot_delay_secs = Some(seconds)) {
+            crate::ui::toasts::show_toast(&format!("Failed to save settings: {}", e));
+        }
+    });
+    delay_box.append(&delay_combo);
+
+    // Opt-in redaction of sensitive text found by OCR
+    let redact_check = gtk::CheckButton::with_label("Redact sensitive text");
+    redact_check.set_tooltip_text(Some(
+        "Pixelate emails, IP addresses, tokens and keys before a capture is saved or uploaded (needs tesseract)",
+    ));
+    redact_check.set_active(settings.borrow().screenshot_redaction.as_ref().is_some_and(|r| r.enabled));
+    let settings_for_redact = settings.clone();
+    redact_check.connect_toggled(move |check| {
+        let saved = update_settings(&settings_for_redact, |s| {
+            s.screenshot_redaction.get_or_insert_with(RedactionOptions::default).enabled = check.is_active();
+        });
+        if let Err(e) = saved {
+            crate::ui::toasts::show_toast(&format!("Failed to save settings: {}", e));
+        }
+    });
+    delay_box.append(&redact_check);
+    vbox.append(&delay_box);
+
+    // Countdown shown while a delayed capture is pending
+    let countdown_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    let countdown_label = gtk::Label::new(None);
+    countdown_label.set_hexpand(true);
+    countdown_label.set_xalign(0.0);
+    let cancel_btn = gtk::Button::with_label("Cancel");
+    cancel_btn.connect_clicked(|_| cancel_delayed_capture());
+    countdown_box.append(&countdown_label);
+    countdown_box.append(&cancel_btn);
+    countdown_box.set_visible(false);
+    vbox.append(&countdown_box);
+
+    // Screenshot mode cards, with the settings key for their sinks
     let modes = [
-        ("Fullscreen", "view-fullscreen-symbolic"),
-        ("Region", "select-rectangle-symbolic"),
-        ("Window", "window-symbolic"),
+        ("Fullscreen", "fullscreen", "view-fullscreen-symbolic"),
+        ("Region", "region", "select-rectangle-symbolic"),
+        ("Window", "window", "window-symbolic"),
+        ("Monitor", "monitor", "video-display-symbolic"),
+        ("Choose Monitor", "choose_monitor", "view-list-symbolic"),
+        ("Each Monitor", "each_monitor", "view-grid-symbolic"),
+        ("Text", TEXT_MODE, "insert-text-symbolic"),
+        ("Scan Code", SCAN_MODE, "scanner-symbolic"),
     ];
-    let mode_box = gtk::Box::new(gtk::Orientation::Horizontal, 12);
-    for (label, icon) in modes.iter() {
+    let mode_box = gtk::FlowBox::new();
+    mode_box.set_selection_mode(gtk::SelectionMode::None);
+    mode_box.set_max_children_per_line(3);
+    mode_box.set_column_spacing(12);
+    mode_box.set_row_spacing(12);
+    for (label, key, icon) in modes.iter() {
         let frame = gtk::Frame::new(Some(label));
         frame.set_width_request(160);
         frame.set_height_request(100);
+        let card = gtk::Box::new(gtk::Orientation::Vertical, 4);
         let btn = gtk::Button::from_icon_name(icon);
-        btn.set_tooltip_text(Some(&format!("Capture {} screenshot", label)));
-        frame.set_child(Some(&btn));
+        if *key == TEXT_MODE {
+            btn.set_tooltip_text(Some("Capture a region and copy the text in it"));
+        } else if *key == SCAN_MODE {
+            btn.set_tooltip_text(Some("Read QR codes and barcodes in a region"));
+        } else {
+            btn.set_tooltip_text(Some(&format!("Capture {} screenshot", label)));
+        }
+        btn.set_vexpand(true);
+        card.append(&btn);
+        // Scans have no outputs to configure
+        if *key != SCAN_MODE {
+            let sinks_btn = gtk::MenuButton::new();
+            sinks_btn.set_label("Outputs");
+            sinks_btn.set_tooltip_text(Some("Where this mode's screenshots go"));
+            sinks_btn.set_popover(Some(&build_sinks_popover(*key, settings.clone())));
+            let menus = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+            menus.set_homogeneous(true);
+            menus.append(&sinks_btn);
+            // Text is recognised from the capture as grim took it
+            if *key == TEXT_MODE {
+                menus.append(&build_language_combo(settings.clone()));
+            } else {
+                let processing_btn = gtk::MenuButton::new();
+                processing_btn.set_label("Processing");
+                processing_btn.set_tooltip_text(Some("Format, size and frame for this mode's screenshots"));
+                processing_btn.set_popover(Some(&build_processing_popover(*key, settings.clone())));
+                menus.append(&processing_btn);
+            }
+            card.append(&menus);
+        }
+        frame.set_child(Some(&card));
+        let key = key.to_string();
         let settings_clone = settings.clone();
         let history_clone = Arc::clone(&history);
         let history_combo_clone = history_combo.clone();
+        let delay_combo_clone = delay_combo.clone();
+        let countdown_box_clone = countdown_box.clone();
+        let countdown_label_clone = countdown_label.clone();
         let label = label.to_string();
         btn.connect_clicked(move |_| {
             let label = label.clone();
-            let settings = settings_clone.clone();
+            let key = key.clone();
+            // As saved at the time of the click, from any tab
+            let settings = settings_clone.borrow().clone();
             let history = Arc::clone(&history_clone);
             let history_combo = history_combo_clone.clone();
+            let delay: u64 = delay_combo_clone.active_id().and_then(|id| id.parse().ok()).unwrap_or(0);
+            let countdown_box = countdown_box_clone.clone();
+            let countdown_label = countdown_label_clone.clone();
+            let mode_sinks = sinks_for_mode(&settings, &key);
+            let processing = processing_for_mode(&settings, &key);
             glib::MainContext::default().spawn_local(async move {
-                let result = match label.as_str() {
-                    "Fullscreen" => screenshot::capture_fullscreen(settings.as_ref()).await,
-                    "Region" => screenshot::capture_region(settings.as_ref()).await,
-                    "Window" => screenshot::capture_focused_window(settings.as_ref()).await,
+                let target = match label.as_str() {
+                    "Fullscreen" => CaptureTarget::Fullscreen,
+                    "Region" | "Text" | "Scan Code" => {
+                        let custom_aspect = settings
+                            .screenshot_custom_aspect
+                            .as_deref()
+                            .and_then(region_selector::AspectLock::parse);
+                        match region_selector::select_region(custom_aspect).await {
+                            Some(region) => CaptureTarget::Region(region),
+                            None => return,
+                        }
+                    }
+                    "Window" => match window_picker::choose_window().await {
+                        Some(client) => CaptureTarget::Window(client),
+                        None => return,
+                    },
+                    "Monitor" => match screenshot::cursor_monitor().await {
+                        Ok(monitor) => CaptureTarget::Monitor(Box::new(monitor)),
+                        Err(e) => {
+                            crate::ui::toasts::show_toast(&format!("Screenshot failed: {}", e));
+                            return;
+                        }
+                    },
+                    "Choose Monitor" => match choose_monitor().await {
+                        Some(monitor) => CaptureTarget::Monitor(Box::new(monitor)),
+                        None => return,
+                    },
+                    "Each Monitor" => CaptureTarget::EachMonitor,
                     _ => return,
                 };
-                if let Ok(path) = result {
-                    {
-                        let mut guard = history.lock().unwrap();
-                        guard.push(path.clone());
-                        history_combo.append_text(path.file_name().unwrap().to_str().unwrap());
-                        history_combo.set_active(Some((guard.len() - 1) as u32));
+                if delay > 0 && !wait_for_delay(delay, &countdown_box, &countdown_label).await {
+                    crate::ui::toasts::show_toast("Screenshot cancelled");
+                    return;
+                }
+                if key == SCAN_MODE {
+                    scan_codes(&settings, &target).await;
+                    return;
+                }
+                // Before anything else reads the pixels, post-processing included.
+                // Text captures are left alone, since their text is what's wanted.
+                let redaction_options = settings
+                    .screenshot_redaction
+                    .clone()
+                    .filter(|options| options.enabled && key != TEXT_MODE);
+                let capture = match &redaction_options {
+                    Some(_) => output_sinks::capture_staged(&settings, &target).await,
+                    None => output_sinks::capture_for(&settings, &target, &mode_sinks).await,
+                };
+                let mut capture = match capture {
+                    Ok(capture) => capture,
+                    Err(e) => {
+                        crate::ui::toasts::show_toast(&format!("Screenshot failed: {}", e));
+                        return;
+                    }
+                };
+                if let Some(options) = redaction_options {
+                    let languages = settings
+                        .ocr_languages
+                        .clone()
+                        .unwrap_or_else(|| ocr::DEFAULT_LANGUAGES.to_string());
+                    if !redact_capture(&capture, &options, &languages).await {
+                        return;
+                    }
+                    capture = match output_sinks::move_into_place(&settings, &capture, &mode_sinks) {
+                        Ok(capture) => capture,
+                        Err(e) => {
+                            delete_capture(&capture);
+                            crate::ui::toasts::show_toast(&format!("Failed to save the redacted screenshot: {}", e));
+                            return;
+                        }
+                    };
+                }
+                // Text is read from the raw capture, before post-processing
+                let text = match capture.primary() {
+                    Some(path) if key == TEXT_MODE => Some(ocr::recognize_capture(&settings, path).await),
+                    _ => None,
+                };
+                // Word boxes are in raw capture pixels
+                let boxes_match = !processing.transforms();
+                let original = capture.clone();
+                let capture = postprocess::process(capture, processing).await.unwrap_or_else(|e| {
+                    crate::ui::toasts::show_toast(&format!("Post-processing failed, kept the original: {}", e));
+                    original
+                });
+                if let Err(e) = output_sinks::deliver(&settings, &capture, &mode_sinks).await {
+                    crate::ui::toasts::show_toast(&format!("Screenshot taken, but {}", e));
+                }
+                // After the sinks, so the text ends up on the clipboard
+                if let Some(text) = text {
+                    let kept = capture
+                        .primary()
+                        .filter(|_| boxes_match && mode_sinks.contains(&OutputSink::Save))
+                        .map(PathBuf::as_path);
+                    let text = match text {
+                        Ok(result) => ocr::keep_and_copy(result, kept).await,
+                        Err(e) => Err(e),
+                    };
+                    match text {
+                        Ok(result) if result.words.is_empty() => {
+                            crate::ui::toasts::show_toast("No text found in the capture")
+                        }
+                        Ok(result) => crate::ui::toasts::show_toast(&format!(
+                            "Copied {} words of text",
+                            result.words.len()
+                        )),
+                        Err(e) => crate::ui::toasts::show_toast(&format!("Text recognition failed: {}", e)),
+                    }
+                }
+                if mode_sinks.contains(&OutputSink::Save) {
+                    let entries = gallery::entries_for_capture(&capture, &key, &target);
+                    match gallery::record(entries) {
+                        Ok(()) => gallery_view::refresh(),
+                        Err(e) => tracing::warn!("Failed to update gallery index: {}", e),
                     }
                 }
+                if output_sinks::discard_unsaved(&capture, &mode_sinks) {
+                    return;
+                }
+                let edit_path = capture.primary().cloned().filter(|_| {
+                    mode_sinks.contains(&OutputSink::Edit) && settings.screenshot_editor.is_none()
+                });
+                push_history(&history, &history_combo, capture);
+                if let Some(path) = edit_path {
+                    annotate_in_app(path, settings.screenshot_dir.clone(), history, history_combo).await;
+                }
             });
         });
-        mode_box.append(&frame);
+        mode_box.insert(&frame, -1);
     }
     vbox.append(&mode_box);
 
@@ -88,11 +338,14 @@ pub fn add_screenshot_controls_section(content: &gtk::Box, settings: Arc<Setting
     open_btn.set_tooltip_text(Some("Open screenshot"));
     let annotate_btn = gtk::Button::from_icon_name("draw-freehand-symbolic");
     annotate_btn.set_tooltip_text(Some("Annotate screenshot"));
-    let share_btn = gtk::Button::from_icon_name("mail-send-symbolic");
-    share_btn.set_tooltip_text(Some("Share/Upload screenshot"));
+    let share_btn = gtk::Button::from_icon_name("edit-copy-symbolic");
+    share_btn.set_tooltip_text(Some("Copy image to clipboard"));
     actions_box.append(&open_btn);
     actions_box.append(&annotate_btn);
+    let upload_btn = gtk::Button::from_icon_name("send-to-symbolic");
+    upload_btn.set_tooltip_text(Some("Upload screenshot and copy the link"));
     actions_box.append(&share_btn);
+    actions_box.append(&upload_btn);
     preview_box.append(&actions_box);
     preview_frame.set_child(Some(&preview_box));
     vbox.append(&preview_frame);
@@ -104,49 +357,508 @@ pub fn add_screenshot_controls_section(content: &gtk::Box, settings: Arc<Setting
     history_combo.connect_changed(move |combo| {
         if let Some(idx) = combo.active() {
             let guard = history_for_combo.lock().unwrap();
-            if let Some(path) = guard.get(idx as usize) {
-                preview_picture_clone.set_filename(Some(path.to_str().unwrap()));
-                filename_label_clone.set_text(path.file_name().unwrap().to_str().unwrap());
+            if let Some(capture) = guard.get(idx as usize) {
+                preview_picture_clone.set_filename(capture.primary());
+                filename_label_clone.set_text(&capture.label());
             }
         }
     });
 
+    // Seed history with the most recent captures from the gallery index
+    let seeded = {
+        let mut guard = history.lock().unwrap();
+        for entry in gallery::load().into_iter().rev().take(HISTORY_SEED).rev() {
+            if entry.path.exists() {
+                let capture = Capture::from(entry.path);
+                history_combo.append_text(&capture.label());
+                guard.push(capture);
+            }
+        }
+        guard.len()
+    };
+    if seeded > 0 {
+        history_combo.set_active(Some((seeded - 1) as u32));
+    }
+
     // Open screenshot
     let history_for_open = Arc::clone(&history);
     let history_combo_for_open = history_combo.clone();
     open_btn.connect_clicked(move |_| {
         if let Some(idx) = history_combo_for_open.active() {
             let guard = history_for_open.lock().unwrap();
-            if let Some(path) = guard.get(idx as usize) {
-                let _ = Command::new("xdg-open").arg(path).spawn();
+            if let Some(capture) = guard.get(idx as usize) {
+                for path in &capture.files {
+                    let _ = Command::new("xdg-open").arg(path).spawn();
+                }
             }
         }
     });
 
-    // Annotate screenshot (modal)
+    // Annotate in the external editor if one is configured, otherwise the built-in one
     let history_for_annotate = Arc::clone(&history);
     let history_combo_for_annotate = history_combo.clone();
+    let settings_for_annotate = settings.clone();
     annotate_btn.connect_clicked(move |_| {
-        if let Some(idx) = history_combo_for_annotate.active() {
-            let guard = history_for_annotate.lock().unwrap();
-            if let Some(path) = guard.get(idx as usize) {
-                let _ = Command::new("swappy").arg("-f").arg(path).spawn();
+        let Some(idx) = history_combo_for_annotate.active() else { return };
+        let path = history_for_annotate.lock().unwrap().get(idx as usize).and_then(|c| c.primary().cloned());
+        let Some(path) = path else { return };
+        let settings = settings_for_annotate.borrow().clone();
+        match settings.screenshot_editor {
+            Some(editor) => {
+                if let Err(e) = output_sinks::open_in_editor(&editor, &path) {
+                    crate::ui::toasts::show_toast(&format!("Failed to open editor: {}", e));
+                }
+            }
+            None => {
+                let history = Arc::clone(&history_for_annotate);
+                let history_combo = history_combo_for_annotate.clone();
+                glib::MainContext::default().spawn_local(async move {
+                    annotate_in_app(path, settings.screenshot_dir, history, history_combo).await;
+                });
             }
         }
     });
 
-    // Share/upload screenshot (modal)
+    // Copy the image data, not its path
     let history_for_share = Arc::clone(&history);
     let history_combo_for_share = history_combo.clone();
     share_btn.connect_clicked(move |_| {
-        if let Some(idx) = history_combo_for_share.active() {
-            let guard = history_for_share.lock().unwrap();
-            if let Some(path) = guard.get(idx as usize) {
-                let _ = Command::new("wl-copy").arg(path).status();
-            }
+        let Some(idx) = history_combo_for_share.active() else { return };
+        let path = history_for_share.lock().unwrap().get(idx as usize).and_then(|c| c.primary().cloned());
+        if let Some(path) = path {
+            glib::MainContext::default().spawn_local(async move {
+                match output_sinks::copy_image(&path).await {
+                    Ok(()) => crate::ui::toasts::show_toast("Image copied to clipboard"),
+                    Err(e) => crate::ui::toasts::show_toast(&format!("Failed to copy image: {}", e)),
+                }
+            });
+        }
+    });
+
+    // Upload with the configured provider
+    let history_for_upload = Arc::clone(&history);
+    let history_combo_for_upload = history_combo.clone();
+    let settings_for_upload = settings.clone();
+    upload_btn.connect_clicked(move |_| {
+        let Some(idx) = history_combo_for_upload.active() else { return };
+        let path = history_for_upload.lock().unwrap().get(idx as usize).and_then(|c| c.primary().cloned());
+        if let Some(path) = path {
+            let settings = settings_for_upload.borrow().clone();
+            glib::MainContext::default().spawn_local(async move {
+                crate::ui::toasts::show_toast("Uploading…");
+                match uploader::upload_and_copy(&settings, &path).await {
+                    Ok(link) => crate::ui::toasts::show_toast(&format!("Link copied: {}", link)),
+                    Err(e) => crate::ui::toasts::show_toast(&format!("Upload failed: {}", e)),
+                }
+            });
         }
     });
 
     clamp.set_child(Some(&vbox));
     content.append(&clamp);
-} 
\ No newline at end of file
+}
+
+// Capture to the cache, decode and show what was found. The capture is
+// deleted afterwards; only the payloads matter.
+async fn scan_codes(settings: &Settings, target: &CaptureTarget) {
+    let capture = match output_sinks::capture_for(settings, target, &[]).await {
+        Ok(capture) => capture,
+        Err(e) => {
+            crate::ui::toasts::show_toast(&format!("Screenshot failed: {}", e));
+            return;
+        }
+    };
+    let Some(path) = capture.primary() else { return };
+    let result = code_scanner::scan_file(path).await;
+    if let Err(e) = std::fs::remove_file(path) {
+        tracing::warn!("Failed to delete scanned capture {:?}: {}", path, e);
+    }
+    match result {
+        Ok(codes) if codes.is_empty() => crate::ui::toasts::show_toast("No QR code or barcode found"),
+        Ok(codes) => scan_results::show_codes(codes),
+        Err(e) => crate::ui::toasts::show_toast(&format!("Failed to scan: {}", e)),
+    }
+}
+
+// Redact every file of the staged capture in place. Returns false when it
+// must not be delivered: redaction failed or the capture was discarded in
+// the preview. The files are deleted then.
+async fn redact_capture(capture: &Capture, options: &RedactionOptions, languages: &str) -> bool {
+    let mut redactions = Vec::new();
+    for path in &capture.files {
+        match redaction::redact_file(path, options, languages).await {
+            Ok(found) => redactions.extend(found),
+            Err(e) => {
+                delete_capture(capture);
+                crate::ui::toasts::show_toast(&format!("Redaction failed, the screenshot was not delivered: {}", e));
+                return false;
+            }
+        }
+    }
+    if redactions.is_empty() {
+        return true;
+    }
+    if !options.preview {
+        crate::ui::toasts::show_toast(&format!("Redacted {} items", redactions.len()));
+        return true;
+    }
+    if redaction_preview::confirm(&capture.files, &redactions).await {
+        return true;
+    }
+    delete_capture(capture);
+    crate::ui::toasts::show_toast("Screenshot discarded");
+    false
+}
+
+fn delete_capture(capture: &Capture) {
+    for path in &capture.files {
+        if let Err(e) = std::fs::remove_file(path) {
+            tracing::warn!("Failed to delete discarded capture {:?}: {}", path, e);
+        }
+    }
+}
+
+// Add a capture to the history and show it in the preview
+fn push_history(history: &Arc<Mutex<Vec<Capture>>>, history_combo: &gtk::ComboBoxText, capture: Capture) {
+    history_combo.append_text(&capture.label());
+    let last = {
+        let mut guard = history.lock().unwrap();
+        guard.push(capture);
+        guard.len() - 1
+    };
+    // The changed handler locks history, so it must be released first
+    history_combo.set_active(Some(last as u32));
+}
+
+// Open the built-in editor. An annotated copy joins the history and the
+// gallery; an overwritten file is reloaded in the preview.
+async fn annotate_in_app(
+    path: PathBuf,
+    copy_dir: PathBuf,
+    history: Arc<Mutex<Vec<Capture>>>,
+    history_combo: gtk::ComboBoxText,
+) {
+    let Some(saved) = annotation_editor::annotate(&path, &copy_dir).await else { return };
+    if saved == path {
+        let active = history_combo.active();
+        history_combo.set_active(None);
+        history_combo.set_active(active);
+    } else {
+        if let Err(e) = gallery::record_copy(&path, &saved) {
+            tracing::warn!("Failed to update gallery index: {}", e);
+        }
+        push_history(&history, &history_combo, Capture::from(saved));
+    }
+    gallery_view::refresh();
+}
+
+fn sinks_for_mode(settings: &Settings, mode: &str) -> Vec<OutputSink> {
+    settings
+        .screenshot_sinks
+        .as_ref()
+        .and_then(|sinks| sinks.get(mode))
+        .cloned()
+        .unwrap_or_else(|| vec![OutputSink::Save])
+}
+
+fn processing_for_mode(settings: &Settings, mode: &str) -> ProcessOptions {
+    settings
+        .screenshot_processing
+        .as_ref()
+        .and_then(|processing| processing.get(mode))
+        .cloned()
+        .unwrap_or_default()
+}
+
+// OCR language, listing the installed tesseract languages. A combination
+// such as "eng+deu" set in settings.toml is kept as an extra entry.
+fn build_language_combo(settings: SharedSettings) -> gtk::ComboBoxText {
+    let combo = gtk::ComboBoxText::new();
+    combo.set_tooltip_text(Some("Language of the text to recognise"));
+    let current = settings.borrow().ocr_languages.clone().unwrap_or_else(|| ocr::DEFAULT_LANGUAGES.to_string());
+    combo.append(Some(&current), &current);
+    combo.set_active_id(Some(&current));
+
+    let combo_for_list = combo.clone();
+    glib::MainContext::default().spawn_local(async move {
+        let languages = match ocr::available_languages().await {
+            Ok(languages) => languages,
+            Err(e) => {
+                tracing::warn!("Failed to list tesseract languages: {}", e);
+                return;
+            }
+        };
+        for language in languages.iter().filter(|l| **l != current) {
+            combo_for_list.append(Some(language), language);
+        }
+        // Only save choices made after the list is filled
+        combo_for_list.connect_changed(move |combo| {
+            let Some(language) = combo.active_id() else { return };
+            if let Err(e) = update_settings(&settings, |s| s.ocr_languages = Some(language.to_string())) {
+                crate::ui::toasts::show_toast(&format!("Failed to save settings: {}", e));
+            }
+        });
+    });
+    combo
+}
+
+// Common post-processing options for one mode. Crop insets and frame
+// details are only in settings.toml and are kept as they are.
+fn build_processing_popover(mode: &'static str, settings: SharedSettings) -> gtk::Popover {
+    let current = processing_for_mode(&settings.borrow(), mode);
+    let grid = gtk::Grid::new();
+    grid.set_row_spacing(6);
+    grid.set_column_spacing(8);
+    grid.set_margin_top(8);
+    grid.set_margin_bottom(8);
+    grid.set_margin_start(8);
+    grid.set_margin_end(8);
+    let add_row = |row: i32, label: &str, widget: &gtk::Widget| {
+        let label = gtk::Label::new(Some(label));
+        label.set_xalign(0.0);
+        grid.attach(&label, 0, row, 1, 1);
+        grid.attach(widget, 1, row, 1, 1);
+    };
+
+    let format_combo = gtk::ComboBoxText::new();
+    for format in OutputFormat::ALL {
+        format_combo.append(Some(format.extension()), format.label());
+    }
+    format_combo.set_active_id(Some(current.format.extension()));
+    add_row(0, "Format", format_combo.upcast_ref());
+    let quality_spin = gtk::SpinButton::with_range(1.0, 100.0, 1.0);
+    quality_spin.set_value(current.quality as f64);
+    quality_spin.set_tooltip_text(Some("JPEG, WebP and AVIF; 100 makes WebP lossless"));
+    add_row(1, "Quality", quality_spin.upcast_ref());
+    let width_spin = gtk::SpinButton::with_range(0.0, 16384.0, 1.0);
+    width_spin.set_value(current.max_width.unwrap_or(0) as f64);
+    width_spin.set_tooltip_text(Some("0 keeps the captured width"));
+    add_row(2, "Max width", width_spin.upcast_ref());
+    let height_spin = gtk::SpinButton::with_range(0.0, 16384.0, 1.0);
+    height_spin.set_value(current.max_height.unwrap_or(0) as f64);
+    height_spin.set_tooltip_text(Some("0 keeps the captured height"));
+    add_row(3, "Max height", height_spin.upcast_ref());
+    let optimize_check = gtk::CheckButton::with_label("Optimise PNG (lossless)");
+    optimize_check.set_active(current.optimize_png);
+    grid.attach(&optimize_check, 0, 4, 2, 1);
+    let frame_check = gtk::CheckButton::with_label("Pretty frame");
+    frame_check.set_active(current.frame.is_some());
+    frame_check.set_tooltip_text(Some("Padding, rounded corners and a drop shadow on a background"));
+    grid.attach(&frame_check, 0, 5, 2, 1);
+
+    let update = {
+        let format_combo = format_combo.clone();
+        let quality_spin = quality_spin.clone();
+        let width_spin = width_spin.clone();
+        let height_spin = height_spin.clone();
+        let optimize_check = optimize_check.clone();
+        let frame_check = frame_check.clone();
+        Rc::new(move || {
+            let saved = update_settings(&settings, |s| {
+                let processing = s.screenshot_processing.get_or_insert_with(HashMap::new);
+                let options = processing.entry(mode.to_string()).or_default();
+                options.format = OutputFormat::ALL
+                    .into_iter()
+                    .find(|f| format_combo.active_id().as_deref() == Some(f.extension()))
+                    .unwrap_or_default();
+                options.quality = quality_spin.value() as u8;
+                options.max_width = Some(width_spin.value() as u32).filter(|w| *w > 0);
+                options.max_height = Some(height_spin.value() as u32).filter(|h| *h > 0);
+                options.optimize_png = optimize_check.is_active();
+                if !frame_check.is_active() {
+                    options.frame = None;
+                } else if options.frame.is_none() {
+                    options.frame = Some(FrameOptions::default());
+                }
+            });
+            if let Err(e) = saved {
+                crate::ui::toasts::show_toast(&format!("Failed to save settings: {}", e));
+            }
+        })
+    };
+    let update_for_format = update.clone();
+    format_combo.connect_changed(move |_| update_for_format());
+    for spin in [&quality_spin, &width_spin, &height_spin] {
+        let update = update.clone();
+        spin.connect_value_changed(move |_| update());
+    }
+    for check in [&optimize_check, &frame_check] {
+        let update = update.clone();
+        check.connect_toggled(move |_| update());
+    }
+
+    let popover = gtk::Popover::new();
+    popover.set_child(Some(&grid));
+    popover
+}
+
+// Toggles for one mode's output sinks. Sinks run in the order listed here,
+// so with several clipboard sinks an upload link wins over the image, and
+// the image over its URI.
+fn build_sinks_popover(mode: &'static str, settings: SharedSettings) -> gtk::Popover {
+    let current = sinks_for_mode(&settings.borrow(), mode);
+    let vbox = gtk::Box::new(gtk::Orientation::Vertical, 6);
+    vbox.set_margin_top(8);
+    vbox.set_margin_bottom(8);
+    vbox.set_margin_start(8);
+    vbox.set_margin_end(8);
+
+    let fixed = [OutputSink::Save, OutputSink::CopyUri, OutputSink::CopyImage, OutputSink::Upload, OutputSink::Edit];
+    let checks: Vec<(OutputSink, gtk::CheckButton)> = fixed
+        .into_iter()
+        .map(|sink| {
+            let check = gtk::CheckButton::with_label(sink.label());
+            check.set_active(current.contains(&sink));
+            vbox.append(&check);
+            (sink, check)
+        })
+        .collect();
+    let command_check = gtk::CheckButton::with_label(OutputSink::Command(String::new()).label());
+    let command_entry = gtk::Entry::new();
+    command_entry.set_placeholder_text(Some("e.g. notify-send Saved {file}"));
+    if let Some(OutputSink::Command(command)) = current.iter().find(|s| matches!(s, OutputSink::Command(_))) {
+        command_check.set_active(true);
+        command_entry.set_text(command);
+    }
+    vbox.append(&command_check);
+    vbox.append(&command_entry);
+
+    let update = {
+        let command_check = command_check.clone();
+        let command_entry = command_entry.clone();
+        let checks = checks.clone();
+        Rc::new(move || {
+            let mut selected: Vec<OutputSink> = checks
+                .iter()
+                .filter(|(_, check)| check.is_active())
+                .map(|(sink, _)| sink.clone())
+                .collect();
+            let command = command_entry.text().trim().to_string();
+            if command_check.is_active() && !command.is_empty() {
+                selected.push(OutputSink::Command(command));
+            }
+            let saved = update_settings(&settings, |s| {
+                s.screenshot_sinks.get_or_insert_with(HashMap::new).insert(mode.to_string(), selected);
+            });
+            if let Err(e) = saved {
+                crate::ui::toasts::show_toast(&format!("Failed to save settings: {}", e));
+            }
+        })
+    };
+    for (_, check) in &checks {
+        let update = update.clone();
+        check.connect_toggled(move |_| update());
+    }
+    let update_for_check = update.clone();
+    command_check.connect_toggled(move |_| update_for_check());
+    // Saved once the command is complete, not on every keystroke
+    let update_for_activate = update.clone();
+    command_entry.connect_activate(move |_| update_for_activate());
+    let focus = gtk::EventControllerFocus::new();
+    focus.connect_leave(move |_| update());
+    command_entry.add_controller(focus);
+
+    let popover = gtk::Popover::new();
+    popover.set_child(Some(&vbox));
+    popover
+}
+
+// Count down `seconds` in the Screenshots tab and as a desktop notification,
+// which stays visible while another window has focus. False if cancelled.
+async fn wait_for_delay(seconds: u64, countdown_box: &gtk::Box, countdown_label: &gtk::Label) -> bool {
+    let handle = DelayHandle::new();
+    // A newer capture supersedes one still counting down
+    if let Some(previous) = PENDING_DELAY.with(|pending| pending.replace(Some(handle.clone()))) {
+        previous.cancel();
+    }
+    let app = gtk::gio::Application::default();
+    let completed = screenshot::countdown(seconds, &handle, |remaining| {
+        let text = format!("Capturing in {}…", remaining);
+        countdown_label.set_text(&text);
+        countdown_box.set_visible(true);
+        if let Some(app) = app.as_ref() {
+            let notification = gtk::gio::Notification::new("Screenshot");
+            notification.set_body(Some(&text));
+            notification.add_button("Cancel", "app.cancel-screenshot");
+            app.send_notification(Some(COUNTDOWN_NOTIFICATION), &notification);
+        }
+    })
+    .await;
+
+    PENDING_DELAY.with(|pending| {
+        let mut pending = pending.borrow_mut();
+        if pending.as_ref().is_some_and(|p| p.same_as(&handle)) {
+            *pending = None;
+        }
+    });
+    countdown_box.set_visible(false);
+    if let Some(app) = app.as_ref() {
+        app.withdraw_notification(COUNTDOWN_NOTIFICATION);
+    }
+    if completed {
+        // Give the notification time to leave the screen before grim runs
+        glib::timeout_future(std::time::Duration::from_millis(300)).await;
+    }
+    completed
+}
+
+// Small dialog listing the connected monitors; None if dismissed
+async fn choose_monitor() -> Option<Monitor> {
+    let monitors = match monitor_control::get_monitors().await {
+        Ok(monitors) => monitors,
+        Err(e) => {
+            crate::ui::toasts::show_toast(&format!("Failed to load monitors: {}", e));
+            return None;
+        }
+    };
+    let window = adw::Window::new();
+    window.set_title(Some("Capture Monitor"));
+    window.set_modal(true);
+    window.set_default_size(420, -1);
+    MAIN_WINDOW.with(|w| {
+        if let Some(main_window) = w.borrow().as_ref() {
+            window.set_transient_for(Some(main_window));
+        }
+    });
+
+    let (sender, receiver) = oneshot::channel();
+    let sender = std::rc::Rc::new(std::cell::RefCell::new(Some(sender)));
+    let list = gtk::ListBox::new();
+    list.add_css_class("boxed-list");
+    list.set_selection_mode(gtk::SelectionMode::None);
+    list.set_margin_top(24);
+    list.set_margin_bottom(24);
+    list.set_margin_start(24);
+    list.set_margin_end(24);
+    for monitor in &monitors {
+        let row = adw::ActionRow::new();
+        row.set_title(&monitor.name);
+        row.set_subtitle(&glib::markup_escape_text(&format!(
+            "{}  ·  {}x{}",
+            monitor.description, monitor.width, monitor.height
+        )));
+        row.set_activatable(true);
+        list.append(&row);
+    }
+    let sender_for_row = sender.clone();
+    let window_for_row = window.clone();
+    list.connect_row_activated(move |_, row| {
+        if let Some(sender) = sender_for_row.borrow_mut().take() {
+            let _ = sender.send(monitors.get(row.index() as usize).cloned());
+        }
+        window_for_row.close();
+    });
+    window.set_content(Some(&list));
+    window.connect_close_request(move |_| {
+        if let Some(sender) = sender.borrow_mut().take() {
+            let _ = sender.send(None);
+        }
+        glib::Propagation::Proceed
+    });
+    window.present();
+
+    let monitor = receiver.await.ok().flatten();
+    // Let the dialog unmap before grim runs
+    glib::timeout_future(std::time::Duration::from_millis(150)).await;
+    monitor
+}
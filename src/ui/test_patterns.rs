@@ -0,0 +1,269 @@
+use crate::display_manager::monitor_types::Monitor;
+use gtk::cairo;
+use gtk::gdk;
+use gtk::glib;
+use gtk::prelude::*;
+use std::cell::Cell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+const IDENTIFY_DURATION: Duration = Duration::from_secs(4);
+// How long the pattern name stays on screen after switching
+const HINT_DURATION: Duration = Duration::from_secs(3);
+// Moving bar crosses the screen once per this many seconds
+const BAR_PERIOD_SECS: f64 = 2.0;
+const GRID_SPACING: f64 = 50.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestPattern {
+    White,
+    Black,
+    Red,
+    Green,
+    Blue,
+    Gradient,
+    Grid,
+    MovingBar,
+}
+
+impl TestPattern {
+    pub const ALL: [TestPattern; 8] = [
+        TestPattern::White,
+        TestPattern::Black,
+        TestPattern::Red,
+        TestPattern::Green,
+        TestPattern::Blue,
+        TestPattern::Gradient,
+        TestPattern::Grid,
+        TestPattern::MovingBar,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TestPattern::White => "White (dead pixels)",
+            TestPattern::Black => "Black (stuck pixels, backlight bleed)",
+            TestPattern::Red => "Red",
+            TestPattern::Green => "Green",
+            TestPattern::Blue => "Blue",
+            TestPattern::Gradient => "Gradients (banding)",
+            TestPattern::Grid => "Grid (geometry, scaling)",
+            TestPattern::MovingBar => "Moving bar (response time)",
+        }
+    }
+}
+
+// GDK's view of a Hyprland output, matched by connector name (e.g. "DP-1")
+fn gdk_monitor(name: &str) -> Option<gdk::Monitor> {
+    let display = gdk::Display::default()?;
+    let monitors = display.monitors();
+    (0..monitors.n_items())
+        .filter_map(|i| monitors.item(i).and_downcast::<gdk::Monitor>())
+        .find(|m| m.connector().as_deref() == Some(name))
+}
+
+// Undecorated window covering the given output
+fn fullscreen_window(output: &str) -> gtk::Window {
+    let window = gtk::Window::new();
+    window.set_decorated(false);
+    window.set_title(Some(&format!("hypr-xdisplay: {}", output)));
+    match gdk_monitor(output) {
+        Some(monitor) => window.fullscreen_on_monitor(&monitor),
+        None => window.fullscreen(),
+    }
+    window
+}
+
+fn show_centered_text(cr: &cairo::Context, text: &str, size: f64, x: f64, y: f64) {
+    cr.set_font_size(size);
+    if let Ok(extents) = cr.text_extents(text) {
+        cr.move_to(
+            x - extents.width() / 2.0 - extents.x_bearing(),
+            y - extents.height() / 2.0 - extents.y_bearing(),
+        );
+        let _ = cr.show_text(text);
+    }
+}
+
+// Show the output index and name in large type on every monitor for a few
+// seconds, so identical screens can be told apart. Indices follow the
+// order of the Monitors tab.
+pub fn identify_monitors(monitors: &[Monitor]) {
+    for (idx, monitor) in monitors.iter().enumerate() {
+        let window = fullscreen_window(&monitor.name);
+        let area = gtk::DrawingArea::new();
+        let index = (idx + 1).to_string();
+        let name = monitor.name.clone();
+        let details = format!(
+            "{}  ·  {}x{} @ {:.0}Hz  ·  scale {}",
+            monitor.description, monitor.width, monitor.height, monitor.refresh_rate, monitor.scale
+        );
+        area.set_draw_func(move |_, cr, width, height| {
+            let (w, h) = (width as f64, height as f64);
+            cr.set_source_rgb(0.11, 0.11, 0.13);
+            let _ = cr.paint();
+            cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+            cr.set_source_rgb(1.0, 1.0, 1.0);
+            show_centered_text(cr, &index, h * 0.4, w / 2.0, h * 0.38);
+            show_centered_text(cr, &name, h * 0.1, w / 2.0, h * 0.7);
+            cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+            cr.set_source_rgb(0.7, 0.7, 0.7);
+            show_centered_text(cr, &details, h * 0.03, w / 2.0, h * 0.82);
+        });
+        window.set_child(Some(&area));
+
+        // Any click or key dismisses early
+        let click = gtk::GestureClick::new();
+        let window_for_click = window.clone();
+        click.connect_released(move |_, _, _, _| window_for_click.close());
+        window.add_controller(click);
+        let key_controller = gtk::EventControllerKey::new();
+        let window_for_key = window.clone();
+        key_controller.connect_key_pressed(move |_, _, _, _| {
+            window_for_key.close();
+            glib::Propagation::Stop
+        });
+        window.add_controller(key_controller);
+
+        let window_for_timeout = window.clone();
+        glib::timeout_add_local_once(IDENTIFY_DURATION, move || window_for_timeout.close());
+        window.present();
+    }
+}
+
+fn draw_pattern(cr: &cairo::Context, pattern: TestPattern, w: f64, h: f64, elapsed: f64) {
+    match pattern {
+        TestPattern::White => cr.set_source_rgb(1.0, 1.0, 1.0),
+        TestPattern::Black => cr.set_source_rgb(0.0, 0.0, 0.0),
+        TestPattern::Red => cr.set_source_rgb(1.0, 0.0, 0.0),
+        TestPattern::Green => cr.set_source_rgb(0.0, 1.0, 0.0),
+        TestPattern::Blue => cr.set_source_rgb(0.0, 0.0, 1.0),
+        TestPattern::Gradient | TestPattern::Grid => cr.set_source_rgb(0.0, 0.0, 0.0),
+        TestPattern::MovingBar => cr.set_source_rgb(0.5, 0.5, 0.5),
+    }
+    let _ = cr.paint();
+
+    match pattern {
+        TestPattern::Gradient => {
+            // Black to full intensity ramps, one band per channel plus grey
+            let bands = [(1.0, 1.0, 1.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0), (0.0, 0.0, 1.0)];
+            let band_height = h / bands.len() as f64;
+            for (i, (r, g, b)) in bands.iter().enumerate() {
+                let gradient = cairo::LinearGradient::new(0.0, 0.0, w, 0.0);
+                gradient.add_color_stop_rgb(0.0, 0.0, 0.0, 0.0);
+                gradient.add_color_stop_rgb(1.0, *r, *g, *b);
+                if cr.set_source(&gradient).is_ok() {
+                    cr.rectangle(0.0, i as f64 * band_height, w, band_height);
+                    let _ = cr.fill();
+                }
+            }
+        }
+        TestPattern::Grid => {
+            cr.set_source_rgb(1.0, 1.0, 1.0);
+            cr.set_line_width(1.0);
+            // Offset by half a pixel so 1px lines land on whole pixels
+            let mut x = 0.5;
+            while x < w {
+                cr.move_to(x, 0.0);
+                cr.line_to(x, h);
+                x += GRID_SPACING;
+            }
+            let mut y = 0.5;
+            while y < h {
+                cr.move_to(0.0, y);
+                cr.line_to(w, y);
+                y += GRID_SPACING;
+            }
+            let _ = cr.stroke();
+            // Border, diagonals and a centred circle show overscan and
+            // non-square scaling
+            cr.set_source_rgb(1.0, 0.2, 0.2);
+            cr.set_line_width(2.0);
+            cr.rectangle(1.0, 1.0, w - 2.0, h - 2.0);
+            cr.move_to(0.0, 0.0);
+            cr.line_to(w, h);
+            cr.move_to(w, 0.0);
+            cr.line_to(0.0, h);
+            let _ = cr.stroke();
+            cr.arc(w / 2.0, h / 2.0, w.min(h) * 0.4, 0.0, 2.0 * PI);
+            let _ = cr.stroke();
+        }
+        TestPattern::MovingBar => {
+            let bar_width = (w * 0.05).max(8.0);
+            let progress = (elapsed / BAR_PERIOD_SECS).fract();
+            let x = progress * (w + bar_width) - bar_width;
+            cr.set_source_rgb(1.0, 1.0, 1.0);
+            cr.rectangle(x, 0.0, bar_width, h);
+            let _ = cr.fill();
+        }
+        _ => {}
+    }
+}
+
+// Fullscreen test patterns on one output. Click, Space or the arrow keys
+// cycle through patterns; Escape closes.
+pub fn show_test_patterns(output: &str) {
+    let window = fullscreen_window(output);
+    let area = gtk::DrawingArea::new();
+    let current = Rc::new(Cell::new(0usize));
+    let switched_at = Rc::new(Cell::new(Instant::now()));
+    let started = Instant::now();
+
+    let current_for_draw = current.clone();
+    let switched_for_draw = switched_at.clone();
+    area.set_draw_func(move |_, cr, width, height| {
+        let (w, h) = (width as f64, height as f64);
+        let pattern = TestPattern::ALL[current_for_draw.get()];
+        draw_pattern(cr, pattern, w, h, started.elapsed().as_secs_f64());
+        if switched_for_draw.get().elapsed() < HINT_DURATION {
+            let hint = format!("{}  —  ←/→ or click to switch, Esc to close", pattern.label());
+            cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+            cr.set_font_size(20.0);
+            if let Ok(extents) = cr.text_extents(&hint) {
+                let (bw, bh) = (extents.width() + 32.0, 44.0);
+                cr.set_source_rgba(0.0, 0.0, 0.0, 0.7);
+                cr.rectangle((w - bw) / 2.0, h - bh - 32.0, bw, bh);
+                let _ = cr.fill();
+            }
+            cr.set_source_rgb(1.0, 1.0, 1.0);
+            show_centered_text(cr, &hint, 20.0, w / 2.0, h - 54.0);
+        }
+    });
+    // Redraw every frame: drives the moving bar and hides the hint
+    area.add_tick_callback(|area, _| {
+        area.queue_draw();
+        glib::ControlFlow::Continue
+    });
+    window.set_child(Some(&area));
+
+    let step = {
+        let current = current.clone();
+        let switched_at = switched_at.clone();
+        move |forward: bool| {
+            let len = TestPattern::ALL.len();
+            let next = if forward { (current.get() + 1) % len } else { (current.get() + len - 1) % len };
+            current.set(next);
+            switched_at.set(Instant::now());
+        }
+    };
+
+    let click = gtk::GestureClick::new();
+    let step_for_click = step.clone();
+    click.connect_released(move |_, _, _, _| step_for_click(true));
+    window.add_controller(click);
+
+    let key_controller = gtk::EventControllerKey::new();
+    let window_for_key = window.clone();
+    key_controller.connect_key_pressed(move |_, keyval, _, _| {
+        match keyval {
+            gdk::Key::Escape | gdk::Key::q => window_for_key.close(),
+            gdk::Key::Right | gdk::Key::space | gdk::Key::Return => step(true),
+            gdk::Key::Left | gdk::Key::BackSpace => step(false),
+            _ => return glib::Propagation::Proceed,
+        }
+        glib::Propagation::Stop
+    });
+    window.add_controller(key_controller);
+
+    window.present();
+}
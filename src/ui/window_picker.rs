@@ -0,0 +1,128 @@
+use crate::screenshot::{self, Client};
+use crate::ui::main_window::MAIN_WINDOW;
+use crate::ui::region_selector;
+use futures::channel::oneshot;
+use gtk::gdk;
+use gtk::glib;
+use libadwaita as adw;
+use adw::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Let the user choose a window to capture, from a list of everything on
+// the visible workspaces or by clicking it on screen. None if cancelled.
+pub async fn choose_window() -> Option<Client> {
+    let clients = match screenshot::visible_clients().await {
+        Ok(clients) => clients,
+        Err(e) => {
+            crate::ui::toasts::show_toast(&format!("Failed to list windows: {}", e));
+            return None;
+        }
+    };
+    if clients.is_empty() {
+        crate::ui::toasts::show_toast("No windows on the visible workspaces");
+        return None;
+    }
+
+    match present_picker(clients).await {
+        Some(Choice::Window(client)) => {
+            // Let the picker unmap before grim runs
+            glib::timeout_future(std::time::Duration::from_millis(150)).await;
+            Some(client)
+        }
+        Some(Choice::ClickToPick) => region_selector::pick_window().await,
+        None => None,
+    }
+}
+
+enum Choice {
+    Window(Client),
+    ClickToPick,
+}
+
+async fn present_picker(clients: Vec<Client>) -> Option<Choice> {
+    let window = adw::Window::new();
+    window.set_title(Some("Capture Window"));
+    window.set_modal(true);
+    window.set_default_size(520, 480);
+    MAIN_WINDOW.with(|w| {
+        if let Some(main_window) = w.borrow().as_ref() {
+            window.set_transient_for(Some(main_window));
+        }
+    });
+
+    let (sender, receiver) = oneshot::channel();
+    let sender = Rc::new(RefCell::new(Some(sender)));
+    let choose = {
+        let window = window.clone();
+        let sender = sender.clone();
+        move |choice: Option<Choice>| {
+            if let Some(sender) = sender.borrow_mut().take() {
+                let _ = sender.send(choice);
+            }
+            window.close();
+        }
+    };
+
+    let vbox = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    vbox.set_margin_top(24);
+    vbox.set_margin_bottom(24);
+    vbox.set_margin_start(24);
+    vbox.set_margin_end(24);
+
+    let pick_btn = gtk::Button::with_label("Click to Pick");
+    pick_btn.set_tooltip_text(Some("Choose a window by clicking it on screen"));
+    pick_btn.add_css_class("suggested-action");
+    let choose_for_pick = choose.clone();
+    pick_btn.connect_clicked(move |_| choose_for_pick(Some(Choice::ClickToPick)));
+    vbox.append(&pick_btn);
+
+    let list = gtk::ListBox::new();
+    list.add_css_class("boxed-list");
+    list.set_selection_mode(gtk::SelectionMode::None);
+    for client in &clients {
+        let row = adw::ActionRow::new();
+        let title = if client.title.is_empty() { client.class.clone() } else { client.title.clone() };
+        row.set_title(&glib::markup_escape_text(&title));
+        row.set_subtitle(&glib::markup_escape_text(&format!(
+            "{}  ·  workspace {}  ·  {}×{}",
+            client.class, client.workspace.name, client.size[0], client.size[1]
+        )));
+        row.set_activatable(true);
+        list.append(&row);
+    }
+    let choose_for_row = choose.clone();
+    list.connect_row_activated(move |_, row| {
+        if let Some(client) = clients.get(row.index() as usize) {
+            choose_for_row(Some(Choice::Window(client.clone())));
+        }
+    });
+    let scrolled = gtk::ScrolledWindow::new();
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&list));
+    vbox.append(&scrolled);
+    window.set_content(Some(&vbox));
+
+    let key_controller = gtk::EventControllerKey::new();
+    let choose_for_key = choose.clone();
+    key_controller.connect_key_pressed(move |_, keyval, _, _| {
+        if keyval == gdk::Key::Escape {
+            choose_for_key(None);
+            glib::Propagation::Stop
+        } else {
+            glib::Propagation::Proceed
+        }
+    });
+    window.add_controller(key_controller);
+
+    // Closing the window any other way cancels
+    window.connect_close_request(move |_| {
+        if let Some(sender) = sender.borrow_mut().take() {
+            let _ = sender.send(None);
+        }
+        glib::Propagation::Proceed
+    });
+
+    window.present();
+    receiver.await.ok().flatten()
+}
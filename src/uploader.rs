@@ -0,0 +1,367 @@
+use crate::output_sinks;
+use crate::settings::Settings;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+// Where uploads go, configured by name under `[upload_providers.<name>]`.
+// Secrets never live here; see `Credentials`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UploadProvider {
+    // multipart/form-data POST; `url_path` is a JSONPath into the response
+    // such as `$.data.link`. Without one the body is taken as the link.
+    Http {
+        url: String,
+        field: String,
+        url_path: Option<String>,
+    },
+    // SigV4-signed PUT to `{endpoint}/{bucket}/{file name}`. `public_url`
+    // replaces `{endpoint}/{bucket}` in the returned link, e.g. for a CDN.
+    S3 {
+        endpoint: String,
+        bucket: String,
+        region: String,
+        public_url: Option<String>,
+    },
+    // Shell command in the `output_sinks` template syntax; the last line
+    // it prints is the link
+    Command {
+        command: String,
+    },
+}
+
+// Secrets for one provider, kept in credentials.toml beside settings.toml
+// and readable by the owner only:
+//
+//   [imgur]
+//   headers = { Authorization = "Client-ID 1234" }
+//   [s3]
+//   access_key = "..."
+//   secret_key = "..."
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Credentials {
+    pub access_key: Option<String>,
+    pub secret_key: Option<String>,
+    pub session_token: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+pub fn credentials_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("hypr-xdisplay")
+        .join("credentials.toml")
+}
+
+pub fn load_credentials(provider: &str) -> Result<Credentials> {
+    let path = credentials_path();
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Credentials::default()),
+        Err(e) => return Err(e.into()),
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if fs::metadata(&path)?.permissions().mode() & 0o077 != 0 {
+            warn!("{:?} is readable by other users; consider chmod 600", path);
+        }
+    }
+    let mut all: HashMap<String, Credentials> = toml::from_str(&text)?;
+    Ok(all.remove(provider).unwrap_or_default())
+}
+
+// Minimal JSONPath: `$`, `.key`, `['key']` and `[index]`
+pub fn json_path<'a>(value: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    let mut rest = path.trim().strip_prefix('$').unwrap_or(path.trim());
+    let mut current = value;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            current = current.get(&after[..end])?;
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']')?;
+            let key = &after[..end];
+            current = match key.trim_matches(|c| c == '\'' || c == '"') {
+                quoted if quoted.len() != key.len() => current.get(quoted)?,
+                index => current.get(index.parse::<usize>().ok()?)?,
+            };
+            rest = &after[end + 1..];
+        } else {
+            return None;
+        }
+    }
+    Some(current)
+}
+
+// Quote a value for a curl config file
+fn curl_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Run curl with secrets passed as a config file on stdin, so they never
+// appear in the process list. Returns the response body.
+async fn curl(args: &[String], config: &[String]) -> Result<String> {
+    let mut child = Command::new("curl")
+        .arg("--silent")
+        .arg("--show-error")
+        .arg("--fail-with-body")
+        .arg("--config")
+        .arg("-")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.join("\n").as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+    let body = String::from_utf8_lossy(&output.stdout).to_string();
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!("upload failed: {} {}", stderr.trim(), body.trim()));
+    }
+    Ok(body)
+}
+
+fn header_config(credentials: &Credentials) -> Vec<String> {
+    credentials
+        .headers
+        .iter()
+        .map(|(name, value)| format!("header = {}", curl_quote(&format!("{}: {}", name, value))))
+        .collect()
+}
+
+// Object key for a file: its name with anything outside the unreserved set
+// percent-encoded
+fn object_key(path: &Path) -> String {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    output_sinks::file_uri(Path::new(&name)).trim_start_matches("file://").to_string()
+}
+
+// Upload one file and return its link
+pub async fn upload(provider: &UploadProvider, credentials: &Credentials, path: &Path) -> Result<String> {
+    info!("Uploading {:?}", path);
+    let file = path.to_string_lossy().replace('\\', "\\\\").replace('"', "\\\"");
+    match provider {
+        UploadProvider::Http { url, field, url_path } => {
+            let args = vec!["--form".to_string(), format!("{}=@\"{}\"", field, file), url.clone()];
+            let body = curl(&args, &header_config(credentials)).await?;
+            let Some(url_path) = url_path else {
+                return Ok(body.trim().to_string());
+            };
+            let response: serde_json::Value = serde_json::from_str(&body)?;
+            json_path(&response, url_path)
+                .and_then(|link| link.as_str())
+                .map(str::to_string)
+                .ok_or_else(|| anyhow::anyhow!("no link at {} in the response", url_path))
+        }
+        UploadProvider::S3 { endpoint, bucket, region, public_url } => {
+            let (Some(access_key), Some(secret_key)) = (&credentials.access_key, &credentials.secret_key) else {
+                return Err(anyhow::anyhow!("missing access_key/secret_key in {:?}", credentials_path()));
+            };
+            let key = object_key(path);
+            let base = format!("{}/{}", endpoint.trim_end_matches('/'), bucket);
+            let mut config = header_config(credentials);
+            config.push(format!("user = {}", curl_quote(&format!("{}:{}", access_key, secret_key))));
+            if let Some(token) = &credentials.session_token {
+                config.push(format!("header = {}", curl_quote(&format!("x-amz-security-token: {}", token))));
+            }
+            let args = vec![
+                "--aws-sigv4".to_string(),
+                format!("aws:amz:{}:s3", region),
+                "--header".to_string(),
+                format!("Content-Type: {}", content_type(path)),
+                "--upload-file".to_string(),
+                path.to_string_lossy().to_string(),
+                format!("{}/{}", base, key),
+            ];
+            curl(&args, &config).await?;
+            let public = public_url.as_deref().map(|p| p.trim_end_matches('/').to_string()).unwrap_or(base);
+            Ok(format!("{}/{}", public, key))
+        }
+        UploadProvider::Command { command } => {
+            let line = output_sinks::command_line(command, &[path.to_path_buf()]);
+            let output = Command::new("sh").arg("-c").arg(&line).output().await?;
+            if !output.status.success() {
+                return Err(anyhow::anyhow!("`{}` exited with {}", command, output.status));
+            }
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .rev()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(str::to_string)
+                .ok_or_else(|| anyhow::anyhow!("`{}` printed no link", command))
+        }
+    }
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref() {
+        Some("mp4") => "video/mp4",
+        Some("mkv") => "video/x-matroska",
+        Some("webm") => "video/webm",
+        _ => output_sinks::image_mime_type(path),
+    }
+}
+
+// Upload with the provider chosen in settings and put the link on the clipboard
+pub async fn upload_and_copy(settings: &Settings, path: &Path) -> Result<String> {
+    let name = settings
+        .upload_provider
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("no upload provider configured"))?;
+    let provider = settings
+        .upload_providers
+        .as_ref()
+        .and_then(|providers| providers.get(name))
+        .ok_or_else(|| anyhow::anyhow!("unknown upload provider {}", name))?;
+    let credentials = load_credentials(name)?;
+    let link = upload(provider, &credentials, path).await?;
+    info!("Uploaded {:?} to {}", path, link);
+    if let Err(e) = output_sinks::copy_text(&link).await {
+        warn!("Failed to copy {} to the clipboard: {}", link, e);
+    }
+    Ok(link)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    // Answers one request with `body` and hands back the request as text
+    async fn stand_in_server(body: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            loop {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let length = text[..end]
+                        .lines()
+                        .filter_map(|line| line.split_once(':'))
+                        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if request.len() >= end + 4 + length {
+                        break;
+                    }
+                }
+                if read == 0 {
+                    break;
+                }
+            }
+            let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+        (url, server)
+    }
+
+    fn test_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hypr-xdisplay-upload-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, b"not really a png").unwrap();
+        path
+    }
+
+    #[test]
+    fn json_path_finds_links() {
+        let response: serde_json::Value =
+            serde_json::from_str(r#"{"data": {"link": "https://i.example/a.png", "files": [{"url": "u0"}, {"url": "u1"}]}}"#)
+                .unwrap();
+        assert_eq!(json_path(&response, "$.data.link").and_then(|v| v.as_str()), Some("https://i.example/a.png"));
+        assert_eq!(json_path(&response, "$['data'].files[1].url").and_then(|v| v.as_str()), Some("u1"));
+        assert_eq!(json_path(&response, "$.data.missing"), None);
+        assert_eq!(json_path(&response, "$.data.files[x]"), None);
+    }
+
+    #[tokio::test]
+    async fn http_posts_the_form_and_reads_the_link() {
+        let (url, server) = stand_in_server(r#"{"data": {"link": "https://i.example/a.png"}}"#).await;
+        let provider = UploadProvider::Http { url, field: "image".to_string(), url_path: Some("$.data.link".to_string()) };
+        let credentials = Credentials {
+            headers: HashMap::from([("Authorization".to_string(), "Client-ID 1234".to_string())]),
+            ..Default::default()
+        };
+        let path = test_file("shot.png");
+
+        let link = upload(&provider, &credentials, &path).await.unwrap();
+        assert_eq!(link, "https://i.example/a.png");
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST / HTTP/1.1\r\n"));
+        assert!(request.contains("Authorization: Client-ID 1234\r\n"));
+        assert!(request.contains(r#"name="image"; filename="shot.png""#));
+        assert!(request.contains("not really a png"));
+    }
+
+    #[tokio::test]
+    async fn s3_puts_a_signed_object() {
+        let (endpoint, server) = stand_in_server("").await;
+        let provider = UploadProvider::S3 {
+            endpoint: format!("{}/", endpoint),
+            bucket: "shots".to_string(),
+            region: "eu-west-1".to_string(),
+            public_url: Some("https://cdn.example/".to_string()),
+        };
+        let credentials = Credentials {
+            access_key: Some("AKID".to_string()),
+            secret_key: Some("secret".to_string()),
+            session_token: Some("token".to_string()),
+            ..Default::default()
+        };
+        let path = test_file("my shot.png");
+
+        let link = upload(&provider, &credentials, &path).await.unwrap();
+        assert_eq!(link, "https://cdn.example/my%20shot.png");
+        let request = server.await.unwrap();
+        assert!(request.starts_with("PUT /shots/my%20shot.png HTTP/1.1\r\n"));
+        assert!(request.contains("Authorization: AWS4-HMAC-SHA256 Credential=AKID/"));
+        assert!(request.contains("/eu-west-1/s3/aws4_request"));
+        assert!(request.contains("x-amz-security-token: token\r\n"));
+        assert!(request.contains("Content-Type: image/png\r\n"));
+        assert!(!request.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn s3_needs_keys() {
+        let provider = UploadProvider::S3 {
+            endpoint: "http://127.0.0.1:1".to_string(),
+            bucket: "shots".to_string(),
+            region: "eu-west-1".to_string(),
+            public_url: None,
+        };
+        assert!(upload(&provider, &Credentials::default(), &test_file("keys.png")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn command_link_is_the_last_line() {
+        let provider = UploadProvider::Command { command: "echo uploading {file}; echo https://x.example/1; echo".to_string() };
+        let link = upload(&provider, &Credentials::default(), &test_file("cmd.png")).await.unwrap();
+        assert_eq!(link, "https://x.example/1");
+
+        let silent = UploadProvider::Command { command: "true".to_string() };
+        assert!(upload(&silent, &Credentials::default(), &test_file("cmd.png")).await.is_err());
+        let failing = UploadProvider::Command { command: "exit 3".to_string() };
+        assert!(upload(&failing, &Credentials::default(), &test_file("cmd.png")).await.is_err());
+    }
+}